@@ -1,51 +1,223 @@
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::block::Block;
+use crate::blockchain::Blockchain;
+use crate::did::DidRegistry;
+use crate::environment::EnvironmentalData;
+use crate::insights::{self, Insight, InsightSources};
+use crate::fraud_detection::{self, FraudDetectionConfig, FraudReport, FraudSignal, FraudSignalKind, RiskAssessment};
+use crate::model_registry::{FeatureVector, ModelInfo, ModelRegistry, ModelTask, Prediction};
+use crate::threat_detection::{ThreatAlert, ThreatDetector};
+use crate::transaction::Transaction;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Anomaly score at or above which an environmental reading is reported.
+pub const ANOMALY_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiAnalytics {
     pub name: String,
     pub version: String,
     pub data_points: usize,
-    pub insights: Vec<String>,
+    pub window_start: u64,
+    pub window_end: u64,
+    pub insights: Vec<Insight>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentalAnomaly {
+    pub data: EnvironmentalData,
+    pub prediction: Prediction,
 }
 
 pub struct AiModule {
-    active_models: Vec<String>,
+    models: ModelRegistry,
     analytics: Vec<AiAnalytics>,
     threat_detection_enabled: bool,
+    threats: ThreatDetector,
+    fraud_config: FraudDetectionConfig,
 }
 
 impl AiModule {
     pub fn new() -> Self {
         AiModule {
-            active_models: vec!["FraudDetectionAI".to_string(), "OptimizationAI".to_string()],
+            models: ModelRegistry::default(),
             analytics: vec![],
             threat_detection_enabled: true,
+            threats: ThreatDetector::default(),
+            fraud_config: FraudDetectionConfig::default(),
         }
     }
 
-    pub fn run_fraud_detection(&self) -> bool {
-        // Placeholder for AI-driven fraud detection logic
+    /// Scores every marketplace sale on chain or pending.
+    pub fn run_fraud_detection(&self, blockchain: &Blockchain, dids: &DidRegistry, now: u64) -> FraudReport {
         println!("🔍 Running AI-driven fraud detection...");
-        true // Simulating a detection success
+        fraud_detection::scan_chain(&self.fraud_config, blockchain, dids, now)
+    }
+
+    /// Scores a sale before it is recorded. `None` if `tx` is not a marketplace transaction.
+    /// A loaded fraud scoring model adds its own signal when it rates the sale as risky.
+    pub fn assess_transaction(&self, blockchain: &Blockchain, dids: &DidRegistry, tx: &Transaction) -> Option<RiskAssessment> {
+        let mut assessment = fraud_detection::assess_transaction(&self.fraud_config, blockchain, dids, tx)?;
+        if let Some(prediction) = self.score_fraud(blockchain, tx, &assessment).filter(|prediction| prediction.from_model) {
+            if f64::from(prediction.score) >= self.fraud_config.hold_score {
+                let signal = FraudSignal {
+                    kind: FraudSignalKind::ModelScore,
+                    weight: f64::from(prediction.score),
+                    detail: format!("Fraud model {} scored the sale {:.2}", prediction.model_version, prediction.score),
+                };
+                assessment.add_signal(signal, &self.fraud_config);
+            }
+        }
+        Some(assessment)
+    }
+
+    /// Runs the fraud scoring model, or its fallback, on a sale.
+    pub fn score_fraud(&self, blockchain: &Blockchain, tx: &Transaction, assessment: &RiskAssessment) -> Option<Prediction> {
+        let history = fraud_detection::recorded_transactions(blockchain);
+        FeatureVector::for_sale(tx, &history, assessment).map(|features| self.models.predict(&features))
+    }
+
+    /// Scores every environmental reading on chain and returns those at or above `ANOMALY_THRESHOLD`.
+    pub fn detect_environmental_anomalies(&self, blockchain: &Blockchain) -> Vec<EnvironmentalAnomaly> {
+        let readings = blockchain.get_environmental_data();
+        readings
+            .iter()
+            .map(|data| EnvironmentalAnomaly {
+                prediction: self.models.predict(&FeatureVector::for_environment(data, &readings)),
+                data: data.clone(),
+            })
+            .filter(|anomaly| anomaly.prediction.score >= ANOMALY_THRESHOLD)
+            .collect()
+    }
+
+    /// Loads the latest model for each task from `dir`.
+    pub fn load_models(&mut self, dir: &Path) -> Result<Vec<ModelInfo>, String> {
+        let loaded = self.models.load_dir(dir)?;
+        for info in &loaded {
+            println!("🧠 Loaded {:?} model {} ({} layers)", info.task, info.version, info.layers);
+        }
+        Ok(loaded)
     }
 
-    pub fn generate_insights(&mut self) {
-        // Simulate generating insights
-        let insights = vec!["Optimize resource allocation".to_string(), "Enhance transaction efficiency".to_string()];
-        let analytics = AiAnalytics {
-            name: "ResourceAllocationAI".to_string(),
-            version: "1.0".to_string(),
-            data_points: 1000,
+    pub fn get_model_registry(&self) -> &ModelRegistry {
+        &self.models
+    }
+
+    pub fn get_model_registry_mut(&mut self) -> &mut ModelRegistry {
+        &mut self.models
+    }
+
+    pub fn get_fraud_config(&self) -> &FraudDetectionConfig {
+        &self.fraud_config
+    }
+
+    pub fn set_fraud_config(&mut self, config: FraudDetectionConfig) {
+        self.fraud_config = config;
+    }
+
+    /// Computes insights from chain data in `[window_start, window_end)` and keeps them.
+    pub fn generate_insights(&mut self, blockchain: &Blockchain, sources: &InsightSources, window_start: u64, window_end: u64) -> &AiAnalytics {
+        let (insights, data_points) = insights::generate_insights(blockchain, sources, window_start, window_end);
+        println!("📊 Insights generated by AI: {} insights from {} transactions", insights.len(), data_points);
+        self.analytics.push(AiAnalytics {
+            name: "MarketplaceInsightsAI".to_string(),
+            version: "2.0".to_string(),
+            data_points,
+            window_start,
+            window_end,
             insights,
-        };
-        self.analytics.push(analytics);
-        println!("📊 Insights generated by AI: Optimize resource allocation, Enhance transaction efficiency");
+        });
+        self.analytics.last().unwrap()
+    }
+
+    pub fn get_analytics(&self) -> &[AiAnalytics] {
+        &self.analytics
+    }
+
+    /// Loaded models as `<task>-<version>`; tasks without a model use their fallback.
+    pub fn get_active_models(&self) -> Vec<String> {
+        [ModelTask::FraudScoring, ModelTask::AnomalyDetection]
+            .into_iter()
+            .filter_map(|task| self.models.get_model(task))
+            .map(|info| format!("{}-{}", info.task.file_prefix(), info.version))
+            .collect()
     }
 
     pub fn enable_threat_detection(&mut self) {
         self.threat_detection_enabled = true;
         println!("🔒 AI threat detection enabled.");
     }
+
+    /// Stops screening inbound activity; bans already imposed are not enforced while off.
+    pub fn disable_threat_detection(&mut self) {
+        self.threat_detection_enabled = false;
+        println!("🔓 AI threat detection disabled.");
+    }
+
+    pub fn is_threat_detection_enabled(&self) -> bool {
+        self.threat_detection_enabled
+    }
+
+    /// Screens a transaction submitted to the mempool by `source`. Fails if the
+    /// signature is invalid and, while detection is enabled, if the source is
+    /// banned or rate limited or the submission trips spam detection.
+    pub fn screen_transaction(&mut self, source: &str, tx: &Transaction, signature_valid: bool, now: u64) -> Result<(), String> {
+        // Invalid signatures are rejected even with detection off; only the penalties are optional
+        if self.threat_detection_enabled {
+            self.threats.check_source(source, now)?;
+        }
+        if !signature_valid {
+            if self.threat_detection_enabled {
+                let alert = self.threats.observe_invalid_signature(source, &tx.id, now);
+                Self::report_threat(alert)?;
+            }
+            return Err(format!("Transaction {} from {} has an invalid signature", tx.id, source));
+        }
+        if !self.threat_detection_enabled {
+            return Ok(());
+        }
+        Self::report_threat(self.threats.observe_transaction(source, tx, now))
+    }
+
+    /// Screens a block announced by `source` before it is considered for the chain.
+    pub fn screen_block(&mut self, source: &str, block: &Block, blockchain: &Blockchain, now: u64) -> Result<(), String> {
+        if !self.threat_detection_enabled {
+            return Ok(());
+        }
+        self.threats.check_source(source, now)?;
+        Self::report_threat(self.threats.observe_block(source, block, blockchain, now))
+    }
+
+    /// Screens a merchant profile registration by `did`.
+    pub fn screen_registration(&mut self, did: &str, now: u64) -> Result<(), String> {
+        if !self.threat_detection_enabled {
+            return Ok(());
+        }
+        self.threats.check_source(did, now)?;
+        Self::report_threat(self.threats.observe_registration(did, now))
+    }
+
+    pub fn get_threat_alerts(&self) -> &[ThreatAlert] {
+        self.threats.get_alerts()
+    }
+
+    pub fn get_threat_detector(&self) -> &ThreatDetector {
+        &self.threats
+    }
+
+    pub fn get_threat_detector_mut(&mut self) -> &mut ThreatDetector {
+        &mut self.threats
+    }
+
+    fn report_threat(alert: Option<ThreatAlert>) -> Result<(), String> {
+        match alert {
+            Some(alert) => {
+                println!("🚨 {:?} from {}: {} ({:?})", alert.kind, alert.source, alert.detail, alert.response);
+                Err(alert.detail)
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 impl Default for AiModule {
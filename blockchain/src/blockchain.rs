@@ -1,6 +1,10 @@
+use std::collections::{HashMap, HashSet};
 use crate::block::Block;
-use crate::transaction::Transaction;
-use std::collections::HashMap;
+use crate::transaction::{Transaction, TransactionType};
+use crate::treasury::{TreasurySource, TREASURY_ADDRESS};
+
+pub const DEFAULT_MINING_REWARD: f64 = 100.0;
+pub const DEFAULT_TREASURY_SHARE: f64 = 0.10;
 
 #[derive(Debug, Clone)]
 pub struct Blockchain {
@@ -8,7 +12,9 @@ pub struct Blockchain {
     difficulty: usize,
     pending_transactions: Vec<Transaction>,
     mining_reward: f64,
-    // validator_stakes: HashMap<String, f64>,
+    treasury_share: f64, // share of each block reward paid into the treasury
+    validator_stakes: HashMap<String, f64>,
+    released_disbursements: HashSet<String>, // ids of mined transactions the treasury accepted
 }
 
 impl Blockchain {
@@ -17,7 +23,10 @@ impl Blockchain {
             chain: Vec::new(),
             difficulty: 4,
             pending_transactions: Vec::new(),
-            mining_reward: 100.0,
+            mining_reward: DEFAULT_MINING_REWARD,
+            treasury_share: DEFAULT_TREASURY_SHARE,
+            validator_stakes: HashMap::new(),
+            released_disbursements: HashSet::new(),
         };
         
         // Create the genesis block
@@ -45,17 +54,28 @@ impl Blockchain {
     }
     
     pub fn mine_pending_transactions(&mut self, mining_reward_address: String) {
-        // Add mining reward transaction
+        let index = self.chain.len();
+        
+        // Add mining reward transaction, less the treasury's share
+        let treasury_amount = self.mining_reward * self.treasury_share;
         let reward_tx = Transaction::new(
             mining_reward_address,
             crate::transaction::TransactionType::MiningReward {
-                amount: self.mining_reward,
+                amount: self.mining_reward - treasury_amount,
             },
         );
         self.pending_transactions.push(reward_tx);
+        if treasury_amount > 0.0 {
+            self.pending_transactions.push(Transaction::new(
+                TREASURY_ADDRESS.to_string(),
+                TransactionType::TreasuryDeposit {
+                    amount: treasury_amount,
+                    source: TreasurySource::BlockReward { block_index: index as u64 },
+                },
+            ));
+        }
         
         // Create new block
-        let index = self.chain.len();
         let previous_hash = self.get_latest_block().unwrap().hash.clone();
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -75,30 +95,51 @@ impl Blockchain {
         // Add to chain and clear pending transactions
         self.chain.push(new_block);
         self.pending_transactions.clear();
+        self.apply_validator_stakes(index);
     }
     
-    pub fn get_balance(&self, address: &str) -> f64 {
-        let mut balance = 0.0;
+    /// Stakes are applied once their block is mined, after the block's balance changes.
+    fn apply_validator_stakes(&mut self, block_index: usize) {
+        let stakes: Vec<(String, f64)> = self.chain[block_index]
+            .transactions
+            .iter()
+            .filter_map(|tx| match tx.transaction_type {
+                TransactionType::ValidatorStake { amount } => Some((tx.from.clone(), amount)),
+                _ => None,
+            })
+            .collect();
         
-        for block in &self.chain {
-            for transaction in &block.transactions {
-                match &transaction.transaction_type {
-                    crate::transaction::TransactionType::CarbonCredit { amount, .. } => {
-                        if transaction.from == address {
-                            balance += amount;
-                        }
-                    }
-                    crate::transaction::TransactionType::MiningReward { amount } => {
-                        if transaction.from == address {
-                            balance += amount;
-                        }
-                    }
-                    _ => {}
-                }
+        for (address, amount) in stakes {
+            let balance = self.get_balance(&address);
+            let staked = self.validator_stakes.entry(address).or_insert(0.0);
+            if is_valid_stake(balance, *staked, amount) {
+                *staked += amount;
             }
         }
-        
-        balance
+    }
+    
+    pub fn get_balance(&self, address: &str) -> f64 {
+        self.get_balance_at_height(address, self.chain.len() as u64)
+    }
+    
+    /// Balance counting only blocks up to and including `height`.
+    pub fn get_balance_at_height(&self, address: &str, height: u64) -> f64 {
+        self.chain
+            .iter()
+            .take_while(|block| block.index as u64 <= height)
+            .flat_map(|block| &block.transactions)
+            .filter_map(|transaction| {
+                transaction.balance_credit().or_else(|| {
+                    transaction.disbursement_credit().filter(|_| self.released_disbursements.contains(&transaction.id))
+                })
+            })
+            .filter(|(credited, _)| *credited == address)
+            .fold(0.0, |balance, (_, amount)| balance + amount)
+    }
+    
+    /// Marks mined disbursements the treasury accepted, so they credit their recipients.
+    pub fn record_released_disbursements(&mut self, tx_ids: &[String]) {
+        self.released_disbursements.extend(tx_ids.iter().cloned());
     }
     
     pub fn is_chain_valid(&self) -> bool {
@@ -128,13 +169,55 @@ impl Blockchain {
         self.difficulty
     }
     
-    pub fn add_validator(&mut self, _address: String, _stake: f64) {
-        // Validator functionality can be implemented here if needed
+    pub fn set_difficulty(&mut self, difficulty: usize) {
+        self.difficulty = difficulty;
+    }
+    
+    pub fn get_mining_reward(&self) -> f64 {
+        self.mining_reward
+    }
+    
+    pub fn set_mining_reward(&mut self, mining_reward: f64) {
+        self.mining_reward = mining_reward;
+    }
+    
+    pub fn get_treasury_share(&self) -> f64 {
+        self.treasury_share
+    }
+    
+    pub fn set_treasury_share(&mut self, treasury_share: f64) {
+        self.treasury_share = treasury_share;
+    }
+    
+    /// Queues a stake; it is locked once mined and cannot exceed the address's unstaked balance.
+    pub fn add_validator(&mut self, address: String, stake: f64) -> Result<(), String> {
+        let pending_stake: f64 = self.pending_transactions
+            .iter()
+            .filter(|tx| tx.from == address)
+            .filter_map(|tx| match tx.transaction_type {
+                TransactionType::ValidatorStake { amount } => Some(amount),
+                _ => None,
+            })
+            .sum();
+        let staked = self.get_validator_stake(&address) + pending_stake;
+        let balance = self.get_balance(&address);
+        if !is_valid_stake(balance, staked, stake) {
+            return Err(format!(
+                "Cannot stake {:.2} from {}: {:.2} of {:.2} is already staked",
+                stake, address, staked, balance
+            ));
+        }
+        
+        self.add_transaction(Transaction::new(address, TransactionType::ValidatorStake { amount: stake }));
+        Ok(())
+    }
+    
+    pub fn get_validator_stake(&self, address: &str) -> f64 {
+        self.validator_stakes.get(address).copied().unwrap_or(0.0)
     }
     
-    pub fn get_validator_stake(&self, _address: &str) -> f64 {
-        // Validator functionality can be implemented here if needed
-        0.0
+    pub fn get_validators(&self) -> &HashMap<String, f64> {
+        &self.validator_stakes
     }
     
     pub fn get_all_transactions(&self) -> Vec<&Transaction> {
@@ -143,6 +226,22 @@ impl Blockchain {
             .collect()
     }
     
+    pub fn get_blocks(&self) -> &Vec<Block> {
+        &self.chain
+    }
+    
+    pub fn get_pending_transactions(&self) -> &Vec<Transaction> {
+        &self.pending_transactions
+    }
+    
+    /// Looks a transaction up by id in mined blocks and in the pending pool.
+    pub fn find_transaction(&self, transaction_id: &str) -> Option<&Transaction> {
+        self.chain.iter()
+            .flat_map(|block| &block.transactions)
+            .chain(self.pending_transactions.iter())
+            .find(|tx| tx.id == transaction_id)
+    }
+    
     pub fn get_environmental_data(&self) -> Vec<crate::environment::EnvironmentalData> {
         let mut env_data = Vec::new();
         
@@ -158,6 +257,11 @@ impl Blockchain {
     }
 }
 
+/// A stake must be positive and fit in the balance that is not staked yet.
+pub fn is_valid_stake(balance: f64, already_staked: f64, amount: f64) -> bool {
+    amount > 0.0 && already_staked + amount <= balance
+}
+
 impl Default for Blockchain {
     fn default() -> Self {
         Self::new()
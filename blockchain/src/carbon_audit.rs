@@ -0,0 +1,203 @@
+//! Chain-wide audit for carbon credits that may count the same reduction twice.
+//! The registry rejects conflicting issuances as they arrive; this report
+//! also catches credits that reached the chain some other way.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::blockchain::Blockchain;
+use crate::carbon_registry::{ReductionClaim, ReductionSource};
+use crate::transaction::{Transaction, TransactionType};
+
+/// Sales of the same product between the same parties and for the same amount,
+/// this close together, look like one sale resubmitted under a new order id.
+const REPEATED_SALE_WINDOW: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DuplicateKind {
+    SharedClaim,
+    SharedEvidence,
+    OverlappingMonitoringPeriod,
+    UnclaimedCredit,
+    RepeatedSale,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspectedDuplicate {
+    pub kind: DuplicateKind,
+    pub transaction_ids: Vec<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditAuditReport {
+    pub generated_at: u64,
+    pub credit_transactions_scanned: usize,
+    pub suspected_duplicates: Vec<SuspectedDuplicate>,
+}
+
+/// (product, buyer, seller, amount in cents)
+type SaleKey = (String, String, String, i64);
+
+struct CreditEntry<'a> {
+    transaction: &'a Transaction,
+    claim: Option<&'a ReductionClaim>,
+}
+
+pub fn audit_carbon_credits(blockchain: &Blockchain) -> CreditAuditReport {
+    let transactions = blockchain.get_all_transactions();
+    let credits: Vec<CreditEntry> = transactions
+        .iter()
+        .filter_map(|tx| match &tx.transaction_type {
+            TransactionType::CarbonCredit { claim, .. } => Some(CreditEntry { transaction: tx, claim: claim.as_ref() }),
+            TransactionType::CarbonCreditIssuance { claim, .. } => Some(CreditEntry { transaction: tx, claim: Some(claim) }),
+            _ => None,
+        })
+        .collect();
+
+    let mut suspected_duplicates = Vec::new();
+    suspected_duplicates.extend(find_unclaimed_credits(&credits));
+    suspected_duplicates.extend(find_shared_claims(&credits));
+    suspected_duplicates.extend(find_shared_evidence(&credits));
+    suspected_duplicates.extend(find_overlapping_periods(&credits));
+    suspected_duplicates.extend(find_repeated_sales(&credits, &transactions));
+
+    CreditAuditReport {
+        generated_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        credit_transactions_scanned: credits.len(),
+        suspected_duplicates,
+    }
+}
+
+fn find_unclaimed_credits(credits: &[CreditEntry]) -> Vec<SuspectedDuplicate> {
+    credits
+        .iter()
+        .filter(|entry| entry.claim.is_none())
+        .map(|entry| SuspectedDuplicate {
+            kind: DuplicateKind::UnclaimedCredit,
+            transaction_ids: vec![entry.transaction.id.clone()],
+            description: format!(
+                "Credit {} is not tied to a reduction claim and cannot be checked for double counting",
+                entry.transaction.id
+            ),
+        })
+        .collect()
+}
+
+fn find_shared_claims(credits: &[CreditEntry]) -> Vec<SuspectedDuplicate> {
+    group_by_key(credits, |claim| Some(claim.claim_id()))
+        .into_iter()
+        .map(|(claim_id, transaction_ids)| SuspectedDuplicate {
+            kind: DuplicateKind::SharedClaim,
+            description: format!("Reduction claim {} was credited {} times", claim_id, transaction_ids.len()),
+            transaction_ids,
+        })
+        .collect()
+}
+
+fn find_shared_evidence(credits: &[CreditEntry]) -> Vec<SuspectedDuplicate> {
+    group_by_key(credits, |claim| Some(claim.evidence_tx_id.clone()))
+        .into_iter()
+        .map(|(evidence_tx_id, transaction_ids)| SuspectedDuplicate {
+            kind: DuplicateKind::SharedEvidence,
+            description: format!("Evidence {} backs {} separate credits", evidence_tx_id, transaction_ids.len()),
+            transaction_ids,
+        })
+        .collect()
+}
+
+fn find_overlapping_periods(credits: &[CreditEntry]) -> Vec<SuspectedDuplicate> {
+    let monitored: Vec<(&Transaction, &ReductionClaim, u64, u64)> = credits
+        .iter()
+        .filter_map(|entry| match entry.claim {
+            Some(claim @ ReductionClaim { source: ReductionSource::ProjectMonitoring { period_start, period_end }, .. }) => {
+                Some((entry.transaction, claim, *period_start, *period_end))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut findings = Vec::new();
+    for (i, (tx_a, claim_a, start_a, end_a)) in monitored.iter().enumerate() {
+        for (tx_b, claim_b, start_b, end_b) in monitored.iter().skip(i + 1) {
+            // Identical periods are already reported as a shared claim
+            let same_period = start_a == start_b && end_a == end_b;
+            if claim_a.project_id == claim_b.project_id && !same_period && start_a <= end_b && start_b <= end_a {
+                findings.push(SuspectedDuplicate {
+                    kind: DuplicateKind::OverlappingMonitoringPeriod,
+                    transaction_ids: vec![tx_a.id.clone(), tx_b.id.clone()],
+                    description: format!(
+                        "Project {} credited twice for overlapping periods {}-{} and {}-{}",
+                        claim_a.project_id, start_a, end_a, start_b, end_b
+                    ),
+                });
+            }
+        }
+    }
+    findings
+}
+
+fn find_repeated_sales(credits: &[CreditEntry], transactions: &[&Transaction]) -> Vec<SuspectedDuplicate> {
+    let by_id: HashMap<&str, &Transaction> = transactions.iter().map(|tx| (tx.id.as_str(), *tx)).collect();
+
+    // Sale key -> [(timestamp, order id, credit tx id)]
+    let mut sales: HashMap<SaleKey, Vec<(u64, String, String)>> = HashMap::new();
+    for entry in credits {
+        let claim = match entry.claim {
+            Some(claim) => claim,
+            None => continue,
+        };
+        if !matches!(claim.source, ReductionSource::MarketplaceSale { .. }) {
+            continue;
+        }
+        let details = match by_id.get(claim.evidence_tx_id.as_str()).and_then(|tx| tx.get_marketplace_details()) {
+            Some(details) => details,
+            None => continue,
+        };
+
+        let evidence_timestamp = by_id[claim.evidence_tx_id.as_str()].timestamp;
+        sales
+            .entry((details.product_did, details.buyer_did, details.seller_did, (details.amount * 100.0).round() as i64))
+            .or_default()
+            .push((evidence_timestamp, details.transaction_id, entry.transaction.id.clone()));
+    }
+
+    let mut findings = Vec::new();
+    for ((product_did, buyer_did, _, _), mut orders) in sales {
+        orders.sort();
+        for pair in orders.windows(2) {
+            let (first_time, first_order, first_tx) = &pair[0];
+            let (second_time, second_order, second_tx) = &pair[1];
+            if second_time - first_time <= REPEATED_SALE_WINDOW && first_order != second_order {
+                findings.push(SuspectedDuplicate {
+                    kind: DuplicateKind::RepeatedSale,
+                    transaction_ids: vec![first_tx.clone(), second_tx.clone()],
+                    description: format!(
+                        "Product {} sold to {} for the same amount under orders {} and {} within 24h",
+                        product_did, buyer_did, first_order, second_order
+                    ),
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// Groups credit transaction ids by a claim-derived key, keeping only keys seen more than once.
+fn group_by_key<F>(credits: &[CreditEntry], key: F) -> Vec<(String, Vec<String>)>
+where
+    F: Fn(&ReductionClaim) -> Option<String>,
+{
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for entry in credits {
+        if let Some(group_key) = entry.claim.and_then(&key) {
+            groups.entry(group_key).or_default().push(entry.transaction.id.clone());
+        }
+    }
+
+    let mut duplicates: Vec<(String, Vec<String>)> = groups.into_iter().filter(|(_, ids)| ids.len() > 1).collect();
+    duplicates.sort();
+    duplicates
+}
@@ -0,0 +1,833 @@
+//! Carbon credit registry.
+//! Every serial number represents one kilogram of CO2e reduction. Serials are
+//! allocated sequentially at issuance, so no two credits ever share a serial.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use crate::blockchain::Blockchain;
+use crate::did::DidRegistry;
+use crate::transaction::{Transaction, TransactionType};
+
+/// Only this address may register projects and issue credits.
+pub const REGISTRY_AUTHORITY: &str = "ecogov_carbon_registry";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonProject {
+    pub project_id: String,
+    pub name: String,
+    pub developer_did: String,
+    pub methodology: String,
+    pub location: String,
+    pub registered_at: u64,
+}
+
+/// What a reduction claim is based on. Each source identifies exactly one
+/// emission reduction, so it can be credited at most once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReductionSource {
+    MarketplaceSale {
+        order_id: String,
+        product_did: String,
+    },
+    ProjectMonitoring {
+        period_start: u64,
+        period_end: u64,
+    },
+}
+
+/// A verifiable emission reduction. Every credit, on either issuance path,
+/// must reference one, and `evidence_tx_id` points at the on-chain
+/// transaction that proves it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReductionClaim {
+    pub project_id: String,
+    pub vintage: u16,
+    pub source: ReductionSource,
+    pub reduction_kg: f64,
+    pub evidence_tx_id: String,
+}
+
+/// A claim that has already been credited, and the transaction that credited it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimRecord {
+    pub claim_id: String,
+    pub claim: ReductionClaim,
+    pub credited_by_tx: String,
+    pub issuance_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CreditStatus {
+    Active,
+    Retired { certificate_id: String },
+}
+
+/// A contiguous run of serials sharing an owner, project, vintage and status.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditBlock {
+    pub serial_start: u64,
+    pub serial_end: u64,
+    pub batch_id: String,
+    pub project_id: String,
+    pub vintage: u16,
+    pub owner: String,
+    pub status: CreditStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetirementCertificate {
+    pub certificate_id: String,
+    pub serial_start: u64,
+    pub serial_end: u64,
+    pub quantity: u64,
+    pub project_id: String,
+    pub vintage: u16,
+    pub retired_by: String,
+    pub beneficiary: String,
+    pub reason: String,
+    pub order_id: Option<String>,
+    pub retired_at: u64,
+    pub transaction_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OwnershipEventKind {
+    Issued { batch_id: String, recipient: String },
+    Transferred { from: String, to: String },
+    Retired { holder: String, certificate_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipEvent {
+    pub transaction_id: String,
+    pub timestamp: u64,
+    pub serial_start: u64,
+    pub serial_end: u64,
+    pub kind: OwnershipEventKind,
+}
+
+/// Blocks whose start lies in `first_key..=last_serial`, as they were before a change.
+struct SavedBlocks {
+    first_key: u64,
+    last_serial: u64,
+    blocks: Vec<CreditBlock>,
+}
+
+/// State added or changed by one transaction of a batch, to restore if a later one fails.
+#[derive(Default)]
+struct UndoEntry {
+    project: Option<String>,
+    claim: Option<ReductionClaim>,
+    certificate: Option<String>,
+    blocks: Option<SavedBlocks>,
+}
+
+#[derive(Clone)]
+pub struct CarbonCreditRegistry {
+    projects: HashMap<String, CarbonProject>,
+    blocks: BTreeMap<u64, CreditBlock>,
+    next_serial: u64,
+    history: Vec<OwnershipEvent>,
+    certificates: HashMap<String, RetirementCertificate>,
+    claims: HashMap<String, ClaimRecord>,
+    claims_by_evidence: HashMap<String, String>,
+    order_offsets: HashMap<String, Vec<String>>, // order id -> certificates of the retirements offsetting it
+    authority_key: Option<String>, // public key that must sign the registry authority's transactions
+}
+
+impl ReductionClaim {
+    /// Deterministic identifier of the underlying reduction. A sale is keyed by
+    /// its order alone, so re-crediting it under another project id still collides.
+    pub fn claim_id(&self) -> String {
+        let key = match &self.source {
+            ReductionSource::MarketplaceSale { order_id, .. } => format!("sale|{}", order_id),
+            ReductionSource::ProjectMonitoring { period_start, period_end } => {
+                format!("monitoring|{}|{}|{}", self.project_id, period_start, period_end)
+            }
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(key.as_bytes());
+        format!("claim_{}", hex::encode(hasher.finalize()))
+    }
+
+    /// Whole credits (one per kg CO2e) this claim can back.
+    pub fn max_credits(&self) -> u64 {
+        self.reduction_kg.max(0.0).floor() as u64
+    }
+
+    fn monitoring_overlaps(&self, other: &ReductionClaim) -> bool {
+        match (&self.source, &other.source) {
+            (
+                ReductionSource::ProjectMonitoring { period_start: a_start, period_end: a_end },
+                ReductionSource::ProjectMonitoring { period_start: b_start, period_end: b_end },
+            ) => self.project_id == other.project_id && a_start <= b_end && b_start <= a_end,
+            _ => false,
+        }
+    }
+}
+
+/// Vintage year of a reduction that happened at `timestamp`.
+pub fn vintage_for_timestamp(timestamp: u64) -> u16 {
+    use chrono::{Datelike, TimeZone, Utc};
+
+    Utc.timestamp_opt(timestamp as i64, 0)
+        .single()
+        .map(|date| date.year() as u16)
+        .unwrap_or(1970)
+}
+
+impl CreditBlock {
+    pub fn quantity(&self) -> u64 {
+        self.serial_end - self.serial_start + 1
+    }
+
+    pub fn contains(&self, serial: u64) -> bool {
+        serial >= self.serial_start && serial <= self.serial_end
+    }
+}
+
+impl CarbonCreditRegistry {
+    pub fn new() -> Self {
+        CarbonCreditRegistry {
+            projects: HashMap::new(),
+            blocks: BTreeMap::new(),
+            next_serial: 1,
+            history: Vec::new(),
+            certificates: HashMap::new(),
+            claims: HashMap::new(),
+            claims_by_evidence: HashMap::new(),
+            order_offsets: HashMap::new(),
+            authority_key: None,
+        }
+    }
+
+    /// A registry that accepts authority transactions signed with `authority_key`.
+    /// Without one, as from `new`, they are all rejected.
+    pub fn with_authority_key(authority_key: String) -> Self {
+        CarbonCreditRegistry {
+            authority_key: Some(authority_key),
+            ..Self::new()
+        }
+    }
+
+    /// Rebuilds the registry by replaying every carbon transaction on the chain.
+    /// Transfers and retirements are checked against the holders' DIDs in `dids`.
+    pub fn from_chain(blockchain: &Blockchain, dids: &DidRegistry, authority_key: &str) -> Result<Self, String> {
+        let mut registry = Self::with_authority_key(authority_key.to_string());
+        for transaction in blockchain.get_all_transactions() {
+            registry.apply_transaction(transaction, dids)?;
+        }
+        Ok(registry)
+    }
+
+    pub fn next_serial(&self) -> u64 {
+        self.next_serial
+    }
+
+    /// Validates a transaction against the current registry state and applies it.
+    /// Transfers and retirements must be signed by an authentication key of the
+    /// holder's DID. Transactions that do not touch the registry are ignored.
+    /// Nothing changes on error.
+    pub fn apply_transaction(&mut self, tx: &Transaction, dids: &DidRegistry) -> Result<(), String> {
+        match &tx.transaction_type {
+            TransactionType::CarbonProjectRegistration { project_id, name, developer_did, methodology, location } => {
+                self.require_authority(tx)?;
+                if self.projects.contains_key(project_id) {
+                    return Err(format!("Carbon project {} is already registered", project_id));
+                }
+                self.projects.insert(project_id.clone(), CarbonProject {
+                    project_id: project_id.clone(),
+                    name: name.clone(),
+                    developer_did: developer_did.clone(),
+                    methodology: methodology.clone(),
+                    location: location.clone(),
+                    registered_at: tx.timestamp,
+                });
+                Ok(())
+            }
+            TransactionType::CarbonCredit { claim, .. } => {
+                self.require_authority(tx)?;
+                let claim = claim.as_ref().ok_or_else(|| {
+                    format!("Carbon credit {} is not tied to a reduction claim", tx.id)
+                })?;
+                // Project reductions are credited through serialized issuance only
+                if !matches!(claim.source, ReductionSource::MarketplaceSale { .. }) {
+                    return Err(format!("Carbon credit {} must be issued as serialized credits", tx.id));
+                }
+                self.check_claim(claim)?;
+                self.record_claim(claim, tx, "CarbonCredit");
+                Ok(())
+            }
+            TransactionType::CarbonCreditIssuance { batch_id, claim, serial_start, serial_end, recipient } => {
+                self.require_authority(tx)?;
+                if !self.projects.contains_key(&claim.project_id) {
+                    return Err(format!("Carbon project {} is not registered", claim.project_id));
+                }
+                if *serial_start != self.next_serial || serial_end < serial_start {
+                    return Err(format!(
+                        "Issuance must allocate serials starting at {}, got {}-{}",
+                        self.next_serial, serial_start, serial_end
+                    ));
+                }
+                let next_serial = serial_end
+                    .checked_add(1)
+                    .ok_or_else(|| format!("Issuance {}-{} exhausts the serial range", serial_start, serial_end))?;
+                if serial_end - serial_start + 1 > claim.max_credits() {
+                    return Err(format!(
+                        "Issuance of {} credits exceeds the {:.2} kg CO2e reduction claimed",
+                        serial_end - serial_start + 1,
+                        claim.reduction_kg
+                    ));
+                }
+                self.check_claim(claim)?;
+                self.record_claim(claim, tx, "CarbonCreditIssuance");
+
+                self.blocks.insert(*serial_start, CreditBlock {
+                    serial_start: *serial_start,
+                    serial_end: *serial_end,
+                    batch_id: batch_id.clone(),
+                    project_id: claim.project_id.clone(),
+                    vintage: claim.vintage,
+                    owner: recipient.clone(),
+                    status: CreditStatus::Active,
+                });
+                self.next_serial = next_serial;
+                self.record_event(tx, *serial_start, *serial_end, OwnershipEventKind::Issued {
+                    batch_id: batch_id.clone(),
+                    recipient: recipient.clone(),
+                });
+                Ok(())
+            }
+            TransactionType::CarbonCreditTransfer { serial_start, serial_end, recipient } => {
+                if *recipient == tx.from {
+                    return Err("Cannot transfer carbon credits to the current holder".to_string());
+                }
+                Self::authorize_holder(tx, dids)?;
+                self.check_active_holding(&tx.from, *serial_start, *serial_end)?;
+
+                for block in self.split_range(*serial_start, *serial_end) {
+                    block.owner = recipient.clone();
+                }
+                self.merge_range(*serial_start, *serial_end);
+                self.record_event(tx, *serial_start, *serial_end, OwnershipEventKind::Transferred {
+                    from: tx.from.clone(),
+                    to: recipient.clone(),
+                });
+                Ok(())
+            }
+            TransactionType::CarbonCreditRetirement { certificate_id, serial_start, serial_end, beneficiary, reason, order_id } => {
+                if self.certificates.contains_key(certificate_id) {
+                    return Err(format!("Retirement certificate {} already exists", certificate_id));
+                }
+                Self::authorize_holder(tx, dids)?;
+                self.check_active_holding(&tx.from, *serial_start, *serial_end)?;
+
+                let (project_id, vintage) = self.single_project_vintage(*serial_start, *serial_end)?;
+                for block in self.split_range(*serial_start, *serial_end) {
+                    block.status = CreditStatus::Retired { certificate_id: certificate_id.clone() };
+                }
+                self.merge_range(*serial_start, *serial_end);
+
+                self.certificates.insert(certificate_id.clone(), RetirementCertificate {
+                    certificate_id: certificate_id.clone(),
+                    serial_start: *serial_start,
+                    serial_end: *serial_end,
+                    quantity: serial_end - serial_start + 1,
+                    project_id,
+                    vintage,
+                    retired_by: tx.from.clone(),
+                    beneficiary: beneficiary.clone(),
+                    reason: reason.clone(),
+                    order_id: order_id.clone(),
+                    retired_at: tx.timestamp,
+                    transaction_id: tx.id.clone(),
+                });
+                if let Some(order_id) = order_id {
+                    self.order_offsets.entry(order_id.clone()).or_default().push(certificate_id.clone());
+                }
+                self.record_event(tx, *serial_start, *serial_end, OwnershipEventKind::Retired {
+                    holder: tx.from.clone(),
+                    certificate_id: certificate_id.clone(),
+                });
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies transactions in order, all or nothing: if one fails, the changes
+    /// made by the earlier ones are undone. Only the state each transaction
+    /// touches is saved, never the whole registry.
+    pub fn apply_batch(&mut self, transactions: &[Transaction], dids: &DidRegistry) -> Result<(), String> {
+        let next_serial = self.next_serial;
+        let history_len = self.history.len();
+        let mut undo_log = Vec::new();
+
+        for tx in transactions {
+            let undo = self.undo_entry(tx);
+            if let Err(e) = self.apply_transaction(tx, dids) {
+                for undo in undo_log.into_iter().rev() {
+                    self.undo(undo);
+                }
+                self.next_serial = next_serial;
+                self.history.truncate(history_len);
+                return Err(e);
+            }
+            undo_log.push(undo);
+        }
+        Ok(())
+    }
+
+    /// What `tx` may add or change, saved before it is applied.
+    fn undo_entry(&self, tx: &Transaction) -> UndoEntry {
+        let mut undo = UndoEntry::default();
+        match &tx.transaction_type {
+            TransactionType::CarbonProjectRegistration { project_id, .. } => undo.project = Some(project_id.clone()),
+            TransactionType::CarbonCredit { claim: Some(claim), .. } => undo.claim = Some(claim.clone()),
+            TransactionType::CarbonCreditIssuance { claim, serial_start, serial_end, .. } => {
+                undo.claim = Some(claim.clone());
+                undo.blocks = Some(self.save_blocks(*serial_start, *serial_end));
+            }
+            TransactionType::CarbonCreditTransfer { serial_start, serial_end, .. } => {
+                undo.blocks = Some(self.save_blocks(*serial_start, *serial_end));
+            }
+            TransactionType::CarbonCreditRetirement { certificate_id, serial_start, serial_end, .. } => {
+                undo.certificate = Some(certificate_id.clone());
+                undo.blocks = Some(self.save_blocks(*serial_start, *serial_end));
+            }
+            _ => {}
+        }
+        undo
+    }
+
+    /// The blocks a change to the range can split or merge: those overlapping
+    /// it and their immediate neighbours.
+    fn save_blocks(&self, serial_start: u64, serial_end: u64) -> SavedBlocks {
+        let first_key = self.blocks.range(..serial_start).next_back().map(|(start, _)| *start).unwrap_or(serial_start);
+        let blocks: Vec<CreditBlock> = self.blocks
+            .range(first_key..=serial_end.saturating_add(1))
+            .map(|(_, block)| block.clone())
+            .collect();
+        let last_serial = blocks.iter().map(|block| block.serial_end).fold(serial_end, u64::max);
+        SavedBlocks { first_key, last_serial, blocks }
+    }
+
+    fn undo(&mut self, undo: UndoEntry) {
+        if let Some(project_id) = undo.project {
+            self.projects.remove(&project_id);
+        }
+        if let Some(claim) = undo.claim {
+            self.claims.remove(&claim.claim_id());
+            self.claims_by_evidence.remove(&claim.evidence_tx_id);
+        }
+        if let Some(certificate_id) = undo.certificate {
+            let order_id = self.certificates.remove(&certificate_id).and_then(|certificate| certificate.order_id);
+            if let Some(order_id) = order_id {
+                let certificate_ids = self.order_offsets.entry(order_id.clone()).or_default();
+                certificate_ids.retain(|id| *id != certificate_id);
+                if certificate_ids.is_empty() {
+                    self.order_offsets.remove(&order_id);
+                }
+            }
+        }
+        if let Some(saved) = undo.blocks {
+            let changed: Vec<u64> = self.blocks.range(saved.first_key..=saved.last_serial).map(|(start, _)| *start).collect();
+            for key in changed {
+                self.blocks.remove(&key);
+            }
+            for block in saved.blocks {
+                self.blocks.insert(block.serial_start, block);
+            }
+        }
+    }
+
+    /// Rejects a claim whose reduction, evidence or monitoring period has already been credited.
+    pub fn check_claim(&self, claim: &ReductionClaim) -> Result<(), String> {
+        if claim.reduction_kg <= 0.0 {
+            return Err("A reduction claim must cover a positive reduction".to_string());
+        }
+
+        let claim_id = claim.claim_id();
+        if let Some(existing) = self.claims.get(&claim_id) {
+            return Err(format!(
+                "Double counting: reduction claim {} was already credited by {} transaction {}",
+                claim_id, existing.issuance_path, existing.credited_by_tx
+            ));
+        }
+
+        if let Some(existing_claim_id) = self.claims_by_evidence.get(&claim.evidence_tx_id) {
+            return Err(format!(
+                "Double counting: evidence {} already backs reduction claim {}",
+                claim.evidence_tx_id, existing_claim_id
+            ));
+        }
+
+        if let Some(existing) = self.claims.values().find(|record| record.claim.monitoring_overlaps(claim)) {
+            return Err(format!(
+                "Double counting: monitoring period overlaps reduction claim {} for project {}",
+                existing.claim_id, claim.project_id
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn record_claim(&mut self, claim: &ReductionClaim, tx: &Transaction, issuance_path: &str) {
+        let claim_id = claim.claim_id();
+        self.claims_by_evidence.insert(claim.evidence_tx_id.clone(), claim_id.clone());
+        self.claims.insert(claim_id.clone(), ClaimRecord {
+            claim_id,
+            claim: claim.clone(),
+            credited_by_tx: tx.id.clone(),
+            issuance_path: issuance_path.to_string(),
+        });
+    }
+
+    pub fn get_claim(&self, claim_id: &str) -> Option<&ClaimRecord> {
+        self.claims.get(claim_id)
+    }
+
+    /// The sender must be the registry authority and sign with its configured key.
+    fn require_authority(&self, tx: &Transaction) -> Result<(), String> {
+        if tx.from != REGISTRY_AUTHORITY {
+            return Err(format!("{} is not authorized to act as the carbon registry", tx.from));
+        }
+        let authority_key = self.authority_key
+            .as_ref()
+            .ok_or_else(|| "No carbon registry authority key is configured".to_string())?;
+        if !tx.verify_signature(authority_key) {
+            return Err(format!("Transaction {} is not signed by the carbon registry", tx.id));
+        }
+        Ok(())
+    }
+
+    fn authorize_holder(tx: &Transaction, dids: &DidRegistry) -> Result<(), String> {
+        dids.verify_transaction(tx)
+            .map(|_| ())
+            .map_err(|e| format!("{} cannot move credits for {}: {}", tx.id, tx.from, e))
+    }
+
+    /// Blocks overlapping the inclusive range, in serial order.
+    fn overlapping_blocks(&self, serial_start: u64, serial_end: u64) -> Vec<&CreditBlock> {
+        let first_key = self
+            .blocks
+            .range(..=serial_start)
+            .next_back()
+            .map(|(start, _)| *start)
+            .unwrap_or(serial_start);
+
+        self.blocks
+            .range(first_key..=serial_end)
+            .map(|(_, block)| block)
+            .filter(|block| block.serial_end >= serial_start)
+            .collect()
+    }
+
+    fn check_active_holding(&self, holder: &str, serial_start: u64, serial_end: u64) -> Result<(), String> {
+        if serial_end < serial_start {
+            return Err(format!("Invalid serial range {}-{}", serial_start, serial_end));
+        }
+
+        let mut expected = serial_start;
+        for block in self.overlapping_blocks(serial_start, serial_end) {
+            if block.serial_start > expected {
+                break;
+            }
+            if block.owner != holder {
+                return Err(format!("Serials {}-{} are not held by {}", serial_start, serial_end, holder));
+            }
+            if let CreditStatus::Retired { certificate_id } = &block.status {
+                return Err(format!(
+                    "Serials {}-{} include credits retired under certificate {}",
+                    serial_start, serial_end, certificate_id
+                ));
+            }
+            expected = block.serial_end + 1;
+        }
+
+        if expected <= serial_end {
+            return Err(format!("Serials {}-{} have not all been issued", serial_start, serial_end));
+        }
+        Ok(())
+    }
+
+    fn single_project_vintage(&self, serial_start: u64, serial_end: u64) -> Result<(String, u16), String> {
+        let blocks = self.overlapping_blocks(serial_start, serial_end);
+        let first = blocks.first().ok_or_else(|| format!("Serials {}-{} not found", serial_start, serial_end))?;
+
+        if blocks.iter().any(|block| block.project_id != first.project_id || block.vintage != first.vintage) {
+            return Err("A retirement must cover credits from a single project and vintage".to_string());
+        }
+        Ok((first.project_id.clone(), first.vintage))
+    }
+
+    /// Splits blocks at the range boundaries and returns the blocks inside the range.
+    fn split_range(&mut self, serial_start: u64, serial_end: u64) -> Vec<&mut CreditBlock> {
+        self.split_at(serial_start);
+        self.split_at(serial_end + 1);
+        self.blocks.range_mut(serial_start..=serial_end).map(|(_, block)| block).collect()
+    }
+
+    fn split_at(&mut self, serial: u64) {
+        let containing = self
+            .blocks
+            .range(..serial)
+            .next_back()
+            .filter(|(_, block)| block.serial_end >= serial)
+            .map(|(start, _)| *start);
+
+        if let Some(start) = containing {
+            let block = self.blocks.get_mut(&start).unwrap();
+            let mut tail = block.clone();
+            block.serial_end = serial - 1;
+            tail.serial_start = serial;
+            self.blocks.insert(serial, tail);
+        }
+    }
+
+    /// Joins the blocks of a transferred or retired range with each other and
+    /// with their neighbours where they became identical.
+    fn merge_range(&mut self, serial_start: u64, serial_end: u64) {
+        let first_key = self.blocks.range(..serial_start).next_back().map(|(start, _)| *start).unwrap_or(serial_start);
+        let keys: Vec<u64> = self.blocks.range(first_key..=serial_end.saturating_add(1)).map(|(start, _)| *start).collect();
+        let mut current: Option<CreditBlock> = None;
+
+        for key in keys {
+            let block = self.blocks.remove(&key).unwrap();
+            current = match current {
+                Some(mut previous)
+                    if previous.serial_end + 1 == block.serial_start
+                        && previous.owner == block.owner
+                        && previous.status == block.status
+                        && previous.batch_id == block.batch_id =>
+                {
+                    previous.serial_end = block.serial_end;
+                    Some(previous)
+                }
+                Some(previous) => {
+                    self.blocks.insert(previous.serial_start, previous);
+                    Some(block)
+                }
+                None => Some(block),
+            };
+        }
+
+        if let Some(last) = current {
+            self.blocks.insert(last.serial_start, last);
+        }
+    }
+
+    fn record_event(&mut self, tx: &Transaction, serial_start: u64, serial_end: u64, kind: OwnershipEventKind) {
+        self.history.push(OwnershipEvent {
+            transaction_id: tx.id.clone(),
+            timestamp: tx.timestamp,
+            serial_start,
+            serial_end,
+            kind,
+        });
+    }
+
+    pub fn get_project(&self, project_id: &str) -> Option<&CarbonProject> {
+        self.projects.get(project_id)
+    }
+
+    pub fn get_projects(&self) -> Vec<&CarbonProject> {
+        self.projects.values().collect()
+    }
+
+    pub fn get_credit_block(&self, serial: u64) -> Option<&CreditBlock> {
+        self.blocks
+            .range(..=serial)
+            .next_back()
+            .map(|(_, block)| block)
+            .filter(|block| block.contains(serial))
+    }
+
+    /// Active credit blocks held by `owner`.
+    pub fn get_holdings(&self, owner: &str) -> Vec<&CreditBlock> {
+        self.blocks
+            .values()
+            .filter(|block| block.owner == owner && block.status == CreditStatus::Active)
+            .collect()
+    }
+
+    /// Picks `quantity` active serials held by `owner`, oldest issuance first.
+    /// Each returned range lies inside a single credit block, so it can be
+    /// retired under one certificate.
+    pub fn select_active_serials(&self, owner: &str, quantity: u64) -> Option<Vec<(u64, u64)>> {
+        let mut remaining = quantity;
+        let mut ranges = Vec::new();
+
+        for block in self.get_holdings(owner) {
+            if remaining == 0 {
+                break;
+            }
+            let take = remaining.min(block.quantity());
+            ranges.push((block.serial_start, block.serial_start + take - 1));
+            remaining -= take;
+        }
+
+        if remaining == 0 {
+            Some(ranges)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_active_balance(&self, owner: &str) -> u64 {
+        self.get_holdings(owner).iter().map(|block| block.quantity()).sum()
+    }
+
+    pub fn get_retirement_certificate(&self, certificate_id: &str) -> Option<&RetirementCertificate> {
+        self.certificates.get(certificate_id)
+    }
+
+    /// Retirements on chain that offset the order.
+    pub fn get_order_retirements(&self, order_id: &str) -> Vec<&RetirementCertificate> {
+        self.order_offsets
+            .get(order_id)
+            .map(|certificate_ids| certificate_ids.iter().filter_map(|id| self.certificates.get(id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Rejects an order that retirements on chain already offset.
+    pub fn check_order_offset(&self, order_id: &str) -> Result<(), String> {
+        match self.order_offsets.get(order_id) {
+            Some(certificate_ids) => Err(format!("Order {} has already been offset by {}", order_id, certificate_ids.join(", "))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn get_retirement_for_serial(&self, serial: u64) -> Option<&RetirementCertificate> {
+        match &self.get_credit_block(serial)?.status {
+            CreditStatus::Retired { certificate_id } => self.certificates.get(certificate_id),
+            CreditStatus::Active => None,
+        }
+    }
+
+    /// Every issuance, transfer and retirement that touched `serial`, oldest first.
+    pub fn get_serial_history(&self, serial: u64) -> Vec<&OwnershipEvent> {
+        self.history
+            .iter()
+            .filter(|event| serial >= event.serial_start && serial <= event.serial_end)
+            .collect()
+    }
+
+    pub fn total_issued(&self) -> u64 {
+        self.next_serial - 1
+    }
+
+    pub fn total_retired(&self) -> u64 {
+        self.certificates.values().map(|certificate| certificate.quantity).sum()
+    }
+}
+
+impl Default for CarbonCreditRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did::DidDocument;
+    use crate::signing::{self, KeyPair};
+
+    fn holder(dids: &mut DidRegistry, did: &str) -> KeyPair {
+        let keys = signing::generate_keypair();
+        let mut creation_tx = Transaction::new_did_creation(DidDocument::new(did, &keys.public_key));
+        creation_tx.sign(&keys.private_key).unwrap();
+        dids.apply_transaction(&creation_tx).unwrap();
+        keys
+    }
+
+    fn signed(from: &str, private_key: &str, transaction_type: TransactionType) -> Transaction {
+        let mut tx = Transaction::new(from.to_string(), transaction_type);
+        tx.sign(private_key).unwrap();
+        tx
+    }
+
+    fn issuance(authority: &KeyPair, evidence_tx_id: &str, serial_start: u64, recipient: &str) -> Transaction {
+        let claim = ReductionClaim {
+            project_id: "project".to_string(),
+            vintage: 2025,
+            source: ReductionSource::ProjectMonitoring { period_start: serial_start, period_end: serial_start + 1 },
+            reduction_kg: 100.0,
+            evidence_tx_id: evidence_tx_id.to_string(),
+        };
+        signed(REGISTRY_AUTHORITY, &authority.private_key, TransactionType::CarbonCreditIssuance {
+            batch_id: format!("batch_{}", evidence_tx_id),
+            claim,
+            serial_start,
+            serial_end: serial_start + 99,
+            recipient: recipient.to_string(),
+        })
+    }
+
+    fn issued_claim_id(tx: &Transaction) -> String {
+        match &tx.transaction_type {
+            TransactionType::CarbonCreditIssuance { claim, .. } => claim.claim_id(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn holdings(registry: &CarbonCreditRegistry, owners: &[&str]) -> Vec<(String, u64, u64, CreditStatus)> {
+        owners
+            .iter()
+            .flat_map(|owner| registry.get_holdings(owner))
+            .map(|block| (block.owner.clone(), block.serial_start, block.serial_end, block.status.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn apply_batch_rolls_back_a_batch_that_fails_partway() {
+        let alice = "did:veritoken:test:alice";
+        let bob = "did:veritoken:test:bob";
+        let mut dids = DidRegistry::new();
+        let alice_keys = holder(&mut dids, alice);
+        let bob_keys = holder(&mut dids, bob);
+        let authority = signing::generate_keypair();
+        let mut registry = CarbonCreditRegistry::with_authority_key(authority.public_key.clone());
+        registry.apply_transaction(&signed(REGISTRY_AUTHORITY, &authority.private_key, TransactionType::CarbonProjectRegistration {
+            project_id: "project".to_string(),
+            name: "Project".to_string(),
+            developer_did: alice.to_string(),
+            methodology: "methodology".to_string(),
+            location: "location".to_string(),
+        }), &dids).unwrap();
+        registry.apply_transaction(&issuance(&authority, "evidence_1", 1, alice), &dids).unwrap();
+        let before = holdings(&registry, &[alice, bob]);
+        let history_len = registry.history.len();
+
+        // Issues, transfers and retires, then fails on a transfer of serials bob no longer holds
+        let batch = vec![
+            issuance(&authority, "evidence_2", 101, alice),
+            signed(alice, &alice_keys.private_key, TransactionType::CarbonCreditTransfer { serial_start: 30, serial_end: 40, recipient: bob.to_string() }),
+            signed(bob, &bob_keys.private_key, TransactionType::CarbonCreditRetirement {
+                certificate_id: "certificate".to_string(),
+                serial_start: 30,
+                serial_end: 35,
+                beneficiary: bob.to_string(),
+                reason: "offset".to_string(),
+                order_id: Some("order".to_string()),
+            }),
+            signed(bob, &bob_keys.private_key, TransactionType::CarbonCreditTransfer { serial_start: 30, serial_end: 40, recipient: alice.to_string() }),
+        ];
+        assert!(registry.apply_batch(&batch, &dids).is_err());
+
+        assert_eq!(holdings(&registry, &[alice, bob]), before);
+        assert_eq!(registry.next_serial(), 101);
+        assert_eq!(registry.history.len(), history_len);
+        assert!(registry.get_retirement_certificate("certificate").is_none());
+        assert!(registry.get_order_retirements("order").is_empty());
+        assert!(registry.get_claim(&issued_claim_id(&batch[0])).is_none());
+
+        // Nothing of the failed batch lingers, so its valid prefix still applies
+        registry.apply_batch(&batch[..3], &dids).unwrap();
+        assert_eq!(registry.next_serial(), 201);
+        assert_eq!(registry.get_order_retirements("order").len(), 1);
+    }
+}
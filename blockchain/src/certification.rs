@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::blockchain::Blockchain;
+use crate::signing;
+use crate::transaction::{Transaction, TransactionType};
+
+/// Only this address may register and deactivate certifiers.
+pub const CERTIFICATION_AUTHORITY: &str = "ecogov_certification_registry";
+
+/// A certification body allowed to attest eco-certifications such as "ISO 14001".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertifierProfile {
+    pub certifier_did: String,
+    pub name: String,
+    pub public_key: String,
+    pub accredited_certifications: Vec<String>,
+    pub registered_at: u64,
+    pub active: bool,
+}
+
+/// An eco-certification issued to a product or merchant DID and signed by its certifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcoCertificationCredential {
+    pub credential_id: String,
+    pub certification_type: String,
+    pub issuer_did: String,
+    pub subject_did: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificationRevocation {
+    pub credential_id: String,
+    pub revoked_by: String,
+    pub reason: String,
+    pub revoked_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CertificationStatus {
+    Valid,
+    NotYetValid,
+    Expired,
+    Revoked,
+    UnknownCertifier,
+    CertifierInactive,
+    NotAccredited,
+    InvalidSignature,
+}
+
+impl EcoCertificationCredential {
+    pub fn new(certification_type: String, issuer_did: String, subject_did: String, validity_period: u64) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        EcoCertificationCredential {
+            credential_id: format!("cert_{:016x}", rand::random::<u64>()),
+            certification_type,
+            issuer_did,
+            subject_did,
+            issued_at: now,
+            expires_at: now + validity_period,
+            signature: None,
+        }
+    }
+
+    /// The exact bytes the certifier signs: the credential without its signature, as JSON.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let unsigned = EcoCertificationCredential { signature: None, ..self.clone() };
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    pub fn sign(&mut self, private_key: &str) -> Result<(), String> {
+        self.signature = Some(signing::sign_message(private_key, &self.signing_payload())?);
+        Ok(())
+    }
+}
+
+impl CertificationStatus {
+    pub fn is_valid(&self) -> bool {
+        *self == CertificationStatus::Valid
+    }
+}
+
+pub struct CertificationRegistry {
+    certifiers: HashMap<String, CertifierProfile>,
+    credentials: HashMap<String, EcoCertificationCredential>,
+    revocations: HashMap<String, CertificationRevocation>,
+    authority_key: Option<String>, // must sign certifier registrations and deactivations
+}
+
+impl CertificationRegistry {
+    pub fn new() -> Self {
+        CertificationRegistry {
+            certifiers: HashMap::new(),
+            credentials: HashMap::new(),
+            revocations: HashMap::new(),
+            authority_key: None,
+        }
+    }
+
+    pub fn with_authority_key(authority_key: String) -> Self {
+        CertificationRegistry {
+            authority_key: Some(authority_key),
+            ..Self::new()
+        }
+    }
+
+    /// Rebuilds the certifiers by replaying their registrations and deactivations.
+    /// Credentials and revocations are held off chain and are not restored.
+    pub fn from_chain(blockchain: &Blockchain, authority_key: &str) -> Result<Self, String> {
+        let mut registry = Self::with_authority_key(authority_key.to_string());
+        for transaction in blockchain.get_all_transactions() {
+            registry.apply_transaction(transaction)?;
+        }
+        Ok(registry)
+    }
+
+    /// Validates a certifier registration or deactivation and applies it.
+    /// Other transactions are ignored. Nothing changes on error.
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<(), String> {
+        match &tx.transaction_type {
+            TransactionType::CertifierRegistration { certifier_did, name, public_key, accredited_certifications } => {
+                self.require_authority(tx)?;
+                if !signing::is_valid_public_key(public_key) {
+                    return Err(format!("Certifier {} has an invalid public key", certifier_did));
+                }
+                if accredited_certifications.is_empty() {
+                    return Err(format!("Certifier {} has no accredited certifications", certifier_did));
+                }
+                if self.certifiers.contains_key(certifier_did) {
+                    return Err(format!("Certifier {} is already registered", certifier_did));
+                }
+
+                self.certifiers.insert(certifier_did.clone(), CertifierProfile {
+                    certifier_did: certifier_did.clone(),
+                    name: name.clone(),
+                    public_key: public_key.clone(),
+                    accredited_certifications: accredited_certifications.clone(),
+                    registered_at: tx.timestamp,
+                    active: true,
+                });
+                Ok(())
+            }
+            TransactionType::CertifierDeactivation { certifier_did } => {
+                self.require_authority(tx)?;
+                match self.certifiers.get_mut(certifier_did) {
+                    Some(profile) if profile.active => {
+                        profile.active = false;
+                        Ok(())
+                    }
+                    Some(_) => Err(format!("Certifier {} is already inactive", certifier_did)),
+                    None => Err(format!("Certifier {} is not registered", certifier_did)),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The sender must be the certification registry and sign with its configured key.
+    fn require_authority(&self, tx: &Transaction) -> Result<(), String> {
+        if tx.from != CERTIFICATION_AUTHORITY {
+            return Err(format!("{} is not authorized to act as the certification registry", tx.from));
+        }
+        let authority_key = self.authority_key
+            .as_ref()
+            .ok_or_else(|| "No certification registry key is configured".to_string())?;
+        if !tx.verify_signature(authority_key) {
+            return Err(format!("Transaction {} is not signed by the certification registry", tx.id));
+        }
+        Ok(())
+    }
+
+    pub fn get_certifier(&self, certifier_did: &str) -> Option<&CertifierProfile> {
+        self.certifiers.get(certifier_did)
+    }
+
+    /// Checks a credential against the certifier registry, its signature, its
+    /// validity window at `at_time` and the revocation list.
+    pub fn check_credential(&self, credential: &EcoCertificationCredential, at_time: u64) -> CertificationStatus {
+        let certifier = match self.certifiers.get(&credential.issuer_did) {
+            Some(certifier) => certifier,
+            None => return CertificationStatus::UnknownCertifier,
+        };
+
+        if !certifier.active {
+            return CertificationStatus::CertifierInactive;
+        }
+
+        if !certifier.accredited_certifications.contains(&credential.certification_type) {
+            return CertificationStatus::NotAccredited;
+        }
+
+        let signature_valid = credential
+            .signature
+            .as_ref()
+            .map(|signature| signing::verify_message(&certifier.public_key, &credential.signing_payload(), signature))
+            .unwrap_or(false);
+        if !signature_valid {
+            return CertificationStatus::InvalidSignature;
+        }
+
+        if self.revocations.contains_key(&credential.credential_id) {
+            return CertificationStatus::Revoked;
+        }
+
+        if at_time < credential.issued_at {
+            CertificationStatus::NotYetValid
+        } else if at_time >= credential.expires_at {
+            CertificationStatus::Expired
+        } else {
+            CertificationStatus::Valid
+        }
+    }
+
+    /// Stores a credential only if it verifies right now.
+    pub fn submit_credential(&mut self, credential: EcoCertificationCredential, now: u64) -> Result<(), String> {
+        if self.credentials.contains_key(&credential.credential_id) {
+            return Err(format!("Credential {} was already submitted", credential.credential_id));
+        }
+
+        let status = self.check_credential(&credential, now);
+        if !status.is_valid() {
+            return Err(format!("Credential {} failed verification: {:?}", credential.credential_id, status));
+        }
+
+        self.credentials.insert(credential.credential_id.clone(), credential);
+        Ok(())
+    }
+
+    /// Only the issuing certifier may revoke a credential.
+    pub fn revoke_credential(&mut self, credential_id: &str, revoked_by: &str, reason: String, now: u64) -> Result<&EcoCertificationCredential, String> {
+        let credential = self
+            .credentials
+            .get(credential_id)
+            .ok_or_else(|| format!("Credential {} not found", credential_id))?;
+
+        if credential.issuer_did != revoked_by {
+            return Err(format!("Only issuer {} can revoke credential {}", credential.issuer_did, credential_id));
+        }
+
+        if self.revocations.contains_key(credential_id) {
+            return Err(format!("Credential {} is already revoked", credential_id));
+        }
+
+        self.revocations.insert(credential_id.to_string(), CertificationRevocation {
+            credential_id: credential_id.to_string(),
+            revoked_by: revoked_by.to_string(),
+            reason,
+            revoked_at: now,
+        });
+
+        Ok(credential)
+    }
+
+    pub fn get_credential(&self, credential_id: &str) -> Option<&EcoCertificationCredential> {
+        self.credentials.get(credential_id)
+    }
+
+    pub fn get_revocation(&self, credential_id: &str) -> Option<&CertificationRevocation> {
+        self.revocations.get(credential_id)
+    }
+
+    /// Credentials held by `subject_did` that are valid at `at_time`.
+    pub fn verified_certifications(&self, subject_did: &str, at_time: u64) -> Vec<&EcoCertificationCredential> {
+        self.credentials
+            .values()
+            .filter(|credential| credential.subject_did == subject_did)
+            .filter(|credential| self.check_credential(credential, at_time).is_valid())
+            .collect()
+    }
+}
+
+impl Default for CertificationRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,130 @@
+//! Rule packs behind a sector's `compliance_standards` labels.
+//! Each supported standard names the rules it demands of off-chain personal
+//! data; `PrivacyManager::compliance_report` evaluates them against identities,
+//! proofs, consents and access logs and lists every violation by sector.
+
+use serde::{Deserialize, Serialize};
+use crate::data_privacy_act::{BREACH_NOTIFICATION_WINDOW, REQUEST_RESPONSE_WINDOW};
+use crate::privacy_manager::SectorType;
+
+/// Consent to hold and process personal data at all.
+pub const PROCESSING_CONSENT: &str = "personal_data_processing";
+/// Consent to disclose education records to third parties.
+pub const EDUCATION_RECORD_CONSENT: &str = "education_record_disclosure";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ComplianceStandard {
+    Gdpr,
+    Ferpa,
+    PciDss,
+    Sox,
+    /// Republic Act No. 10173, the Philippine Data Privacy Act
+    DataPrivacyAct,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComplianceRule {
+    /// No off-chain record is held past its sector's retention window
+    DataMinimization,
+    /// Every active identity with off-chain data has given consent for the purpose
+    Consent { purpose: String },
+    /// Identities that were revoked or withdrew all consent have no off-chain data left
+    RightToErasure,
+    /// Every logged access to personal data names who accessed it and why
+    AccessLogging,
+    /// Mandatory breaches are reported to the regulator within `window` seconds of discovery
+    BreachNotification { window: u64 },
+    /// Data-subject requests are resolved within `window` seconds of filing
+    RequestResponse { window: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceViolation {
+    pub standard: ComplianceStandard,
+    pub rule: ComplianceRule,
+    pub sector: SectorType,
+    /// The identity or record the violation is about
+    pub subject: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorComplianceReport {
+    pub sector: SectorType,
+    pub standards: Vec<ComplianceStandard>,
+    /// Labels in the sector config with no rule pack behind them
+    pub unsupported_standards: Vec<String>,
+    pub rules_checked: usize,
+    pub violations: Vec<ComplianceViolation>,
+    pub compliant: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    pub generated_at: u64,
+    pub sectors: Vec<SectorComplianceReport>,
+    pub total_violations: usize,
+}
+
+impl ComplianceStandard {
+    /// Parses a `compliance_standards` label such as "PCI DSS".
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            ComplianceStandard::Gdpr,
+            ComplianceStandard::Ferpa,
+            ComplianceStandard::PciDss,
+            ComplianceStandard::Sox,
+            ComplianceStandard::DataPrivacyAct,
+        ]
+            .into_iter()
+            .find(|standard| standard.label().eq_ignore_ascii_case(label.trim()))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComplianceStandard::Gdpr => "GDPR",
+            ComplianceStandard::Ferpa => "FERPA",
+            ComplianceStandard::PciDss => "PCI DSS",
+            ComplianceStandard::Sox => "SOX",
+            ComplianceStandard::DataPrivacyAct => "RA 10173",
+        }
+    }
+
+    pub fn rule_pack(&self) -> Vec<ComplianceRule> {
+        match self {
+            ComplianceStandard::Gdpr => vec![
+                ComplianceRule::DataMinimization,
+                ComplianceRule::Consent { purpose: PROCESSING_CONSENT.to_string() },
+                ComplianceRule::RightToErasure,
+                ComplianceRule::AccessLogging,
+                // Article 33 and the one-month limit of Article 12(3)
+                ComplianceRule::BreachNotification { window: 72 * 3_600 },
+                ComplianceRule::RequestResponse { window: 30 * 86_400 },
+            ],
+            ComplianceStandard::Ferpa => vec![
+                ComplianceRule::Consent { purpose: EDUCATION_RECORD_CONSENT.to_string() },
+                ComplianceRule::AccessLogging,
+            ],
+            // Requirement 3 (keep data only as long as needed) and 10 (log all access)
+            ComplianceStandard::PciDss => vec![
+                ComplianceRule::DataMinimization,
+                ComplianceRule::AccessLogging,
+            ],
+            ComplianceStandard::Sox => vec![ComplianceRule::AccessLogging],
+            ComplianceStandard::DataPrivacyAct => vec![
+                ComplianceRule::DataMinimization,
+                ComplianceRule::Consent { purpose: PROCESSING_CONSENT.to_string() },
+                ComplianceRule::RightToErasure,
+                ComplianceRule::AccessLogging,
+                ComplianceRule::BreachNotification { window: BREACH_NOTIFICATION_WINDOW },
+                ComplianceRule::RequestResponse { window: REQUEST_RESPONSE_WINDOW },
+            ],
+        }
+    }
+}
+
+impl ComplianceReport {
+    pub fn sector(&self, sector: &SectorType) -> Option<&SectorComplianceReport> {
+        self.sectors.iter().find(|report| report.sector == *sector)
+    }
+}
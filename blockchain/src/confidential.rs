@@ -0,0 +1,147 @@
+//! Encrypted transaction payloads.
+//! A payload is sealed once under a random content key, and that key is wrapped
+//! for each recipient (the identity owner, plus the regulator for sectors under
+//! government oversight) through an ephemeral Ristretto key agreement. The chain
+//! carries the ciphertext and a salted hash commitment to the contents.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::zk_proofs::{self, PedersenOpening};
+
+pub const OWNER_RECIPIENT: &str = "owner";
+pub const REGULATOR_RECIPIENT: &str = "regulator";
+
+const KEY_WRAP_DOMAIN: &[u8] = b"lightchain/key-wrap/v1";
+
+/// The content key, encrypted to one recipient's public key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedKey {
+    pub recipient: String,
+    pub ephemeral_public_key: String,
+    pub nonce: String,
+    pub wrapped_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedPayload {
+    pub nonce: String,
+    pub ciphertext: String,
+    pub recipients: Vec<WrappedKey>,
+}
+
+/// What a recipient sees after decrypting. The salt keeps the public
+/// commitment from being brute-forced when the body is guessable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfidentialContents {
+    pub salt: String,
+    pub body: serde_json::Value,
+    pub amount_opening: Option<PedersenOpening>,
+}
+
+impl ConfidentialContents {
+    pub fn new(body: serde_json::Value, amount_opening: Option<PedersenOpening>) -> Self {
+        ConfidentialContents {
+            salt: hex::encode(rand::random::<[u8; 32]>()),
+            body,
+            amount_opening,
+        }
+    }
+
+    /// SHA-256 over the salt and the body; published in place of the body.
+    pub fn commitment(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(self.body.to_string().as_bytes());
+        hex::encode(hasher.finalize())
+    }
+}
+
+impl EncryptedPayload {
+    pub fn has_recipient(&self, recipient: &str) -> bool {
+        self.recipients.iter().any(|wrapped| wrapped.recipient == recipient)
+    }
+}
+
+/// Encrypts the contents for each `(recipient, public_key)` pair.
+pub fn seal(contents: &ConfidentialContents, recipients: &[(&str, &str)]) -> Result<EncryptedPayload, String> {
+    let plaintext = serde_json::to_vec(contents).map_err(|e| format!("Cannot serialize payload: {}", e))?;
+    let content_key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(&content_key)
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| "Cannot encrypt payload".to_string())?;
+
+    let recipients = recipients
+        .iter()
+        .map(|(recipient, public_key)| wrap_key(recipient, public_key, &content_key))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(EncryptedPayload {
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+        recipients,
+    })
+}
+
+/// Decrypts the payload as `recipient` holding `secret`.
+pub fn open(payload: &EncryptedPayload, recipient: &str, secret: &str) -> Result<ConfidentialContents, String> {
+    let wrapped = payload.recipients
+        .iter()
+        .find(|wrapped| wrapped.recipient == recipient)
+        .ok_or_else(|| format!("Payload is not encrypted to {}", recipient))?;
+    let content_key = unwrap_key(wrapped, secret)?;
+    let nonce = hex::decode(&payload.nonce).map_err(|_| "Payload nonce is not valid hex".to_string())?;
+    let ciphertext = hex::decode(&payload.ciphertext).map_err(|_| "Payload ciphertext is not valid hex".to_string())?;
+    let plaintext = ChaCha20Poly1305::new(&content_key)
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "Payload failed authentication".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Cannot decode payload: {}", e))
+}
+
+fn wrap_key(recipient: &str, public_key: &str, content_key: &Key) -> Result<WrappedKey, String> {
+    let recipient_key = zk_proofs::parse_point(public_key)?;
+    let ephemeral_secret = Scalar::random(&mut OsRng);
+    let ephemeral_public = ephemeral_secret * RISTRETTO_BASEPOINT_POINT;
+    let key_encryption_key = derive_key_encryption_key(&(ephemeral_secret * recipient_key), &ephemeral_public, &recipient_key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let wrapped_key = ChaCha20Poly1305::new(&key_encryption_key)
+        .encrypt(&nonce, content_key.as_slice())
+        .map_err(|_| format!("Cannot wrap payload key for {}", recipient))?;
+
+    Ok(WrappedKey {
+        recipient: recipient.to_string(),
+        ephemeral_public_key: zk_proofs::encode_point(&ephemeral_public),
+        nonce: hex::encode(nonce),
+        wrapped_key: hex::encode(wrapped_key),
+    })
+}
+
+fn unwrap_key(wrapped: &WrappedKey, secret: &str) -> Result<Key, String> {
+    let secret = zk_proofs::parse_scalar(secret)?;
+    let ephemeral_public = zk_proofs::parse_point(&wrapped.ephemeral_public_key)?;
+    let key_encryption_key = derive_key_encryption_key(
+        &(secret * ephemeral_public),
+        &ephemeral_public,
+        &(secret * RISTRETTO_BASEPOINT_POINT),
+    );
+    let nonce = hex::decode(&wrapped.nonce).map_err(|_| "Wrapped key nonce is not valid hex".to_string())?;
+    let wrapped_key = hex::decode(&wrapped.wrapped_key).map_err(|_| "Wrapped key is not valid hex".to_string())?;
+    let content_key = ChaCha20Poly1305::new(&key_encryption_key)
+        .decrypt(Nonce::from_slice(&nonce), wrapped_key.as_slice())
+        .map_err(|_| format!("Secret does not unlock the {} key", wrapped.recipient))?;
+    Ok(*Key::from_slice(&content_key))
+}
+
+fn derive_key_encryption_key(shared: &RistrettoPoint, ephemeral_public: &RistrettoPoint, recipient_key: &RistrettoPoint) -> Key {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_WRAP_DOMAIN);
+    hasher.update(shared.compress().as_bytes());
+    hasher.update(ephemeral_public.compress().as_bytes());
+    hasher.update(recipient_key.compress().as_bytes());
+    hasher.finalize()
+}
@@ -0,0 +1,351 @@
+//! W3C Verifiable Credentials issued by `did:veritoken` DIDs.
+//! Issuers sign credentials with an assertion key from their DID document and
+//! publish revocation status lists on chain. `verify_credential` checks the
+//! signature against the resolved key, the validity window and the issuer's
+//! status list; only its report can become a `CredentialVerification` transaction.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use crate::blockchain::Blockchain;
+use crate::certification::{CertificationStatus, EcoCertificationCredential};
+use crate::did::{DidRegistry, VerificationRelationship};
+use crate::signing;
+use crate::transaction::{Transaction, TransactionType};
+
+pub const VC_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+pub const VC_TYPE: &str = "VerifiableCredential";
+pub const PROOF_TYPE: &str = "Ed25519Signature2020";
+pub const STATUS_ENTRY_TYPE: &str = "StatusList2021Entry";
+pub const REVOCATION_PURPOSE: &str = "revocation";
+/// Entries per status list. Large enough that a lookup does not reveal which credential is checked.
+pub const STATUS_LIST_LENGTH: usize = 4096;
+
+/// Points at the entry for a credential in its issuer's status list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialStatus {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub status_type: String,
+    pub status_purpose: String,
+    pub status_list_index: usize,
+    pub status_list_credential: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialProof {
+    #[serde(rename = "type")]
+    pub proof_type: String,
+    pub created: u64,
+    /// DID URL of the issuer key, e.g. `did:veritoken-certifier:mainnet:abc#key-1`
+    pub verification_method: String,
+    pub proof_purpose: String,
+    pub proof_value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiableCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub credential_type: Vec<String>,
+    pub issuer: String,
+    pub issuance_date: u64,
+    pub expiration_date: Option<u64>,
+    /// Claims about the subject; `id` names the subject DID
+    pub credential_subject: Value,
+    pub credential_status: Option<CredentialStatus>,
+    pub proof: Option<CredentialProof>,
+}
+
+/// A revocation bitstring published by an issuer. Bit `i` set means the credential
+/// at index `i` is revoked; bits are never cleared once set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusList {
+    pub list_id: String,
+    pub issuer: String,
+    /// Hex-encoded bitstring, index 0 in the highest bit of the first byte
+    pub encoded_list: String,
+    pub version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CredentialCheck {
+    Valid,
+    Malformed,
+    UnknownIssuer,
+    InvalidSignature,
+    NotYetValid,
+    Expired,
+    Revoked,
+    StatusListNotFound,
+}
+
+/// The outcome of checking a credential, recorded on chain by `CredentialVerification`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialVerificationReport {
+    pub credential_id: String,
+    pub credential_type: String,
+    pub issuer_did: String,
+    pub subject_did: String,
+    /// Hash of the credential exactly as presented, proof included
+    pub credential_hash: String,
+    pub checked_at: u64,
+    /// Why the credential failed, if it did
+    pub failure: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatusListRegistry {
+    lists: HashMap<String, StatusList>,
+}
+
+impl VerifiableCredential {
+    /// An unsigned credential valid from now for `validity_period` seconds, if given.
+    pub fn new(issuer: &str, credential_type: &str, credential_subject: Value, validity_period: Option<u64>) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        VerifiableCredential {
+            context: vec![VC_CONTEXT.to_string()],
+            id: format!("urn:veritoken:credential:{:016x}", rand::random::<u64>()),
+            credential_type: vec![VC_TYPE.to_string(), credential_type.to_string()],
+            issuer: issuer.to_string(),
+            issuance_date: now,
+            expiration_date: validity_period.map(|period| now + period),
+            credential_subject,
+            credential_status: None,
+            proof: None,
+        }
+    }
+
+    /// Places the credential at `index` of the issuer's status list; call before signing.
+    pub fn set_status(&mut self, list_id: &str, index: usize) {
+        self.credential_status = Some(CredentialStatus {
+            id: format!("{}#{}", list_id, index),
+            status_type: STATUS_ENTRY_TYPE.to_string(),
+            status_purpose: REVOCATION_PURPOSE.to_string(),
+            status_list_index: index,
+            status_list_credential: list_id.to_string(),
+        });
+    }
+
+    pub fn subject_did(&self) -> Option<&str> {
+        self.credential_subject.get("id").and_then(Value::as_str)
+    }
+
+    /// The most specific type, e.g. "ProductOriginCredential".
+    pub fn primary_type(&self) -> &str {
+        self.credential_type
+            .iter()
+            .rfind(|credential_type| *credential_type != VC_TYPE)
+            .map(String::as_str)
+            .unwrap_or(VC_TYPE)
+    }
+
+    /// The bytes the issuer signs: the credential without its proof.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let unsigned = VerifiableCredential { proof: None, ..self.clone() };
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    /// Signs with the issuer key `verification_method`, a DID URL under the issuer.
+    pub fn sign(&mut self, private_key: &str, verification_method: &str) -> Result<(), String> {
+        let proof_value = signing::sign_message(private_key, &self.signing_payload())?;
+        self.proof = Some(CredentialProof {
+            proof_type: PROOF_TYPE.to_string(),
+            created: self.issuance_date,
+            verification_method: verification_method.to_string(),
+            proof_purpose: "assertionMethod".to_string(),
+            proof_value,
+        });
+        Ok(())
+    }
+
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(serde_json::to_vec(self).unwrap_or_default()))
+    }
+}
+
+impl StatusList {
+    pub fn new(list_id: &str, issuer: &str) -> Self {
+        StatusList {
+            list_id: list_id.to_string(),
+            issuer: issuer.to_string(),
+            encoded_list: hex::encode(vec![0u8; STATUS_LIST_LENGTH / 8]),
+            version: 1,
+        }
+    }
+
+    fn bits(&self) -> Vec<u8> {
+        hex::decode(&self.encoded_list).unwrap_or_default()
+    }
+
+    pub fn is_revoked(&self, index: usize) -> bool {
+        self.bits()
+            .get(index / 8)
+            .map(|byte| byte & (0x80 >> (index % 8)) != 0)
+            .unwrap_or(false)
+    }
+
+    /// Sets the bit for `index` and bumps the version, ready to publish again.
+    pub fn revoke(&mut self, index: usize) -> Result<(), String> {
+        let mut bits = self.bits();
+        let byte = bits
+            .get_mut(index / 8)
+            .ok_or_else(|| format!("Index {} is outside status list {}", index, self.list_id))?;
+        *byte |= 0x80 >> (index % 8);
+        self.encoded_list = hex::encode(bits);
+        self.version += 1;
+        Ok(())
+    }
+
+    pub fn revoked_count(&self) -> usize {
+        self.bits().iter().map(|byte| byte.count_ones() as usize).sum()
+    }
+}
+
+impl CredentialVerificationReport {
+    pub fn is_valid(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Reports an eco-certification checked by the certification registry.
+    pub fn for_certification(credential: &EcoCertificationCredential, status: &CertificationStatus, checked_at: u64) -> Self {
+        CredentialVerificationReport {
+            credential_id: credential.credential_id.clone(),
+            credential_type: credential.certification_type.clone(),
+            issuer_did: credential.issuer_did.clone(),
+            subject_did: credential.subject_did.clone(),
+            credential_hash: hex::encode(Sha256::digest(serde_json::to_vec(credential).unwrap_or_default())),
+            checked_at,
+            failure: (!status.is_valid()).then(|| format!("{:?}", status)),
+        }
+    }
+}
+
+impl StatusListRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds every status list by replaying the chain.
+    pub fn from_chain(blockchain: &Blockchain) -> Result<Self, String> {
+        let mut registry = Self::new();
+        for transaction in blockchain.get_all_transactions() {
+            registry.apply_transaction(transaction)?;
+        }
+        Ok(registry)
+    }
+
+    /// Applies a status list publication from its issuer. A republished list must
+    /// carry a higher version and keep every revocation already published.
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<(), String> {
+        let TransactionType::CredentialStatusListPublication { list } = &tx.transaction_type else {
+            return Ok(());
+        };
+        if tx.from != list.issuer {
+            return Err(format!("Status list {} can only be published by {}", list.list_id, list.issuer));
+        }
+        if list.bits().len() != STATUS_LIST_LENGTH / 8 {
+            return Err(format!("Status list {} must hold {} entries", list.list_id, STATUS_LIST_LENGTH));
+        }
+        if let Some(current) = self.lists.get(&list.list_id) {
+            if current.issuer != list.issuer {
+                return Err(format!("Status list {} belongs to {}", list.list_id, current.issuer));
+            }
+            if list.version <= current.version {
+                return Err(format!("Status list {} is already at version {}", list.list_id, current.version));
+            }
+            let cleared = current.bits()
+                .iter()
+                .zip(list.bits())
+                .any(|(old, new)| old & !new != 0);
+            if cleared {
+                return Err(format!("Status list {} cannot un-revoke a credential", list.list_id));
+            }
+        }
+        self.lists.insert(list.list_id.clone(), list.clone());
+        Ok(())
+    }
+
+    pub fn get_list(&self, list_id: &str) -> Option<&StatusList> {
+        self.lists.get(list_id)
+    }
+}
+
+/// Checks a credential's shape, its proof against the issuer's resolved assertion
+/// keys, its validity window at `at_time` and its issuer's status list.
+pub fn check_credential(
+    credential: &VerifiableCredential,
+    dids: &DidRegistry,
+    status_lists: &StatusListRegistry,
+    at_time: u64,
+) -> CredentialCheck {
+    let well_formed = credential.context.first().map(String::as_str) == Some(VC_CONTEXT)
+        && credential.credential_type.iter().any(|credential_type| credential_type == VC_TYPE)
+        && credential.subject_did().is_some();
+    let proof = match &credential.proof {
+        Some(proof) if well_formed && proof.proof_type == PROOF_TYPE => proof,
+        _ => return CredentialCheck::Malformed,
+    };
+
+    if dids.resolve(&credential.issuer).did_document.is_none() {
+        return CredentialCheck::UnknownIssuer;
+    }
+    let signed_by = dids.verify_signature(
+        &credential.issuer,
+        VerificationRelationship::AssertionMethod,
+        &credential.signing_payload(),
+        &proof.proof_value,
+    );
+    if signed_by.as_deref() != Ok(proof.verification_method.as_str()) {
+        return CredentialCheck::InvalidSignature;
+    }
+
+    if at_time < credential.issuance_date {
+        return CredentialCheck::NotYetValid;
+    }
+    if credential.expiration_date.is_some_and(|expires_at| at_time >= expires_at) {
+        return CredentialCheck::Expired;
+    }
+
+    match &credential.credential_status {
+        None => CredentialCheck::Valid,
+        Some(status) => match status_lists.get_list(&status.status_list_credential) {
+            Some(list) if list.issuer == credential.issuer => {
+                if list.is_revoked(status.status_list_index) {
+                    CredentialCheck::Revoked
+                } else {
+                    CredentialCheck::Valid
+                }
+            }
+            _ => CredentialCheck::StatusListNotFound,
+        },
+    }
+}
+
+pub fn verify_credential(
+    credential: &VerifiableCredential,
+    dids: &DidRegistry,
+    status_lists: &StatusListRegistry,
+    at_time: u64,
+) -> CredentialVerificationReport {
+    let check = check_credential(credential, dids, status_lists, at_time);
+    CredentialVerificationReport {
+        credential_id: credential.id.clone(),
+        credential_type: credential.primary_type().to_string(),
+        issuer_did: credential.issuer.clone(),
+        subject_did: credential.subject_did().unwrap_or_default().to_string(),
+        credential_hash: credential.hash(),
+        checked_at: at_time,
+        failure: (check != CredentialCheck::Valid).then(|| format!("{:?}", check)),
+    }
+}
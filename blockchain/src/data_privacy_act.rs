@@ -0,0 +1,172 @@
+//! Republic Act No. 10173, the Philippine Data Privacy Act of 2012.
+//! Data-subject access and erasure requests and personal data breach notices
+//! are recorded on chain like other identity records. `PrivacyManager::npc_report`
+//! summarises them, with consents, in the shape the National Privacy Commission
+//! asks of personal information controllers.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::privacy_manager::{AnonymousIdentity, ConsentRecord, DataAccessEvent, PrivacyProof, SectorType};
+use crate::retention::RecordCategory;
+use crate::zk_proofs::SchnorrProof;
+
+/// NPC Circular 16-03: the Commission is notified within 72 hours of knowledge of a breach.
+pub const BREACH_NOTIFICATION_WINDOW: u64 = 72 * 3_600;
+/// Time allowed to act on a data-subject request before it counts as overdue.
+pub const REQUEST_RESPONSE_WINDOW: u64 = 15 * 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataSubjectRight {
+    Access,
+    Erasure,
+}
+
+/// How a data subject shows a request is theirs, over
+/// `privacy_manager::data_subject_request_statement`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SubjectAuthorization {
+    /// Proof of the anonymous identity's secret
+    IdentityProof(SchnorrProof),
+    /// Signature by an authentication key of the subject's DID
+    DidSignature(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RequestOutcome {
+    /// `evidence` is the export digest for access, or the last deletion receipt hash for erasure
+    Fulfilled { evidence: String },
+    Denied { reason: String },
+}
+
+/// A request by a data subject, named by anonymous id or DID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataSubjectRequest {
+    pub request_id: String,
+    pub subject: String,
+    pub sector: SectorType,
+    pub right: DataSubjectRight,
+    pub filed_at: u64,
+    pub resolved_at: Option<u64>,
+    pub outcome: Option<RequestOutcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonalRecordExport {
+    pub record_id: String,
+    pub category: RecordCategory,
+    pub sector: SectorType,
+    pub stored_at: u64,
+    pub contents: serde_json::Value,
+}
+
+/// Everything held about a subject, handed over on an access request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubjectAccessExport {
+    pub request_id: String,
+    pub subject: String,
+    pub identity: Option<AnonymousIdentity>,
+    pub consents: Vec<ConsentRecord>,
+    pub privacy_proofs: Vec<PrivacyProof>,
+    pub personal_records: Vec<PersonalRecordExport>,
+    pub accesses: Vec<DataAccessEvent>,
+    /// Profiles the integration service keeps under the subject's DID
+    pub profiles: Vec<serde_json::Value>,
+    pub generated_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BreachAudience {
+    Commission,
+    DataSubjects,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreachNotice {
+    pub breach_id: String,
+    pub description: String,
+    pub affected_sectors: Vec<SectorType>,
+    pub affected_subjects: usize,
+    /// Sensitive personal information makes notification mandatory
+    pub sensitive_personal_information: bool,
+    pub discovered_at: u64,
+    pub reported_at: u64,
+    pub commission_notified_at: Option<u64>,
+    pub subjects_notified_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsentSummary {
+    pub active: usize,
+    pub granted_in_period: usize,
+    pub withdrawn_in_period: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestSummary {
+    pub received: usize,
+    pub fulfilled: usize,
+    pub denied: usize,
+    pub pending: usize,
+    pub overdue: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpcReport {
+    pub personal_information_controller: String,
+    pub data_protection_officer: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub consents: ConsentSummary,
+    pub access_requests: RequestSummary,
+    pub erasure_requests: RequestSummary,
+    pub breaches: Vec<BreachNotice>,
+    pub mandatory_notifications: usize,
+    pub late_notifications: usize,
+    pub generated_at: u64,
+}
+
+impl DataSubjectRequest {
+    pub fn is_pending(&self) -> bool {
+        self.outcome.is_none()
+    }
+
+    /// Unresolved past `window`, or resolved after it.
+    pub fn is_overdue(&self, window: u64, now: u64) -> bool {
+        self.resolved_at.unwrap_or(now) > self.filed_at.saturating_add(window)
+    }
+}
+
+impl SubjectAccessExport {
+    pub fn digest(&self) -> String {
+        hex::encode(Sha256::digest(serde_json::to_vec(self).unwrap_or_default()))
+    }
+}
+
+impl BreachNotice {
+    pub fn is_mandatory(&self) -> bool {
+        self.sensitive_personal_information
+    }
+
+    /// The Commission was not told within `window` of discovery.
+    pub fn is_notification_late(&self, window: u64, now: u64) -> bool {
+        self.commission_notified_at.unwrap_or(now) > self.discovered_at.saturating_add(window)
+    }
+}
+
+impl RequestSummary {
+    pub fn from_requests<'a>(requests: impl Iterator<Item = &'a DataSubjectRequest>, now: u64) -> Self {
+        let mut summary = RequestSummary::default();
+        for request in requests {
+            summary.received += 1;
+            match &request.outcome {
+                Some(RequestOutcome::Fulfilled { .. }) => summary.fulfilled += 1,
+                Some(RequestOutcome::Denied { .. }) => summary.denied += 1,
+                None => summary.pending += 1,
+            }
+            if request.is_overdue(REQUEST_RESPONSE_WINDOW, now) {
+                summary.overdue += 1;
+            }
+        }
+        summary
+    }
+}
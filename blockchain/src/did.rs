@@ -0,0 +1,403 @@
+//! The `did:veritoken` DID method.
+//! Identifiers look like `did:veritoken-merchant:mainnet:green-shop-ph`: the
+//! method may carry a subject kind after a hyphen, followed by the network and
+//! a method-specific id. Documents are created, updated and deactivated through
+//! chain transactions signed with an authentication key of the DID's controller,
+//! and `DidRegistry::resolve` returns W3C DID resolution results.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::blockchain::Blockchain;
+use crate::signing;
+use crate::transaction::{Transaction, TransactionType};
+
+pub const DID_METHOD: &str = "veritoken";
+pub const DID_CONTEXTS: [&str; 2] = [
+    "https://www.w3.org/ns/did/v1",
+    "https://w3id.org/security/suites/ed25519-2020/v1",
+];
+pub const ED25519_KEY_TYPE: &str = "Ed25519VerificationKey2020";
+pub const MARKETPLACE_SERVICE_TYPE: &str = "MarketplaceService";
+
+/// The parts of a `did:veritoken[-kind]:network:id` identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParsedDid {
+    /// e.g. "merchant" for `did:veritoken-merchant:...`
+    pub kind: Option<String>,
+    pub network: String,
+    pub specific_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub controller: String,
+    pub public_key_hex: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceEndpoint {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub service_type: String,
+    pub service_endpoint: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    /// The DID whose authentication keys may update or deactivate this document
+    pub controller: String,
+    pub verification_method: Vec<VerificationMethod>,
+    pub authentication: Vec<String>,
+    pub assertion_method: Vec<String>,
+    pub service: Vec<ServiceEndpoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationRelationship {
+    /// Keys that may sign transactions for the DID
+    Authentication,
+    /// Keys that may sign statements such as receipts and credentials
+    AssertionMethod,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DidRecord {
+    pub document: DidDocument,
+    pub created: u64,
+    pub updated: u64,
+    pub deactivated: bool,
+    /// Number of updates since creation
+    pub version_id: u64,
+    pub last_tx_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocumentMetadata {
+    pub created: u64,
+    pub updated: u64,
+    pub deactivated: bool,
+    pub version_id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionMetadata {
+    pub content_type: String,
+    /// "invalidDid" or "notFound" when resolution fails
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionResult {
+    pub did_document: Option<DidDocument>,
+    pub did_document_metadata: Option<DidDocumentMetadata>,
+    pub did_resolution_metadata: DidResolutionMetadata,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DidRegistry {
+    records: HashMap<String, DidRecord>,
+}
+
+pub fn parse_did(did: &str) -> Result<ParsedDid, String> {
+    let parts: Vec<&str> = did.split(':').collect();
+    let [scheme, method, network, specific_id] = parts[..] else {
+        return Err(format!("{} is not of the form did:{}:<network>:<id>", did, DID_METHOD));
+    };
+    let kind = match method.strip_prefix(DID_METHOD) {
+        Some("") => None,
+        Some(kind) => match kind.strip_prefix('-') {
+            Some(kind) if !kind.is_empty() => Some(kind),
+            _ => return Err(format!("{} has an invalid method", did)),
+        },
+        None => return Err(format!("{} is not a did:{} identifier", did, DID_METHOD)),
+    };
+    let valid_segment = |segment: &str| {
+        !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    };
+    if scheme != "did" || !valid_segment(network) || !valid_segment(specific_id) {
+        return Err(format!("{} is not a valid DID", did));
+    }
+    Ok(ParsedDid {
+        kind: kind.map(str::to_string),
+        network: network.to_string(),
+        specific_id: specific_id.to_string(),
+    })
+}
+
+impl DidDocument {
+    /// A self-controlled document with one key for authentication and assertions.
+    pub fn new(did: &str, public_key: &str) -> Self {
+        let mut document = Self::controlled_by(did, did);
+        document.add_key("key-1", public_key, &[VerificationRelationship::Authentication, VerificationRelationship::AssertionMethod]);
+        document
+    }
+
+    /// A document with no keys of its own, managed through `controller`'s keys.
+    pub fn controlled_by(did: &str, controller: &str) -> Self {
+        DidDocument {
+            context: DID_CONTEXTS.iter().map(|context| context.to_string()).collect(),
+            id: did.to_string(),
+            controller: controller.to_string(),
+            verification_method: Vec::new(),
+            authentication: Vec::new(),
+            assertion_method: Vec::new(),
+            service: Vec::new(),
+        }
+    }
+
+    /// Adds or replaces the key `did#fragment` and lists it under each relationship.
+    pub fn add_key(&mut self, fragment: &str, public_key: &str, relationships: &[VerificationRelationship]) {
+        let id = format!("{}#{}", self.id, fragment);
+        self.remove_key(fragment);
+        self.verification_method.push(VerificationMethod {
+            id: id.clone(),
+            key_type: ED25519_KEY_TYPE.to_string(),
+            controller: self.controller.clone(),
+            public_key_hex: public_key.to_string(),
+        });
+        for relationship in relationships {
+            match relationship {
+                VerificationRelationship::Authentication => self.authentication.push(id.clone()),
+                VerificationRelationship::AssertionMethod => self.assertion_method.push(id.clone()),
+            }
+        }
+    }
+
+    pub fn remove_key(&mut self, fragment: &str) {
+        let id = format!("{}#{}", self.id, fragment);
+        self.verification_method.retain(|method| method.id != id);
+        self.authentication.retain(|reference| *reference != id);
+        self.assertion_method.retain(|reference| *reference != id);
+    }
+
+    pub fn add_service(&mut self, fragment: &str, service_type: &str, endpoint: &str) {
+        let id = format!("{}#{}", self.id, fragment);
+        self.service.retain(|service| service.id != id);
+        self.service.push(ServiceEndpoint {
+            id,
+            service_type: service_type.to_string(),
+            service_endpoint: endpoint.to_string(),
+        });
+    }
+
+    /// Keys listed under a relationship.
+    pub fn keys_for(&self, relationship: VerificationRelationship) -> Vec<&VerificationMethod> {
+        let references = match relationship {
+            VerificationRelationship::Authentication => &self.authentication,
+            VerificationRelationship::AssertionMethod => &self.assertion_method,
+        };
+        self.verification_method
+            .iter()
+            .filter(|method| references.contains(&method.id))
+            .collect()
+    }
+
+    /// Checks the DID, that every key is a valid Ed25519 key under this DID,
+    /// and that every reference names a key in the document.
+    pub fn validate(&self) -> Result<(), String> {
+        parse_did(&self.id)?;
+        parse_did(&self.controller)?;
+        for method in &self.verification_method {
+            if !method.id.starts_with(&format!("{}#", self.id)) {
+                return Err(format!("Key {} does not belong to {}", method.id, self.id));
+            }
+            if method.key_type != ED25519_KEY_TYPE || !signing::is_valid_public_key(&method.public_key_hex) {
+                return Err(format!("Key {} is not a valid {}", method.id, ED25519_KEY_TYPE));
+            }
+            if self.verification_method.iter().filter(|other| other.id == method.id).count() > 1 {
+                return Err(format!("Key {} is listed twice", method.id));
+            }
+        }
+        let dangling = self.authentication
+            .iter()
+            .chain(&self.assertion_method)
+            .find(|reference| !self.verification_method.iter().any(|method| method.id == **reference));
+        if let Some(reference) = dangling {
+            return Err(format!("{} references missing key {}", self.id, reference));
+        }
+        if self.controller == self.id && self.authentication.is_empty() {
+            return Err(format!("Self-controlled {} needs an authentication key", self.id));
+        }
+        Ok(())
+    }
+}
+
+impl DidRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds every DID document by replaying the chain.
+    pub fn from_chain(blockchain: &Blockchain) -> Result<Self, String> {
+        let mut registry = Self::new();
+        for transaction in blockchain.get_all_transactions() {
+            registry.apply_transaction(transaction)?;
+        }
+        Ok(registry)
+    }
+
+    /// Validates a DID transaction against the current documents and applies it.
+    /// Other transactions are ignored. Nothing changes on error.
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<(), String> {
+        match &tx.transaction_type {
+            TransactionType::DidCreation { document } => {
+                document.validate()?;
+                if self.records.contains_key(&document.id) {
+                    return Err(format!("{} is already registered", document.id));
+                }
+                let signer = if document.controller == document.id { document } else { self.active_document(&document.controller)? };
+                self.authorize(tx, signer)?;
+                self.records.insert(document.id.clone(), DidRecord {
+                    document: document.clone(),
+                    created: tx.timestamp,
+                    updated: tx.timestamp,
+                    deactivated: false,
+                    version_id: 0,
+                    last_tx_id: tx.id.clone(),
+                });
+                Ok(())
+            }
+            TransactionType::DidUpdate { document, previous_version_id } => {
+                document.validate()?;
+                self.authorize_controller(tx, &document.id)?;
+                if document.controller != document.id {
+                    self.active_document(&document.controller)?;
+                }
+                let record = self.records.get_mut(&document.id).unwrap();
+                if record.version_id != *previous_version_id {
+                    return Err(format!(
+                        "Update {} replaces version {} of {}, current version is {}",
+                        tx.id, previous_version_id, document.id, record.version_id
+                    ));
+                }
+                record.document = document.clone();
+                record.updated = tx.timestamp;
+                record.version_id += 1;
+                record.last_tx_id = tx.id.clone();
+                Ok(())
+            }
+            TransactionType::DidDeactivation { did } => {
+                self.authorize_controller(tx, did)?;
+                let record = self.records.get_mut(did).unwrap();
+                record.deactivated = true;
+                record.updated = tx.timestamp;
+                record.last_tx_id = tx.id.clone();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The sender must be the current controller of an active DID and sign with
+    /// one of the controller's authentication keys.
+    fn authorize_controller(&self, tx: &Transaction, did: &str) -> Result<(), String> {
+        let controller = self.active_document(did)?.controller.clone();
+        self.authorize(tx, self.active_document(&controller)?)
+    }
+
+    fn authorize(&self, tx: &Transaction, controller: &DidDocument) -> Result<(), String> {
+        if tx.from != controller.id {
+            return Err(format!("{} is not the controller {}", tx.from, controller.id));
+        }
+        let signed = controller
+            .keys_for(VerificationRelationship::Authentication)
+            .iter()
+            .any(|method| tx.verify_signature(&method.public_key_hex));
+        if !signed {
+            return Err(format!("Transaction {} is not signed by an authentication key of {}", tx.id, controller.id));
+        }
+        Ok(())
+    }
+
+    fn active_document(&self, did: &str) -> Result<&DidDocument, String> {
+        let record = self.records
+            .get(did)
+            .ok_or_else(|| format!("{} is not registered", did))?;
+        if record.deactivated {
+            return Err(format!("{} is deactivated", did));
+        }
+        Ok(&record.document)
+    }
+
+    /// W3C DID resolution. A deactivated DID still resolves, flagged in its metadata.
+    pub fn resolve(&self, did: &str) -> DidResolutionResult {
+        let failure = |error: &str| DidResolutionResult {
+            did_document: None,
+            did_document_metadata: None,
+            did_resolution_metadata: DidResolutionMetadata {
+                content_type: "application/did+json".to_string(),
+                error: Some(error.to_string()),
+            },
+        };
+        if parse_did(did).is_err() {
+            return failure("invalidDid");
+        }
+        match self.records.get(did) {
+            Some(record) => DidResolutionResult {
+                did_document: Some(record.document.clone()),
+                did_document_metadata: Some(DidDocumentMetadata {
+                    created: record.created,
+                    updated: record.updated,
+                    deactivated: record.deactivated,
+                    version_id: record.version_id,
+                }),
+                did_resolution_metadata: DidResolutionMetadata {
+                    content_type: "application/did+json".to_string(),
+                    error: None,
+                },
+            },
+            None => failure("notFound"),
+        }
+    }
+
+    /// Dereferences a DID URL such as `did:veritoken:mainnet:abc#key-1`.
+    pub fn resolve_key(&self, did_url: &str) -> Option<&VerificationMethod> {
+        let did = did_url.split('#').next()?;
+        self.records
+            .get(did)?
+            .document
+            .verification_method
+            .iter()
+            .find(|method| method.id == did_url)
+    }
+
+    pub fn get_record(&self, did: &str) -> Option<&DidRecord> {
+        self.records.get(did)
+    }
+
+    /// Checks `signature` over `message` against the DID's keys for the relationship
+    /// and returns the id of the key that made it. A document with no keys of its
+    /// own, like a product's, is checked against its controller's keys.
+    pub fn verify_signature(&self, did: &str, relationship: VerificationRelationship, message: &[u8], signature: &str) -> Result<String, String> {
+        let mut document = self.active_document(did)?;
+        if document.verification_method.is_empty() && document.controller != document.id {
+            document = self.active_document(&document.controller)?;
+        }
+        document
+            .keys_for(relationship)
+            .into_iter()
+            .find(|method| signing::verify_message(&method.public_key_hex, message, signature))
+            .map(|method| method.id.clone())
+            .ok_or_else(|| format!("Signature does not match any {:?} key of {}", relationship, did))
+    }
+
+    /// Checks that a transaction is signed by an authentication key of its sender's DID.
+    pub fn verify_transaction(&self, tx: &Transaction) -> Result<String, String> {
+        let signature = tx.signature
+            .as_ref()
+            .ok_or_else(|| format!("Transaction {} is not signed", tx.id))?;
+        self.verify_signature(&tx.from, VerificationRelationship::Authentication, &tx.signing_payload(), signature)
+    }
+}
@@ -0,0 +1,206 @@
+//! Selective disclosure of transaction fields.
+//! A sector's `transparency_level` decides which fields of a transaction appear
+//! in its public view. Every field, shown or not, carries a salted commitment,
+//! so whoever holds the salts can later prove the full record matches the view.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use crate::privacy_manager::SectorType;
+use crate::transaction::Transaction;
+
+/// Minimum transparency level at which each kind of field is shown in full.
+pub const IDENTITY_DISCLOSURE_LEVEL: f64 = 0.8;
+pub const AMOUNT_DISCLOSURE_LEVEL: f64 = 0.5;
+pub const DETAIL_DISCLOSURE_LEVEL: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldClass {
+    /// DIDs, addresses and anonymous ids
+    Identity,
+    Amount,
+    /// Environmental measurements stay public at every level
+    Environmental,
+    Detail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldVisibility {
+    Disclosed,
+    /// Amounts below the disclosure level show only their order of magnitude
+    Banded,
+    Committed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisclosedField {
+    pub name: String,
+    pub class: FieldClass,
+    pub visibility: FieldVisibility,
+    pub value: Option<Value>,
+    pub band: Option<String>,
+    pub commitment: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactedView {
+    pub tx_id: String,
+    pub transaction_type: String,
+    pub sector: SectorType,
+    pub transparency_level: f64,
+    pub fields: Vec<DisclosedField>,
+    /// Commits to every field commitment in order
+    pub record_commitment: String,
+}
+
+/// The salts behind a view's commitments, kept by the record holder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisclosureOpening {
+    pub tx_id: String,
+    pub salts: BTreeMap<String, String>,
+}
+
+/// Produces the public view of `tx` for a sector and the opening that proves it.
+pub fn redact(tx: &Transaction, sector: SectorType, transparency_level: f64) -> (RedactedView, DisclosureOpening) {
+    let mut salts = BTreeMap::new();
+    let fields = record_fields(tx)
+        .into_iter()
+        .map(|(name, value)| {
+            let salt = hex::encode(rand::random::<[u8; 16]>());
+            let class = classify_field(&name);
+            let visibility = visibility_for(class, transparency_level);
+            let field = DisclosedField {
+                commitment: field_commitment(&name, &value, &salt),
+                value: (visibility == FieldVisibility::Disclosed).then(|| value.clone()),
+                band: (visibility == FieldVisibility::Banded).then(|| value.as_f64().map(amount_band)).flatten(),
+                name: name.clone(),
+                class,
+                visibility,
+            };
+            salts.insert(name, salt);
+            field
+        })
+        .collect::<Vec<_>>();
+
+    let view = RedactedView {
+        tx_id: tx.id.clone(),
+        transaction_type: tx.get_type_name().to_string(),
+        sector,
+        transparency_level,
+        record_commitment: record_commitment(&fields),
+        fields,
+    };
+    (view, DisclosureOpening { tx_id: tx.id.clone(), salts })
+}
+
+/// Checks that `tx` is the record behind `view`: every commitment opens to the
+/// transaction's field, and every shown value or band matches it.
+pub fn verify_record(view: &RedactedView, tx: &Transaction, opening: &DisclosureOpening) -> Result<(), String> {
+    if view.tx_id != tx.id || opening.tx_id != tx.id {
+        return Err(format!("View {} does not describe transaction {}", view.tx_id, tx.id));
+    }
+    if view.record_commitment != record_commitment(&view.fields) {
+        return Err(format!("View {} has an inconsistent record commitment", view.tx_id));
+    }
+
+    let fields = record_fields(tx);
+    if fields.len() != view.fields.len() {
+        return Err(format!("View {} does not cover every field of the record", view.tx_id));
+    }
+    for ((name, value), field) in fields.iter().zip(&view.fields) {
+        let salt = opening.salts
+            .get(name)
+            .ok_or_else(|| format!("Opening has no salt for field {}", name))?;
+        if field.name != *name || field.commitment != field_commitment(name, value, salt) {
+            return Err(format!("Field {} does not match its commitment", name));
+        }
+        let shown_matches = match field.visibility {
+            FieldVisibility::Disclosed => field.value.as_ref() == Some(value),
+            FieldVisibility::Banded => field.band == value.as_f64().map(amount_band),
+            FieldVisibility::Committed => field.value.is_none() && field.band.is_none(),
+        };
+        if !shown_matches {
+            return Err(format!("Field {} is shown differently from the record", name));
+        }
+    }
+    Ok(())
+}
+
+/// Proves a single committed field without opening the rest of the record.
+pub fn verify_field(view: &RedactedView, name: &str, value: &Value, salt: &str) -> bool {
+    view.fields
+        .iter()
+        .any(|field| field.name == name && field.commitment == field_commitment(name, value, salt))
+}
+
+pub fn visibility_for(class: FieldClass, transparency_level: f64) -> FieldVisibility {
+    match class {
+        FieldClass::Environmental => FieldVisibility::Disclosed,
+        FieldClass::Identity if transparency_level >= IDENTITY_DISCLOSURE_LEVEL => FieldVisibility::Disclosed,
+        FieldClass::Amount if transparency_level >= AMOUNT_DISCLOSURE_LEVEL => FieldVisibility::Disclosed,
+        FieldClass::Amount => FieldVisibility::Banded,
+        FieldClass::Detail if transparency_level >= DETAIL_DISCLOSURE_LEVEL => FieldVisibility::Disclosed,
+        _ => FieldVisibility::Committed,
+    }
+}
+
+pub fn classify_field(name: &str) -> FieldClass {
+    match name {
+        "from" | "buyer" | "seller" | "recipient" | "voter" | "delegate" | "delegator" | "proposer"
+        | "beneficiary" | "holder" | "anonymous_id" | "entity_id" => FieldClass::Identity,
+        "carbon_footprint" | "sustainability_score" | "impact_type" | "impact_value" | "measurement_unit" => {
+            FieldClass::Environmental
+        }
+        _ if name.ends_with("_did") => FieldClass::Identity,
+        _ if name == "amount" || name.ends_with("_amount") || name.ends_with("_cost") => FieldClass::Amount,
+        _ => FieldClass::Detail,
+    }
+}
+
+/// Order-of-magnitude band such as "1K-10K".
+pub fn amount_band(amount: f64) -> String {
+    const BANDS: [(f64, &str); 5] = [
+        (100.0, "<100"),
+        (1_000.0, "100-1K"),
+        (10_000.0, "1K-10K"),
+        (100_000.0, "10K-100K"),
+        (1_000_000.0, "100K-1M"),
+    ];
+    BANDS
+        .iter()
+        .find(|(upper, _)| amount < *upper)
+        .map(|(_, band)| band.to_string())
+        .unwrap_or_else(|| "1M+".to_string())
+}
+
+/// The sender, timestamp and payload fields of a transaction, sorted by name.
+fn record_fields(tx: &Transaction) -> Vec<(String, Value)> {
+    let mut fields = BTreeMap::new();
+    fields.insert("from".to_string(), Value::String(tx.from.clone()));
+    fields.insert("timestamp".to_string(), Value::from(tx.timestamp));
+    if let Ok(Value::Object(variant)) = serde_json::to_value(&tx.transaction_type) {
+        for payload in variant.into_values() {
+            if let Value::Object(payload) = payload {
+                fields.extend(payload);
+            }
+        }
+    }
+    fields.into_iter().collect()
+}
+
+fn field_commitment(name: &str, value: &Value, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(name.as_bytes());
+    hasher.update(value.to_string().as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn record_commitment(fields: &[DisclosedField]) -> String {
+    let mut hasher = Sha256::new();
+    for field in fields {
+        hasher.update(format!("{}:{}|", field.name, field.commitment));
+    }
+    hex::encode(hasher.finalize())
+}
@@ -0,0 +1,402 @@
+//! Rule-based and statistical fraud screening of marketplace sales.
+//! Each sale is scored from the signals it raises against the other sales on
+//! chain: self-dealing, linked parties, wash-trading loops, bursts from one
+//! buyer, amounts far from the product's usual price, and sellers earning
+//! credits out of line with their sales. The score decides whether the
+//! integration service lets the sale through, holds it or rejects it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::blockchain::Blockchain;
+use crate::carbon_registry::ReductionSource;
+use crate::did::DidRegistry;
+use crate::transaction::{Transaction, TransactionType};
+
+/// How strongly each signal points to fraud, from 0 to 1.
+pub const SELF_DEALING_WEIGHT: f64 = 0.95;
+pub const LINKED_PARTIES_WEIGHT: f64 = 0.6;
+pub const WASH_TRADING_WEIGHT: f64 = 0.7;
+pub const TRANSACTION_BURST_WEIGHT: f64 = 0.4;
+pub const AMOUNT_OUTLIER_WEIGHT: f64 = 0.35;
+pub const CREDIT_MISMATCH_WEIGHT: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FraudSignalKind {
+    /// Buyer and seller are the same DID
+    SelfDealing,
+    /// Buyer and seller DIDs share a controller or a key
+    LinkedParties,
+    /// The payment closes a loop of sales back to the buyer
+    WashTrading,
+    TransactionBurst,
+    AmountOutlier,
+    /// The seller's credits are far out of line with its sales
+    CreditMismatch,
+    /// A loaded fraud scoring model rates the sale as risky
+    ModelScore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FraudSignal {
+    pub kind: FraudSignalKind,
+    pub weight: f64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RiskDecision {
+    Allow,
+    /// Kept off chain until someone reviews it
+    Hold,
+    Reject,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    pub order_id: String,
+    pub tx_id: String,
+    pub buyer_did: String,
+    pub seller_did: String,
+    /// 0 for no signals, approaching 1 as independent signals add up
+    pub score: f64,
+    pub decision: RiskDecision,
+    pub reasons: Vec<FraudSignal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FraudReport {
+    pub generated_at: u64,
+    pub transactions_checked: usize,
+    /// Sales that raised at least one signal
+    pub flagged: Vec<RiskAssessment>,
+    pub held: usize,
+    pub rejected: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FraudDetectionConfig {
+    /// Sales from one buyer within `burst_window` seconds that count as a burst
+    pub burst_threshold: usize,
+    pub burst_window: u64,
+    /// Sales further apart than this do not form a wash-trading loop
+    pub wash_trade_window: u64,
+    /// Longest loop of sales, in parties, that is followed
+    pub max_loop_length: usize,
+    /// Modified z-score above which an amount is an outlier for its product
+    pub outlier_threshold: f64,
+    /// Earlier sales of a product needed before its amounts are judged
+    pub min_product_samples: usize,
+    /// Credits per unit of sales, as a multiple of the sellers' median, that counts as a mismatch
+    pub credit_ratio_threshold: f64,
+    pub hold_score: f64,
+    pub reject_score: f64,
+}
+
+/// A marketplace sale as screening sees it.
+#[derive(Debug, Clone)]
+struct Sale {
+    tx_id: String,
+    order_id: String,
+    buyer: String,
+    seller: String,
+    product: String,
+    amount: f64,
+    timestamp: u64,
+}
+
+impl Default for FraudDetectionConfig {
+    fn default() -> Self {
+        FraudDetectionConfig {
+            burst_threshold: 5,
+            burst_window: 10 * 60,
+            wash_trade_window: 7 * 24 * 60 * 60,
+            max_loop_length: 4,
+            outlier_threshold: 3.5,
+            min_product_samples: 5,
+            credit_ratio_threshold: 5.0,
+            hold_score: 0.5,
+            reject_score: 0.9,
+        }
+    }
+}
+
+impl RiskAssessment {
+    pub fn is_flagged(&self) -> bool {
+        !self.reasons.is_empty()
+    }
+
+    /// Adds a signal and recomputes the score and decision.
+    pub fn add_signal(&mut self, signal: FraudSignal, config: &FraudDetectionConfig) {
+        self.reasons.push(signal);
+        self.rescore(config);
+    }
+
+    /// Signals are treated as independent evidence.
+    fn rescore(&mut self, config: &FraudDetectionConfig) {
+        self.score = 1.0 - self.reasons.iter().map(|reason| 1.0 - reason.weight).product::<f64>();
+        self.decision = if self.score >= config.reject_score {
+            RiskDecision::Reject
+        } else if self.score >= config.hold_score {
+            RiskDecision::Hold
+        } else {
+            RiskDecision::Allow
+        };
+    }
+}
+
+impl Sale {
+    fn from_transaction(tx: &Transaction) -> Option<Self> {
+        match &tx.transaction_type {
+            TransactionType::MarketplaceTransaction { transaction_id, buyer_did, seller_did, product_did, amount, .. } => Some(Sale {
+                tx_id: tx.id.clone(),
+                order_id: transaction_id.clone(),
+                buyer: buyer_did.clone(),
+                seller: seller_did.clone(),
+                product: product_did.clone(),
+                amount: *amount,
+                timestamp: tx.timestamp,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Scores `tx` against everything already on chain or pending. `None` if it is not a sale.
+pub fn assess_transaction(config: &FraudDetectionConfig, blockchain: &Blockchain, dids: &DidRegistry, tx: &Transaction) -> Option<RiskAssessment> {
+    let candidate = Sale::from_transaction(tx)?;
+    let transactions = recorded_transactions(blockchain);
+    let sales: Vec<Sale> = transactions
+        .iter()
+        .filter_map(|tx| Sale::from_transaction(tx))
+        .filter(|sale| sale.tx_id != candidate.tx_id)
+        .collect();
+    let mismatched_sellers = credit_mismatches(config, &transactions, &sales);
+    Some(assess_sale(config, &candidate, &sales, dids, &mismatched_sellers))
+}
+
+/// Scores every sale on chain or pending against the others.
+pub fn scan_chain(config: &FraudDetectionConfig, blockchain: &Blockchain, dids: &DidRegistry, now: u64) -> FraudReport {
+    let transactions = recorded_transactions(blockchain);
+    let sales: Vec<Sale> = transactions.iter().filter_map(|tx| Sale::from_transaction(tx)).collect();
+    let mismatched_sellers = credit_mismatches(config, &transactions, &sales);
+
+    let flagged: Vec<RiskAssessment> = sales
+        .iter()
+        .map(|candidate| {
+            let others: Vec<Sale> = sales.iter().filter(|sale| sale.tx_id != candidate.tx_id).cloned().collect();
+            assess_sale(config, candidate, &others, dids, &mismatched_sellers)
+        })
+        .filter(RiskAssessment::is_flagged)
+        .collect();
+
+    FraudReport {
+        generated_at: now,
+        transactions_checked: sales.len(),
+        held: flagged.iter().filter(|assessment| assessment.decision == RiskDecision::Hold).count(),
+        rejected: flagged.iter().filter(|assessment| assessment.decision == RiskDecision::Reject).count(),
+        flagged,
+    }
+}
+
+/// Mined and pending transactions, in order.
+pub fn recorded_transactions(blockchain: &Blockchain) -> Vec<&Transaction> {
+    blockchain.get_all_transactions()
+        .into_iter()
+        .chain(blockchain.get_pending_transactions())
+        .collect()
+}
+
+fn assess_sale(
+    config: &FraudDetectionConfig,
+    candidate: &Sale,
+    others: &[Sale],
+    dids: &DidRegistry,
+    mismatched_sellers: &HashMap<String, String>,
+) -> RiskAssessment {
+    let mut reasons = Vec::new();
+    let mut signal = |kind, weight, detail: String| reasons.push(FraudSignal { kind, weight, detail });
+
+    if candidate.buyer == candidate.seller {
+        signal(FraudSignalKind::SelfDealing, SELF_DEALING_WEIGHT, format!("{} is both buyer and seller", candidate.buyer));
+    } else if let Some(link) = linked_parties(dids, &candidate.buyer, &candidate.seller) {
+        signal(FraudSignalKind::LinkedParties, LINKED_PARTIES_WEIGHT, link);
+    }
+
+    if let Some(path) = wash_trading_loop(config, candidate, others) {
+        signal(FraudSignalKind::WashTrading, WASH_TRADING_WEIGHT, format!("Payments loop {}", path.join(" → ")));
+    }
+
+    let burst = others
+        .iter()
+        .filter(|sale| sale.buyer == candidate.buyer)
+        .filter(|sale| sale.timestamp <= candidate.timestamp && candidate.timestamp - sale.timestamp <= config.burst_window)
+        .count()
+        + 1;
+    if burst >= config.burst_threshold {
+        signal(
+            FraudSignalKind::TransactionBurst,
+            TRANSACTION_BURST_WEIGHT,
+            format!("{} made {} purchases within {} seconds", candidate.buyer, burst, config.burst_window),
+        );
+    }
+
+    let product_amounts: Vec<f64> = others
+        .iter()
+        .filter(|sale| sale.product == candidate.product)
+        .map(|sale| sale.amount)
+        .collect();
+    if product_amounts.len() >= config.min_product_samples {
+        if let Some((z_score, median)) = modified_z_score(candidate.amount, &product_amounts) {
+            if z_score.abs() > config.outlier_threshold {
+                signal(
+                    FraudSignalKind::AmountOutlier,
+                    AMOUNT_OUTLIER_WEIGHT,
+                    format!("Amount {:.2} is far from the usual {:.2} for {} (z = {:.1})", candidate.amount, median, candidate.product, z_score),
+                );
+            }
+        }
+    }
+
+    if let Some(detail) = mismatched_sellers.get(&candidate.seller) {
+        signal(FraudSignalKind::CreditMismatch, CREDIT_MISMATCH_WEIGHT, detail.clone());
+    }
+
+    let mut assessment = RiskAssessment {
+        order_id: candidate.order_id.clone(),
+        tx_id: candidate.tx_id.clone(),
+        buyer_did: candidate.buyer.clone(),
+        seller_did: candidate.seller.clone(),
+        score: 0.0,
+        decision: RiskDecision::Allow,
+        reasons,
+    };
+    assessment.rescore(config);
+    assessment
+}
+
+/// Two DIDs are linked when one controls the other, they share a controller, or
+/// their documents list the same key.
+fn linked_parties(dids: &DidRegistry, buyer: &str, seller: &str) -> Option<String> {
+    let buyer_document = &dids.get_record(buyer)?.document;
+    let seller_document = &dids.get_record(seller)?.document;
+
+    if buyer_document.controller == seller || seller_document.controller == buyer {
+        return Some(format!("{} and {} control one another", buyer, seller));
+    }
+    if buyer_document.controller == seller_document.controller && buyer_document.controller != buyer {
+        return Some(format!("{} and {} are both controlled by {}", buyer, seller, buyer_document.controller));
+    }
+    buyer_document.verification_method
+        .iter()
+        .find(|key| seller_document.verification_method.iter().any(|other| other.public_key_hex == key.public_key_hex))
+        .map(|_| format!("{} and {} share a verification key", buyer, seller))
+}
+
+/// Follows payments onward from the seller and returns the parties on the
+/// shortest loop back to the buyer, if there is one within the window.
+fn wash_trading_loop(config: &FraudDetectionConfig, candidate: &Sale, others: &[Sale]) -> Option<Vec<String>> {
+    let mut payments: HashMap<&str, Vec<&str>> = HashMap::new();
+    for sale in others {
+        if sale.buyer != sale.seller && sale.timestamp.abs_diff(candidate.timestamp) <= config.wash_trade_window {
+            payments.entry(sale.buyer.as_str()).or_default().push(sale.seller.as_str());
+        }
+    }
+
+    let mut queue = VecDeque::from([vec![candidate.buyer.as_str(), candidate.seller.as_str()]]);
+    let mut visited = HashSet::from([candidate.seller.as_str()]);
+    while let Some(path) = queue.pop_front() {
+        let last = *path.last()?;
+        for &next in payments.get(last).into_iter().flatten() {
+            if next == candidate.buyer {
+                let mut loop_path: Vec<String> = path.iter().map(|party| party.to_string()).collect();
+                loop_path.push(next.to_string());
+                return Some(loop_path);
+            }
+            if path.len() < config.max_loop_length && visited.insert(next) {
+                let mut longer = path.clone();
+                longer.push(next);
+                queue.push_back(longer);
+            }
+        }
+    }
+    None
+}
+
+/// Robust z-score of `value` against `samples`, with the samples' median.
+fn modified_z_score(value: f64, samples: &[f64]) -> Option<(f64, f64)> {
+    let center = median(samples.to_vec())?;
+    let deviations: Vec<f64> = samples.iter().map(|sample| (sample - center).abs()).collect();
+    let spread = match median(deviations.clone())? {
+        mad if mad > 0.0 => mad / 0.6745,
+        // Mostly identical amounts; fall back to the mean absolute deviation
+        _ => deviations.iter().sum::<f64>() / deviations.len() as f64 * 1.2533,
+    };
+    if spread <= 0.0 {
+        return (value != center).then_some((f64::INFINITY, center));
+    }
+    Some(((value - center) / spread, center))
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let middle = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) { (values[middle - 1] + values[middle]) / 2.0 } else { values[middle] })
+}
+
+/// Sellers whose marketplace credits per unit of sales are far above the
+/// median seller's, or who hold credits for sales that are not on chain.
+fn credit_mismatches(config: &FraudDetectionConfig, transactions: &[&Transaction], sales: &[Sale]) -> HashMap<String, String> {
+    let sellers_by_tx: HashMap<&str, &str> = sales.iter().map(|sale| (sale.tx_id.as_str(), sale.seller.as_str())).collect();
+    let mut credits: HashMap<String, f64> = HashMap::new();
+    for tx in transactions {
+        let TransactionType::CarbonCredit { amount, project_id, claim } = &tx.transaction_type else {
+            continue;
+        };
+        let Some(claim) = claim else { continue };
+        if !matches!(claim.source, ReductionSource::MarketplaceSale { .. }) {
+            continue;
+        }
+        let seller = sellers_by_tx
+            .get(claim.evidence_tx_id.as_str())
+            .map(|seller| seller.to_string())
+            .or_else(|| project_id.strip_prefix("sustainable_merchant_").map(str::to_string));
+        if let Some(seller) = seller {
+            *credits.entry(seller).or_default() += amount;
+        }
+    }
+
+    let mut sales_volume: HashMap<&str, f64> = HashMap::new();
+    for sale in sales {
+        *sales_volume.entry(sale.seller.as_str()).or_default() += sale.amount;
+    }
+    let ratios: Vec<f64> = credits
+        .iter()
+        .filter_map(|(seller, credit)| {
+            sales_volume.get(seller.as_str()).filter(|volume| **volume > 0.0).map(|volume| credit / volume)
+        })
+        .collect();
+    let median_ratio = median(ratios);
+
+    credits
+        .into_iter()
+        .filter_map(|(seller, credit)| {
+            let volume = sales_volume.get(seller.as_str()).copied().unwrap_or(0.0);
+            let detail = if volume <= 0.0 {
+                Some(format!("{} holds {:.2} marketplace credits with no sales on chain", seller, credit))
+            } else {
+                median_ratio
+                    .filter(|median| *median > 0.0 && credit / volume > median * config.credit_ratio_threshold)
+                    .map(|median| format!(
+                        "{} earned {:.4} credits per unit of sales, {:.1}× the median seller",
+                        seller,
+                        credit / volume,
+                        credit / volume / median
+                    ))
+            };
+            detail.map(|detail| (seller, detail))
+        })
+        .collect()
+}
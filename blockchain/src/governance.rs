@@ -1,4 +1,20 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use crate::block::Block;
+use crate::blockchain::{self, Blockchain};
+use crate::privacy_manager::{SectorType, VerificationLevel};
+use crate::treasury::GrantTerms;
+use crate::transaction::{Transaction, TransactionType};
+
+/// Only this address may register eligible voters and set their reputation.
+pub const GOVERNANCE_AUTHORITY: &str = "ecogov_governance";
+
+/// Slack for comparing summed voting power, which may be fractional.
+const POWER_EPSILON: f64 = 1e-9;
+
+/// Mining gets impractically slow beyond this many leading zeros.
+const MAX_DIFFICULTY: usize = 6;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GovernanceProposal {
@@ -6,14 +22,33 @@ pub struct GovernanceProposal {
     pub proposer: String,
     pub title: String,
     pub description: String,
+    pub proposal_type: ProposalType,
+    pub topic: GovernanceTopic,
+    pub voting_mode: VotingMode,
+    pub sector_scope: SectorScope,
+    pub voting_power: VotingPowerStrategy,
     pub status: ProposalStatus,
-    pub votes_for: u64,
-    pub votes_against: u64,
+    pub votes_for: f64,
+    pub votes_against: f64,
+    pub votes_abstain: f64,
+    pub votes_veto: f64,
+    pub eligible_voters: u64,
+    /// Combined power of every voter eligible when the proposal was created
+    pub eligible_power: f64,
     pub created_at: u64,
+    pub created_at_height: u64,
     pub voting_deadline: u64,
+    pub end_height: Option<u64>,
+    pub action: Option<GovernanceAction>,
+    /// Set when a proposal with an action passes; the action waits for this height
+    pub executable_at_height: Option<u64>,
+    pub executed_at_height: Option<u64>,
+    /// Commitments discarded at finalization because they were never revealed
+    pub unrevealed_commitments: u64,
+    pub sector_results: Vec<SectorResult>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProposalStatus {
     Active,
     Passed,
@@ -21,47 +56,460 @@ pub enum ProposalStatus {
     Expired,
 }
 
+/// Decides which approval threshold a proposal must meet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProposalType {
+    #[default]
+    General,
+    ParameterChange,
+    TreasurySpend,
+    Constitutional,
+}
+
+/// A live parameter change carried by a proposal and applied once it passes
+/// and its timelock has elapsed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GovernanceAction {
+    SetMiningDifficulty {
+        difficulty: usize,
+    },
+    SetMiningReward {
+        reward: f64,
+    },
+    SetCommissionRate {
+        rate: f64,
+    },
+    SetWalletEnabled {
+        enabled: bool,
+    },
+    SetCarbonCreditRate {
+        currency: String,
+        rate_per_kg: f64,
+    },
+    SetSectorRequirements {
+        sector: SectorType,
+        required_verification_level: VerificationLevel,
+        government_oversight: bool,
+        transparency_level: f64,
+    },
+    /// Minimum sustainability score a sale must exceed to earn carbon credits
+    SetCreditAwardThreshold {
+        threshold: f64,
+    },
+    /// Share of each block reward paid into the treasury
+    SetTreasuryShare {
+        share: f64,
+    },
+    FundGrant {
+        terms: GrantTerms,
+    },
+    /// Cancels a grant's unreleased tranches and frees their funds
+    ClawbackGrant {
+        grant_id: String,
+        reason: String,
+    },
+}
+
+/// An executed action as recorded on chain, with the action that would restore
+/// the old value. Treasury actions replace no value and have no `previous`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterChangeRecord {
+    pub proposal_id: String,
+    pub previous: Option<GovernanceAction>,
+    pub applied: GovernanceAction,
+    pub transaction_id: String,
+    pub block_height: u64,
+}
+
+/// Open votes are visible as soon as they are mined. Under commit-reveal,
+/// voters first publish only a hash of their vote, then reveal it after the
+/// voting period closes and before the reveal deadline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum VotingMode {
+    #[default]
+    Open,
+    CommitReveal {
+        reveal_deadline: u64,
+        reveal_end_height: Option<u64>,
+    },
+}
+
+/// Limits who may vote to voters attested in `eligible_sectors` (all voters
+/// when empty) and requires the proposal to pass within each of `required_approvals`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SectorScope {
+    pub eligible_sectors: Vec<SectorType>,
+    pub required_approvals: Vec<SectorType>,
+}
+
+/// Votes cast by voters of one sector, delegated power counted under the delegator's sector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorResult {
+    pub sector: SectorType,
+    pub required: bool,
+    pub eligible_power: f64,
+    pub votes_for: f64,
+    pub votes_against: f64,
+    pub votes_abstain: f64,
+    pub votes_veto: f64,
+    /// The sector's own outcome, set at finalization
+    pub outcome: Option<ProposalStatus>,
+}
+
+/// A sealed ballot; only the hash is public until it is revealed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteCommitment {
+    pub voter: String,
+    pub commitment: String,
+    pub transaction_id: String,
+    pub block_height: u64,
+}
+
+/// Subject area of a proposal; delegations can be limited to one topic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GovernanceTopic {
+    #[default]
+    General,
+    EnvironmentalPolicy,
+    MarketplaceRules,
+    Treasury,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ApprovalThreshold {
+    /// More than half of the decisive votes
+    SimpleMajority,
+    /// At least two thirds of the decisive votes
+    Supermajority,
+}
+
+/// How much a single voter's vote weighs. Balances and stakes are read at the
+/// proposal's creation block so tokens moved afterwards cannot change the outcome.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum VotingPowerStrategy {
+    #[default]
+    OnePerVoter,
+    TokenBalance,
+    StakedBalance,
+    /// Square root of the token balance
+    Quadratic,
+    /// Weight recorded on chain from a merchant's verified record or a sector identity
+    Reputation,
+}
+
+/// Why a voter holds the reputation weight recorded for them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ReputationBasis {
+    MerchantRecord {
+        verified_certifications: usize,
+        sustainability_score: f64,
+    },
+    Sector {
+        sector: SectorType,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceParams {
+    /// Minimum share of eligible voting power (0.0 to 1.0) that must vote, abstentions included
+    pub quorum: f64,
+    pub thresholds: HashMap<ProposalType, ApprovalThreshold>,
+    pub voting_power: HashMap<ProposalType, VotingPowerStrategy>,
+    /// Share of non-abstaining votes cast as veto that rejects a proposal outright
+    pub veto_threshold: Option<f64>,
+    /// Blocks between a proposal passing and its action taking effect
+    pub execution_delay_blocks: u64,
+    /// Public key that must sign voter registrations, reputation updates,
+    /// sector attestations and executions. None rejects them all
+    pub authority_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+    /// Against, and counts towards the veto threshold
+    Veto,
+}
+
+/// The vote that currently counts for one voter on one proposal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastVote {
+    pub voter: String,
+    pub choice: VoteChoice,
+    pub power: f64,
+    pub transaction_id: String,
+    pub block_height: u64,
+    pub timestamp: u64,
+}
+
+/// Power of a voter who did not vote, carried by the first direct voter down their delegation chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegatedVote {
+    pub delegator: String,
+    pub represented_by: String,
+    /// Delegates passed through, ending with `represented_by`
+    pub path: Vec<String>,
+    pub choice: VoteChoice,
+    pub power: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteTally {
+    pub proposal_id: String,
+    pub votes_for: f64,
+    pub votes_against: f64,
+    pub votes_abstain: f64,
+    pub votes_veto: f64,
+    pub votes: Vec<CastVote>,
+    pub delegated_votes: Vec<DelegatedVote>,
+    pub sector_results: Vec<SectorResult>,
+}
+
+impl SectorScope {
+    pub fn is_restricted(&self) -> bool {
+        !self.eligible_sectors.is_empty() || !self.required_approvals.is_empty()
+    }
+
+    pub fn admits(&self, sector: Option<&SectorType>) -> bool {
+        self.eligible_sectors.is_empty() || sector.is_some_and(|sector| self.eligible_sectors.contains(sector))
+    }
+}
+
+impl GovernanceAction {
+    /// Spending needs a treasury proposal; everything else is a parameter change.
+    pub fn required_proposal_type(&self) -> ProposalType {
+        match self {
+            GovernanceAction::FundGrant { .. } | GovernanceAction::ClawbackGrant { .. } => ProposalType::TreasurySpend,
+            _ => ProposalType::ParameterChange,
+        }
+    }
+
+    /// Rejects values that could not be applied, so a passed action always executes.
+    pub fn validate(&self) -> Result<(), String> {
+        let valid = match self {
+            GovernanceAction::SetMiningDifficulty { difficulty } => (1..=MAX_DIFFICULTY).contains(difficulty),
+            GovernanceAction::SetMiningReward { reward } => reward.is_finite() && *reward >= 0.0,
+            GovernanceAction::SetCommissionRate { rate } => (0.0..=1.0).contains(rate),
+            GovernanceAction::SetWalletEnabled { .. } => true,
+            GovernanceAction::SetCarbonCreditRate { currency, rate_per_kg } => {
+                !currency.trim().is_empty() && rate_per_kg.is_finite() && *rate_per_kg > 0.0
+            }
+            GovernanceAction::SetSectorRequirements { transparency_level, .. } => (0.0..=1.0).contains(transparency_level),
+            GovernanceAction::SetCreditAwardThreshold { threshold } => (0.0..=10.0).contains(threshold),
+            GovernanceAction::SetTreasuryShare { share } => (0.0..=1.0).contains(share),
+            GovernanceAction::FundGrant { terms } => return terms.validate(),
+            GovernanceAction::ClawbackGrant { grant_id, .. } => !grant_id.trim().is_empty(),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("Invalid governance action: {}", self))
+        }
+    }
+}
+
+impl std::fmt::Display for GovernanceAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GovernanceAction::SetMiningDifficulty { difficulty } => write!(f, "mining difficulty = {}", difficulty),
+            GovernanceAction::SetMiningReward { reward } => write!(f, "mining reward = {:.2}", reward),
+            GovernanceAction::SetCommissionRate { rate } => write!(f, "wallet commission rate = {:.2}%", rate * 100.0),
+            GovernanceAction::SetWalletEnabled { enabled } => write!(f, "wallet enabled = {}", enabled),
+            GovernanceAction::SetCarbonCreditRate { currency, rate_per_kg } => {
+                write!(f, "carbon credit rate = {:.2} {} per kg", rate_per_kg, currency)
+            }
+            GovernanceAction::SetSectorRequirements { sector, required_verification_level, government_oversight, transparency_level } => write!(
+                f,
+                "{:?} sector requirements = {:?} verification, oversight {}, transparency {:.2}",
+                sector, required_verification_level, government_oversight, transparency_level
+            ),
+            GovernanceAction::SetCreditAwardThreshold { threshold } => write!(f, "credit award threshold = {:.1}", threshold),
+            GovernanceAction::SetTreasuryShare { share } => write!(f, "treasury share of block rewards = {:.2}%", share * 100.0),
+            GovernanceAction::FundGrant { terms } => write!(
+                f,
+                "grant {} of {:.2} to {} in {} tranches",
+                terms.grant_id, terms.total_amount(), terms.recipient, terms.tranches.len()
+            ),
+            GovernanceAction::ClawbackGrant { grant_id, reason } => write!(f, "claw back grant {} ({})", grant_id, reason),
+        }
+    }
+}
+
+impl GovernanceTopic {
+    pub const ALL: [GovernanceTopic; 4] = [
+        GovernanceTopic::General,
+        GovernanceTopic::EnvironmentalPolicy,
+        GovernanceTopic::MarketplaceRules,
+        GovernanceTopic::Treasury,
+    ];
+}
+
+impl ApprovalThreshold {
+    /// Compared without division so an exact two thirds is not lost to rounding.
+    pub fn is_met(&self, votes_for: f64, decisive_votes: f64) -> bool {
+        match self {
+            ApprovalThreshold::SimpleMajority => 2.0 * votes_for - decisive_votes > POWER_EPSILON,
+            ApprovalThreshold::Supermajority => 3.0 * votes_for - 2.0 * decisive_votes >= -POWER_EPSILON,
+        }
+    }
+}
+
+impl GovernanceParams {
+    pub fn threshold_for(&self, proposal_type: &ProposalType) -> ApprovalThreshold {
+        self.thresholds
+            .get(proposal_type)
+            .copied()
+            .unwrap_or(ApprovalThreshold::SimpleMajority)
+    }
+
+    pub fn strategy_for(&self, proposal_type: &ProposalType) -> VotingPowerStrategy {
+        self.voting_power.get(proposal_type).copied().unwrap_or_default()
+    }
+}
+
+impl Default for GovernanceParams {
+    fn default() -> Self {
+        let mut thresholds = HashMap::new();
+        thresholds.insert(ProposalType::General, ApprovalThreshold::SimpleMajority);
+        thresholds.insert(ProposalType::ParameterChange, ApprovalThreshold::Supermajority);
+        thresholds.insert(ProposalType::TreasurySpend, ApprovalThreshold::SimpleMajority);
+        thresholds.insert(ProposalType::Constitutional, ApprovalThreshold::Supermajority);
+
+        let mut voting_power = HashMap::new();
+        voting_power.insert(ProposalType::General, VotingPowerStrategy::OnePerVoter);
+        voting_power.insert(ProposalType::ParameterChange, VotingPowerStrategy::StakedBalance);
+        voting_power.insert(ProposalType::TreasurySpend, VotingPowerStrategy::Quadratic);
+        voting_power.insert(ProposalType::Constitutional, VotingPowerStrategy::Reputation);
+
+        GovernanceParams {
+            quorum: 0.334,
+            thresholds,
+            voting_power,
+            veto_threshold: Some(0.334),
+            execution_delay_blocks: 2,
+            authority_key: None,
+        }
+    }
+}
+
+/// Hash a voter commits to under commit-reveal. Binding the voter and proposal
+/// stops a commitment from being copied onto another ballot.
+pub fn vote_commitment(proposal_id: &str, voter: &str, choice: VoteChoice, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}|{}|{}|{}", proposal_id, voter, choice.as_str(), salt).as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Reputation weight for a voter identified only by sector.
+pub fn sector_reputation_weight(sector: &SectorType) -> f64 {
+    match sector {
+        SectorType::Government => 3.0,
+        SectorType::Education | SectorType::Industry => 2.0,
+        SectorType::Enterprise | SectorType::Business => 1.5,
+        SectorType::Individual => 1.0,
+    }
+}
+
+/// Reputation weight earned by a merchant's verified sustainability record.
+pub fn merchant_reputation_weight(verified_certifications: usize, sustainability_score: f64) -> f64 {
+    1.0 + (verified_certifications as f64 * 0.5).min(2.0) + sustainability_score.clamp(0.0, 10.0) / 10.0
+}
+
+impl VoteChoice {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VoteChoice::For => "for",
+            VoteChoice::Against => "against",
+            VoteChoice::Abstain => "abstain",
+            VoteChoice::Veto => "veto",
+        }
+    }
+
+    pub fn parse(vote: &str) -> Option<Self> {
+        match vote.to_lowercase().as_str() {
+            "for" | "yes" => Some(VoteChoice::For),
+            "against" | "no" => Some(VoteChoice::Against),
+            "abstain" => Some(VoteChoice::Abstain),
+            "veto" | "no_with_veto" => Some(VoteChoice::Veto),
+            _ => None,
+        }
+    }
+}
+
 impl GovernanceProposal {
     pub fn new(id: String, proposer: String, title: String, description: String) -> Self {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        
+
         GovernanceProposal {
             id,
             proposer,
             title,
             description,
+            proposal_type: ProposalType::General,
+            topic: GovernanceTopic::General,
+            voting_mode: VotingMode::Open,
+            sector_scope: SectorScope::default(),
+            voting_power: VotingPowerStrategy::OnePerVoter,
             status: ProposalStatus::Active,
-            votes_for: 0,
-            votes_against: 0,
+            votes_for: 0.0,
+            votes_against: 0.0,
+            votes_abstain: 0.0,
+            votes_veto: 0.0,
+            eligible_voters: 0,
+            eligible_power: 0.0,
             created_at: now,
+            created_at_height: 0,
             voting_deadline: now + (7 * 24 * 60 * 60), // 7 days from now
+            end_height: None,
+            action: None,
+            executable_at_height: None,
+            executed_at_height: None,
+            unrevealed_commitments: 0,
+            sector_results: Vec::new(),
         }
     }
-    
-    pub fn vote(&mut self, in_favor: bool) {
-        if in_favor {
-            self.votes_for += 1;
+
+    pub fn get_total_votes(&self) -> f64 {
+        self.votes_for + self.votes_against + self.votes_abstain + self.votes_veto
+    }
+
+    /// Votes that take a side; abstentions count towards quorum only.
+    pub fn get_decisive_votes(&self) -> f64 {
+        self.votes_for + self.votes_against + self.votes_veto
+    }
+
+    pub fn get_approval_percentage(&self) -> f64 {
+        let decisive = self.get_decisive_votes();
+        if decisive <= 0.0 {
+            0.0
         } else {
-            self.votes_against += 1;
+            (self.votes_for / decisive) * 100.0
         }
     }
-    
-    pub fn get_total_votes(&self) -> u64 {
-        self.votes_for + self.votes_against
+
+    pub fn get_turnout_percentage(&self) -> f64 {
+        if self.eligible_power <= 0.0 {
+            0.0
+        } else {
+            (self.get_total_votes() / self.eligible_power) * 100.0
+        }
     }
-    
-    pub fn get_approval_percentage(&self) -> f64 {
-        let total = self.get_total_votes();
-        if total == 0 {
+
+    pub fn get_veto_percentage(&self) -> f64 {
+        let decisive = self.get_decisive_votes();
+        if decisive <= 0.0 {
             0.0
         } else {
-            (self.votes_for as f64 / total as f64) * 100.0
+            (self.votes_veto / decisive) * 100.0
         }
     }
-    
+
     pub fn is_expired(&self) -> bool {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -69,24 +517,852 @@ impl GovernanceProposal {
             .as_secs();
         now > self.voting_deadline
     }
-    
-    pub fn finalize(&mut self) {
-        if self.is_expired() {
-            self.status = ProposalStatus::Expired;
-        } else if self.get_approval_percentage() > 50.0 {
-            self.status = ProposalStatus::Passed;
-        } else {
+
+    /// Votes, or commitments under commit-reveal, are accepted strictly before
+    /// the deadline and, if set, below the end height.
+    pub fn is_voting_open(&self, timestamp: u64, height: u64) -> bool {
+        self.status == ProposalStatus::Active
+            && timestamp < self.voting_deadline
+            && self.end_height.is_none_or(|end_height| height < end_height)
+    }
+
+    /// The reveal window runs from the close of voting until the reveal deadline.
+    pub fn is_reveal_open(&self, timestamp: u64, height: u64) -> bool {
+        match self.voting_mode {
+            VotingMode::Open => false,
+            VotingMode::CommitReveal { reveal_deadline, reveal_end_height } => {
+                self.status == ProposalStatus::Active
+                    && !self.is_voting_open(timestamp, height)
+                    && timestamp < reveal_deadline
+                    && reveal_end_height.is_none_or(|end_height| height < end_height)
+            }
+        }
+    }
+
+    /// Open proposals are counted when voting closes, commit-reveal ones when the reveal window does.
+    pub fn is_due(&self, timestamp: u64, height: u64) -> bool {
+        let voting_closed = timestamp >= self.voting_deadline
+            || self.end_height.is_some_and(|end_height| height >= end_height);
+        match self.voting_mode {
+            VotingMode::Open => voting_closed,
+            VotingMode::CommitReveal { reveal_deadline, reveal_end_height } => {
+                timestamp >= reveal_deadline || reveal_end_height.is_some_and(|end_height| height >= end_height)
+            }
+        }
+    }
+
+    pub fn record_tally(&mut self, tally: &VoteTally) {
+        self.votes_for = tally.votes_for;
+        self.votes_against = tally.votes_against;
+        self.votes_abstain = tally.votes_abstain;
+        self.votes_veto = tally.votes_veto;
+        self.sector_results = tally.sector_results.clone();
+    }
+
+    /// Counts the recorded tally once voting has closed. Without quorum the
+    /// proposal expires; otherwise a veto rejects it before approval is counted.
+    /// Each sector whose approval is required must also pass on its own votes.
+    pub fn finalize(&mut self, params: &GovernanceParams) {
+        let threshold = params.threshold_for(&self.proposal_type);
+        self.status = evaluate_outcome(
+            [self.votes_for, self.votes_against, self.votes_abstain, self.votes_veto],
+            self.eligible_power,
+            threshold,
+            params,
+        );
+
+        for result in &mut self.sector_results {
+            result.outcome = Some(evaluate_outcome(
+                [result.votes_for, result.votes_against, result.votes_abstain, result.votes_veto],
+                result.eligible_power,
+                threshold,
+                params,
+            ));
+        }
+        let required_sector_failed = self.sector_results
+            .iter()
+            .any(|result| result.required && result.outcome != Some(ProposalStatus::Passed));
+        if self.status == ProposalStatus::Passed && required_sector_failed {
             self.status = ProposalStatus::Rejected;
         }
     }
 }
 
+/// Applies quorum, veto and approval threshold, in that order, to one set of
+/// votes given as [for, against, abstain, veto].
+fn evaluate_outcome(votes: [f64; 4], eligible_power: f64, threshold: ApprovalThreshold, params: &GovernanceParams) -> ProposalStatus {
+    let [votes_for, votes_against, votes_abstain, votes_veto] = votes;
+    let total = votes_for + votes_against + votes_abstain + votes_veto;
+    let decisive = votes_for + votes_against + votes_veto;
+
+    if eligible_power <= 0.0 || total - params.quorum * eligible_power < -POWER_EPSILON {
+        ProposalStatus::Expired
+    } else if params
+        .veto_threshold
+        .is_some_and(|veto_threshold| decisive > 0.0 && votes_veto - veto_threshold * decisive >= -POWER_EPSILON)
+    {
+        ProposalStatus::Rejected
+    } else if threshold.is_met(votes_for, decisive) {
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    }
+}
+
 impl std::fmt::Display for GovernanceProposal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Proposal {}: {} - Status: {:?}, Votes: {} for, {} against",
-            self.id, self.title, self.status, self.votes_for, self.votes_against
+            "Proposal {}: {} - Status: {:?}, Votes: {:.2} for, {:.2} against, {:.2} abstain, {:.2} veto",
+            self.id, self.title, self.status, self.votes_for, self.votes_against, self.votes_abstain, self.votes_veto
         )
     }
 }
+
+/// Governance state derived from mined blocks. Proposals, voter registrations
+/// and votes are all transactions, so every node replaying the chain reaches
+/// the same tallies. Invalid governance transactions on chain are skipped.
+pub struct GovernanceRegistry {
+    params: GovernanceParams,
+    proposals: HashMap<String, GovernanceProposal>,
+    voters: HashMap<String, String>, // voter address -> public key
+    votes: HashMap<String, HashMap<String, CastVote>>, // proposal id -> voter -> latest vote
+    commitments: HashMap<String, HashMap<String, VoteCommitment>>, // proposal id -> voter -> latest commitment
+    power_snapshots: HashMap<String, HashMap<String, f64>>, // proposal id -> voter -> power
+    sector_snapshots: HashMap<String, HashMap<String, SectorType>>, // proposal id -> voter -> sector
+    voter_sectors: HashMap<String, SectorType>,
+    delegations: HashMap<String, HashMap<Option<GovernanceTopic>, String>>, // delegator -> topic -> delegate
+    parameter_changes: Vec<ParameterChangeRecord>,
+    balances: HashMap<String, f64>,
+    stakes: HashMap<String, f64>,
+    reputations: HashMap<String, f64>,
+    applied_transactions: HashSet<String>, // ids of applied governance transactions, so none is replayed
+}
+
+impl GovernanceRegistry {
+    pub fn new() -> Self {
+        Self::with_params(GovernanceParams::default())
+    }
+
+    pub fn with_params(params: GovernanceParams) -> Self {
+        GovernanceRegistry {
+            params,
+            proposals: HashMap::new(),
+            voters: HashMap::new(),
+            votes: HashMap::new(),
+            commitments: HashMap::new(),
+            power_snapshots: HashMap::new(),
+            sector_snapshots: HashMap::new(),
+            voter_sectors: HashMap::new(),
+            delegations: HashMap::new(),
+            parameter_changes: Vec::new(),
+            balances: HashMap::new(),
+            stakes: HashMap::new(),
+            reputations: HashMap::new(),
+            applied_transactions: HashSet::new(),
+        }
+    }
+
+    pub fn from_chain(blockchain: &Blockchain, params: GovernanceParams) -> Self {
+        let mut registry = Self::with_params(params);
+        for block in blockchain.get_blocks() {
+            registry.apply_block(block);
+        }
+        registry
+    }
+
+    /// Applies a mined block's governance transactions, then finalizes any
+    /// proposals whose voting period the block closes. Balances and stakes are
+    /// updated first so a proposal created here snapshots the whole block.
+    pub fn apply_block(&mut self, block: &Block) -> Vec<String> {
+        let height = block.index as u64;
+        for tx in &block.transactions {
+            if let Some((address, amount)) = tx.balance_credit() {
+                *self.balances.entry(address.to_string()).or_insert(0.0) += amount;
+            }
+        }
+        for tx in &block.transactions {
+            if let TransactionType::ValidatorStake { amount } = tx.transaction_type {
+                let balance = self.balances.get(&tx.from).copied().unwrap_or(0.0);
+                let staked = self.stakes.entry(tx.from.clone()).or_insert(0.0);
+                if blockchain::is_valid_stake(balance, *staked, amount) {
+                    *staked += amount;
+                }
+            }
+        }
+        for tx in &block.transactions {
+            // Invalid transactions stay on chain but never count
+            let _ = self.apply_transaction(tx, height, block.timestamp);
+        }
+        self.finalize_due(block.timestamp, height)
+    }
+
+    /// Credits the disbursements in `block` that the treasury accepted. They are
+    /// known only after the treasury applies the block, so they count from the next one.
+    pub fn apply_treasury_releases(&mut self, block: &Block, released: &[String]) {
+        for tx in block.transactions.iter().filter(|tx| released.contains(&tx.id)) {
+            if let Some((recipient, amount)) = tx.disbursement_credit() {
+                *self.balances.entry(recipient.to_string()).or_insert(0.0) += amount;
+            }
+        }
+    }
+
+    /// Checks whether a governance transaction would be accepted at the given height and time.
+    pub fn validate_transaction(&self, tx: &Transaction, height: u64, timestamp: u64) -> Result<(), String> {
+        // A signed ballot or delegation resubmitted later must not undo what replaced it
+        if self.applied_transactions.contains(&tx.id) {
+            return Err(format!("Governance transaction {} was already applied", tx.id));
+        }
+        match &tx.transaction_type {
+            TransactionType::GovernanceVoterRegistration { voter, public_key } => {
+                self.require_authority(tx, "register voters")?;
+                if !crate::signing::is_valid_public_key(public_key) {
+                    return Err(format!("Voter {} has an invalid public key", voter));
+                }
+                if self.voters.contains_key(voter) {
+                    return Err(format!("Voter {} is already registered", voter));
+                }
+                Ok(())
+            }
+            TransactionType::GovernanceDelegation { delegate, topic } => {
+                self.verify_voter_signature(tx)?;
+                if !self.voters.contains_key(delegate) {
+                    return Err(format!("Delegate {} is not an eligible voter", delegate));
+                }
+                if *delegate == tx.from {
+                    return Err(format!("{} cannot delegate to themselves", tx.from));
+                }
+                if self.would_create_cycle(&tx.from, delegate, *topic) {
+                    return Err(format!("Delegating from {} to {} would create a delegation cycle", tx.from, delegate));
+                }
+                Ok(())
+            }
+            TransactionType::GovernanceUndelegation { topic } => {
+                self.verify_voter_signature(tx)?;
+                if !self.delegations.get(&tx.from).is_some_and(|delegations| delegations.contains_key(topic)) {
+                    return Err(format!("{} has no delegation for {:?}", tx.from, topic));
+                }
+                Ok(())
+            }
+            TransactionType::GovernanceExecution { proposal_id, applied, .. } => {
+                self.require_authority(tx, "execute proposals")?;
+                let proposal = self.proposals.get(proposal_id)
+                    .ok_or_else(|| format!("Proposal {} not found", proposal_id))?;
+                if proposal.action.as_ref() != Some(applied) {
+                    return Err(format!("Execution does not match the action of proposal {}", proposal_id));
+                }
+                if proposal.executed_at_height.is_some() {
+                    return Err(format!("Proposal {} was already executed", proposal_id));
+                }
+                match proposal.executable_at_height {
+                    Some(executable_at) if height >= executable_at => Ok(()),
+                    Some(executable_at) => Err(format!("Proposal {} is timelocked until block {}", proposal_id, executable_at)),
+                    None => Err(format!("Proposal {} has not passed", proposal_id)),
+                }
+            }
+            TransactionType::GovernanceReputationUpdate { voter, weight, .. } => {
+                self.require_authority(tx, "set reputation")?;
+                if !self.voters.contains_key(voter) {
+                    return Err(format!("{} is not an eligible voter", voter));
+                }
+                if !weight.is_finite() || *weight < 0.0 {
+                    return Err(format!("Reputation weight {} for {} is invalid", weight, voter));
+                }
+                Ok(())
+            }
+            // May arrive in the same block as the voter's registration
+            TransactionType::GovernanceSectorAssignment { .. } => {
+                self.require_authority(tx, "attest sectors")?;
+                Ok(())
+            }
+            TransactionType::GovernanceSectorRevocation { .. } => {
+                self.require_authority(tx, "withdraw sectors")?;
+                Ok(())
+            }
+            TransactionType::GovernanceProposalCreation { proposal_id, proposal_type, voting_mode, sector_scope, voting_deadline, end_height, action, .. } => {
+                self.verify_voter_signature(tx)?;
+                if let Some(sector) = sector_scope.required_approvals.iter().find(|sector| !sector_scope.admits(Some(sector))) {
+                    return Err(format!("Proposal {} requires {:?} approval but excludes its voters", proposal_id, sector));
+                }
+                if let VotingMode::CommitReveal { reveal_deadline, reveal_end_height } = voting_mode {
+                    if reveal_deadline <= voting_deadline {
+                        return Err(format!("Proposal {} must close reveals after voting", proposal_id));
+                    }
+                    if let (Some(end_height), Some(reveal_end_height)) = (end_height, reveal_end_height) {
+                        if reveal_end_height <= end_height {
+                            return Err(format!("Proposal {} must close reveals after voting", proposal_id));
+                        }
+                    }
+                }
+                if let Some(action) = action {
+                    if *proposal_type != action.required_proposal_type() {
+                        return Err(format!(
+                            "Proposal {} must be {:?} to carry this action",
+                            proposal_id, action.required_proposal_type()
+                        ));
+                    }
+                    action.validate()?;
+                }
+                if self.proposals.contains_key(proposal_id) {
+                    return Err(format!("Proposal {} already exists", proposal_id));
+                }
+                if *voting_deadline <= timestamp {
+                    return Err(format!("Proposal {} has a voting deadline in the past", proposal_id));
+                }
+                if end_height.is_some_and(|end_height| end_height <= height) {
+                    return Err(format!("Proposal {} has an end height that was already reached", proposal_id));
+                }
+                Ok(())
+            }
+            TransactionType::GovernanceVote { proposal_id, vote } => {
+                self.verify_voter_signature(tx)?;
+                let proposal = self.proposals.get(proposal_id)
+                    .ok_or_else(|| format!("Proposal {} not found", proposal_id))?;
+                if proposal.voting_mode != VotingMode::Open {
+                    return Err(format!("Proposal {} takes sealed votes; commit and reveal instead", proposal_id));
+                }
+                if !proposal.is_voting_open(timestamp, height) {
+                    return Err(format!("Voting on proposal {} is closed", proposal_id));
+                }
+                self.check_snapshot_eligibility(proposal_id, &tx.from)?;
+                VoteChoice::parse(vote).ok_or_else(|| format!("Unknown vote '{}'", vote))?;
+                Ok(())
+            }
+            TransactionType::GovernanceVoteCommitment { proposal_id, commitment } => {
+                self.verify_voter_signature(tx)?;
+                let proposal = self.proposals.get(proposal_id)
+                    .ok_or_else(|| format!("Proposal {} not found", proposal_id))?;
+                if proposal.voting_mode == VotingMode::Open {
+                    return Err(format!("Proposal {} takes open votes", proposal_id));
+                }
+                if !proposal.is_voting_open(timestamp, height) {
+                    return Err(format!("Voting on proposal {} is closed", proposal_id));
+                }
+                self.check_snapshot_eligibility(proposal_id, &tx.from)?;
+                if commitment.len() != 64 || hex::decode(commitment).is_err() {
+                    return Err("Commitment must be a hex SHA-256 hash".to_string());
+                }
+                Ok(())
+            }
+            TransactionType::GovernanceVoteReveal { proposal_id, vote, salt } => {
+                self.verify_voter_signature(tx)?;
+                let proposal = self.proposals.get(proposal_id)
+                    .ok_or_else(|| format!("Proposal {} not found", proposal_id))?;
+                if !proposal.is_reveal_open(timestamp, height) {
+                    return Err(format!("The reveal window for proposal {} is not open", proposal_id));
+                }
+                if self.votes.get(proposal_id).is_some_and(|votes| votes.contains_key(&tx.from)) {
+                    return Err(format!("{} already revealed a vote on proposal {}", tx.from, proposal_id));
+                }
+                let committed = self.commitments
+                    .get(proposal_id)
+                    .and_then(|commitments| commitments.get(&tx.from))
+                    .ok_or_else(|| format!("{} has no commitment on proposal {}", tx.from, proposal_id))?;
+                let choice = VoteChoice::parse(vote).ok_or_else(|| format!("Unknown vote '{}'", vote))?;
+                if vote_commitment(proposal_id, &tx.from, choice, salt) != committed.commitment {
+                    return Err(format!("Reveal from {} does not match their commitment", tx.from));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn apply_transaction(&mut self, tx: &Transaction, height: u64, timestamp: u64) -> Result<(), String> {
+        self.validate_transaction(tx, height, timestamp)?;
+
+        match &tx.transaction_type {
+            TransactionType::GovernanceVoterRegistration { voter, public_key } => {
+                self.voters.insert(voter.clone(), public_key.clone());
+            }
+            TransactionType::GovernanceDelegation { delegate, topic } => {
+                self.delegations.entry(tx.from.clone()).or_default().insert(*topic, delegate.clone());
+                self.refresh_active_tallies();
+            }
+            TransactionType::GovernanceUndelegation { topic } => {
+                if let Some(delegations) = self.delegations.get_mut(&tx.from) {
+                    delegations.remove(topic);
+                }
+                self.refresh_active_tallies();
+            }
+            TransactionType::GovernanceReputationUpdate { voter, weight, .. } => {
+                self.reputations.insert(voter.clone(), *weight);
+            }
+            TransactionType::GovernanceExecution { proposal_id, previous, applied } => {
+                self.proposals.get_mut(proposal_id).unwrap().executed_at_height = Some(height);
+                self.parameter_changes.push(ParameterChangeRecord {
+                    proposal_id: proposal_id.clone(),
+                    previous: previous.clone(),
+                    applied: applied.clone(),
+                    transaction_id: tx.id.clone(),
+                    block_height: height,
+                });
+            }
+            TransactionType::GovernanceSectorAssignment { voter, sector } => {
+                self.voter_sectors.insert(voter.clone(), sector.clone());
+            }
+            TransactionType::GovernanceSectorRevocation { voter, .. } => {
+                self.voter_sectors.remove(voter);
+            }
+            TransactionType::GovernanceProposalCreation { proposal_id, title, description, proposal_type, topic, voting_mode, sector_scope, voting_deadline, end_height, action } => {
+                let voting_power = self.params.strategy_for(proposal_type);
+                let eligible: Vec<&String> = self.voters
+                    .keys()
+                    .filter(|voter| sector_scope.admits(self.voter_sectors.get(*voter)))
+                    .collect();
+                let snapshot: HashMap<String, f64> = eligible
+                    .iter()
+                    .map(|voter| ((*voter).clone(), self.voting_power_of(voter, voting_power)))
+                    .collect();
+                let sectors: HashMap<String, SectorType> = eligible
+                    .iter()
+                    .filter_map(|voter| self.voter_sectors.get(*voter).map(|sector| ((*voter).clone(), sector.clone())))
+                    .collect();
+                let eligible_voters = snapshot.len() as u64;
+                let eligible_power = snapshot.values().fold(0.0, |total, power| total + power);
+                self.power_snapshots.insert(proposal_id.clone(), snapshot);
+                self.sector_snapshots.insert(proposal_id.clone(), sectors);
+
+                self.proposals.insert(proposal_id.clone(), GovernanceProposal {
+                    id: proposal_id.clone(),
+                    proposer: tx.from.clone(),
+                    title: title.clone(),
+                    description: description.clone(),
+                    proposal_type: *proposal_type,
+                    topic: *topic,
+                    voting_mode: *voting_mode,
+                    sector_scope: sector_scope.clone(),
+                    voting_power,
+                    status: ProposalStatus::Active,
+                    votes_for: 0.0,
+                    votes_against: 0.0,
+                    votes_abstain: 0.0,
+                    votes_veto: 0.0,
+                    // Quorum is measured against the electorate when the proposal was created
+                    eligible_voters,
+                    eligible_power,
+                    created_at: timestamp,
+                    created_at_height: height,
+                    voting_deadline: *voting_deadline,
+                    end_height: *end_height,
+                    action: action.clone(),
+                    executable_at_height: None,
+                    executed_at_height: None,
+                    unrevealed_commitments: 0,
+                    sector_results: Vec::new(),
+                });
+                if let Some(tally) = self.tally(proposal_id) {
+                    self.proposals.get_mut(proposal_id).unwrap().record_tally(&tally);
+                }
+            }
+            TransactionType::GovernanceVoteCommitment { proposal_id, commitment } => {
+                // A voter may change their mind by committing again before voting closes
+                self.commitments.entry(proposal_id.clone()).or_default().insert(tx.from.clone(), VoteCommitment {
+                    voter: tx.from.clone(),
+                    commitment: commitment.clone(),
+                    transaction_id: tx.id.clone(),
+                    block_height: height,
+                });
+            }
+            TransactionType::GovernanceVote { proposal_id, vote } | TransactionType::GovernanceVoteReveal { proposal_id, vote, .. } => {
+                // A later open vote from the same voter replaces the earlier one; a reveal counts as the vote
+                self.votes.entry(proposal_id.clone()).or_default().insert(tx.from.clone(), CastVote {
+                    voter: tx.from.clone(),
+                    choice: VoteChoice::parse(vote).unwrap(),
+                    power: self.power_snapshots[proposal_id][&tx.from],
+                    transaction_id: tx.id.clone(),
+                    block_height: height,
+                    timestamp,
+                });
+                if let Some(tally) = self.tally(proposal_id) {
+                    self.proposals.get_mut(proposal_id).unwrap().record_tally(&tally);
+                }
+            }
+            _ => return Ok(()),
+        }
+        self.applied_transactions.insert(tx.id.clone());
+        Ok(())
+    }
+
+    /// A topic-specific delegation takes precedence over one covering every topic.
+    pub fn get_delegate(&self, voter: &str, topic: GovernanceTopic) -> Option<&String> {
+        let delegations = self.delegations.get(voter)?;
+        delegations.get(&Some(topic)).or_else(|| delegations.get(&None))
+    }
+
+    fn would_create_cycle(&self, delegator: &str, delegate: &str, topic: Option<GovernanceTopic>) -> bool {
+        // A catch-all delegation applies to every topic without its own delegation
+        let affected: Vec<GovernanceTopic> = match topic {
+            Some(topic) => vec![topic],
+            None => GovernanceTopic::ALL
+                .iter()
+                .copied()
+                .filter(|topic| !self.delegations.get(delegator).is_some_and(|d| d.contains_key(&Some(*topic))))
+                .collect(),
+        };
+
+        affected.into_iter().any(|topic| {
+            let mut current = delegate;
+            for _ in 0..=self.voters.len() {
+                if current == delegator {
+                    return true;
+                }
+                match self.get_delegate(current, topic) {
+                    Some(next) => current = next,
+                    None => return false,
+                }
+            }
+            true
+        })
+    }
+
+    /// Delegation changes move power between live tallies, so they are recounted.
+    fn refresh_active_tallies(&mut self) {
+        let active: Vec<String> = self.proposals
+            .values()
+            .filter(|proposal| proposal.status == ProposalStatus::Active)
+            .map(|proposal| proposal.id.clone())
+            .collect();
+        for proposal_id in active {
+            if let Some(tally) = self.tally(&proposal_id) {
+                self.proposals.get_mut(&proposal_id).unwrap().record_tally(&tally);
+            }
+        }
+    }
+
+    fn check_snapshot_eligibility(&self, proposal_id: &str, voter: &str) -> Result<(), String> {
+        if self.power_snapshots.get(proposal_id).is_some_and(|snapshot| snapshot.contains_key(voter)) {
+            Ok(())
+        } else {
+            Err(format!("{} was not an eligible voter when proposal {} was created", voter, proposal_id))
+        }
+    }
+
+    fn voting_power_of(&self, voter: &str, strategy: VotingPowerStrategy) -> f64 {
+        let balance = self.balances.get(voter).copied().unwrap_or(0.0).max(0.0);
+        match strategy {
+            VotingPowerStrategy::OnePerVoter => 1.0,
+            VotingPowerStrategy::TokenBalance => balance,
+            VotingPowerStrategy::StakedBalance => self.stakes.get(voter).copied().unwrap_or(0.0),
+            VotingPowerStrategy::Quadratic => balance.sqrt(),
+            VotingPowerStrategy::Reputation => self.reputations.get(voter).copied().unwrap_or(1.0),
+        }
+    }
+
+    /// The sender must be the governance authority and sign with its configured key.
+    fn require_authority(&self, tx: &Transaction, action: &str) -> Result<(), String> {
+        if tx.from != GOVERNANCE_AUTHORITY {
+            return Err(format!("{} is not authorized to {}", tx.from, action));
+        }
+        let authority_key = self.params.authority_key
+            .as_ref()
+            .ok_or_else(|| format!("No governance authority key is configured to {}", action))?;
+        if !tx.verify_signature(authority_key) {
+            return Err(format!("Transaction {} is not signed by the governance authority", tx.id));
+        }
+        Ok(())
+    }
+
+    pub fn verify_voter_signature(&self, tx: &Transaction) -> Result<(), String> {
+        let public_key = self.voters.get(&tx.from)
+            .ok_or_else(|| format!("{} is not an eligible voter", tx.from))?;
+        if !tx.verify_signature(public_key) {
+            return Err(format!("Invalid signature from {}", tx.from));
+        }
+        Ok(())
+    }
+
+    /// Finalizes every active proposal whose deadline or end height a mined block reached.
+    /// Only `apply_block` calls this, so every node finalizes at the same block.
+    fn finalize_due(&mut self, timestamp: u64, height: u64) -> Vec<String> {
+        let due: Vec<String> = self.proposals
+            .values()
+            .filter(|proposal| proposal.status == ProposalStatus::Active)
+            .filter(|proposal| proposal.is_due(timestamp, height))
+            .map(|proposal| proposal.id.clone())
+            .collect();
+
+        for proposal_id in &due {
+            let tally = self.tally(proposal_id).unwrap();
+            // Only reveals were ever counted; sealed ballots left unopened are dropped
+            let revealed = self.votes.get(proposal_id);
+            let unrevealed = self.commitments
+                .remove(proposal_id)
+                .map(|commitments| {
+                    commitments.keys().filter(|voter| !revealed.is_some_and(|votes| votes.contains_key(*voter))).count() as u64
+                })
+                .unwrap_or(0);
+            let proposal = self.proposals.get_mut(proposal_id).unwrap();
+            proposal.unrevealed_commitments = unrevealed;
+            proposal.record_tally(&tally);
+            proposal.finalize(&self.params);
+            if proposal.status == ProposalStatus::Passed && proposal.action.is_some() {
+                proposal.executable_at_height = Some(height + self.params.execution_delay_blocks);
+            }
+        }
+        due
+    }
+
+    /// Passed proposals whose timelock has elapsed at `height` but whose action is not yet on chain.
+    pub fn pending_executions(&self, height: u64) -> Vec<&GovernanceProposal> {
+        let mut pending: Vec<&GovernanceProposal> = self.proposals
+            .values()
+            .filter(|proposal| proposal.executed_at_height.is_none())
+            .filter(|proposal| proposal.executable_at_height.is_some_and(|executable_at| height >= executable_at))
+            .collect();
+        pending.sort_by(|a, b| a.executable_at_height.cmp(&b.executable_at_height).then(a.id.cmp(&b.id)));
+        pending
+    }
+
+    pub fn get_parameter_changes(&self) -> &Vec<ParameterChangeRecord> {
+        &self.parameter_changes
+    }
+
+    pub fn tally(&self, proposal_id: &str) -> Option<VoteTally> {
+        if !self.proposals.contains_key(proposal_id) {
+            return None;
+        }
+
+        let mut votes: Vec<CastVote> = self.votes
+            .get(proposal_id)
+            .map(|votes| votes.values().cloned().collect())
+            .unwrap_or_default();
+        votes.sort_by(|a, b| a.voter.cmp(&b.voter));
+
+        let delegated_votes = self.resolve_delegated_votes(proposal_id);
+
+        let count = |choice: VoteChoice| {
+            let direct = votes.iter().filter(|vote| vote.choice == choice).fold(0.0, |total, vote| total + vote.power);
+            let delegated = delegated_votes.iter().filter(|vote| vote.choice == choice).fold(0.0, |total, vote| total + vote.power);
+            direct + delegated
+        };
+        let sector_results = self.sector_results(proposal_id, &votes, &delegated_votes);
+        Some(VoteTally {
+            proposal_id: proposal_id.to_string(),
+            votes_for: count(VoteChoice::For),
+            votes_against: count(VoteChoice::Against),
+            votes_abstain: count(VoteChoice::Abstain),
+            votes_veto: count(VoteChoice::Veto),
+            votes,
+            delegated_votes,
+            sector_results,
+        })
+    }
+
+    /// Breaks a tally down by the sector each voter was attested in when the
+    /// proposal was created. Voters without a sector appear in no breakdown.
+    fn sector_results(&self, proposal_id: &str, votes: &[CastVote], delegated_votes: &[DelegatedVote]) -> Vec<SectorResult> {
+        let (proposal, sectors, snapshot) = match (
+            self.proposals.get(proposal_id),
+            self.sector_snapshots.get(proposal_id),
+            self.power_snapshots.get(proposal_id),
+        ) {
+            (Some(proposal), Some(sectors), Some(snapshot)) => (proposal, sectors, snapshot),
+            _ => return Vec::new(),
+        };
+
+        let ballots = votes
+            .iter()
+            .map(|vote| (&vote.voter, vote.choice, vote.power))
+            .chain(delegated_votes.iter().map(|vote| (&vote.delegator, vote.choice, vote.power)));
+
+        let mut results: Vec<SectorResult> = SectorType::ALL
+            .iter()
+            .filter(|sector| sectors.values().any(|voter_sector| voter_sector == *sector)
+                || proposal.sector_scope.required_approvals.contains(sector))
+            .map(|sector| SectorResult {
+                sector: sector.clone(),
+                required: proposal.sector_scope.required_approvals.contains(sector),
+                eligible_power: sectors
+                    .iter()
+                    .filter(|(_, voter_sector)| *voter_sector == sector)
+                    .fold(0.0, |total, (voter, _)| total + snapshot[voter]),
+                votes_for: 0.0,
+                votes_against: 0.0,
+                votes_abstain: 0.0,
+                votes_veto: 0.0,
+                outcome: None,
+            })
+            .collect();
+
+        for (voter, choice, power) in ballots {
+            let result = match sectors.get(voter).and_then(|sector| results.iter_mut().find(|result| result.sector == *sector)) {
+                Some(result) => result,
+                None => continue,
+            };
+            match choice {
+                VoteChoice::For => result.votes_for += power,
+                VoteChoice::Against => result.votes_against += power,
+                VoteChoice::Abstain => result.votes_abstain += power,
+                VoteChoice::Veto => result.votes_veto += power,
+            }
+        }
+        results
+    }
+
+    /// Every eligible voter who did not vote passes their power down their
+    /// delegation chain to the first voter who did. Direct votes always win,
+    /// and each voter's power is counted at most once.
+    fn resolve_delegated_votes(&self, proposal_id: &str) -> Vec<DelegatedVote> {
+        let (proposal, snapshot) = match (self.proposals.get(proposal_id), self.power_snapshots.get(proposal_id)) {
+            (Some(proposal), Some(snapshot)) => (proposal, snapshot),
+            _ => return Vec::new(),
+        };
+        let direct_votes = self.votes.get(proposal_id);
+
+        let mut delegators: Vec<&String> = snapshot.keys().collect();
+        delegators.sort();
+
+        let mut delegated_votes = Vec::new();
+        for delegator in delegators {
+            if direct_votes.is_some_and(|votes| votes.contains_key(delegator)) {
+                continue;
+            }
+
+            let mut path = Vec::new();
+            let mut current = delegator;
+            while let Some(delegate) = self.get_delegate(current, proposal.topic) {
+                // Cycles are rejected on submission; this only guards the walk
+                if delegate == delegator || path.contains(delegate) {
+                    break;
+                }
+                path.push(delegate.clone());
+                if let Some(vote) = direct_votes.and_then(|votes| votes.get(delegate)) {
+                    delegated_votes.push(DelegatedVote {
+                        delegator: delegator.clone(),
+                        represented_by: delegate.clone(),
+                        path,
+                        choice: vote.choice,
+                        power: snapshot[delegator],
+                    });
+                    break;
+                }
+                current = delegate;
+            }
+        }
+        delegated_votes
+    }
+
+    /// Voters who have committed on a proposal; the commitments reveal nothing about their choice.
+    pub fn get_commitments(&self, proposal_id: &str) -> Vec<&VoteCommitment> {
+        self.commitments
+            .get(proposal_id)
+            .map(|commitments| commitments.values().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get_params(&self) -> &GovernanceParams {
+        &self.params
+    }
+
+    pub fn get_proposal(&self, proposal_id: &str) -> Option<&GovernanceProposal> {
+        self.proposals.get(proposal_id)
+    }
+
+    pub fn get_proposals(&self) -> Vec<&GovernanceProposal> {
+        self.proposals.values().collect()
+    }
+
+    pub fn is_eligible_voter(&self, voter: &str) -> bool {
+        self.voters.contains_key(voter)
+    }
+
+    pub fn get_voter_sector(&self, voter: &str) -> Option<&SectorType> {
+        self.voter_sectors.get(voter)
+    }
+
+    /// Power `voter` holds on a proposal, fixed when the proposal was created.
+    pub fn get_voting_power(&self, proposal_id: &str, voter: &str) -> Option<f64> {
+        self.power_snapshots.get(proposal_id)?.get(voter).copied()
+    }
+
+    pub fn get_reputation(&self, voter: &str) -> Option<f64> {
+        self.reputations.get(voter).copied()
+    }
+}
+
+impl Default for GovernanceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::{self, KeyPair};
+
+    fn signed(mut tx: Transaction, private_key: &str) -> Transaction {
+        tx.sign(private_key).unwrap();
+        tx
+    }
+
+    fn mine(registry: &mut GovernanceRegistry, index: usize, transactions: Vec<Transaction>) {
+        registry.apply_block(&Block::new(index, String::new(), transactions, 1_000 + index as u64));
+    }
+
+    /// Registers each voter under the authority key and returns their keys in order.
+    fn registry_with_voters(voters: &[&str]) -> (GovernanceRegistry, Vec<KeyPair>) {
+        let authority = signing::generate_keypair();
+        let mut registry = GovernanceRegistry::with_params(GovernanceParams {
+            authority_key: Some(authority.public_key.clone()),
+            ..GovernanceParams::default()
+        });
+        let keys: Vec<KeyPair> = voters.iter().map(|_| signing::generate_keypair()).collect();
+        let registrations = voters
+            .iter()
+            .zip(&keys)
+            .map(|(voter, keys)| signed(
+                Transaction::new(GOVERNANCE_AUTHORITY.to_string(), TransactionType::GovernanceVoterRegistration {
+                    voter: voter.to_string(),
+                    public_key: keys.public_key.clone(),
+                }),
+                &authority.private_key,
+            ))
+            .collect();
+        mine(&mut registry, 1, registrations);
+        (registry, keys)
+    }
+
+    #[test]
+    fn replayed_ballot_is_rejected() {
+        let (mut registry, keys) = registry_with_voters(&["alice"]);
+        let proposal = Transaction::new_governance_proposal(
+            "alice".to_string(), "prop_1".to_string(), "Title".to_string(), "Description".to_string(),
+            ProposalType::General, 100_000, None,
+        );
+        mine(&mut registry, 2, vec![signed(proposal, &keys[0].private_key)]);
+
+        let vote_for = signed(Transaction::new_governance_vote("alice".to_string(), "prop_1".to_string(), VoteChoice::For), &keys[0].private_key);
+        mine(&mut registry, 3, vec![vote_for.clone()]);
+        let vote_against = signed(Transaction::new_governance_vote("alice".to_string(), "prop_1".to_string(), VoteChoice::Against), &keys[0].private_key);
+        mine(&mut registry, 4, vec![vote_against]);
+
+        let error = registry.validate_transaction(&vote_for, 5, 1_005).unwrap_err();
+        assert!(error.contains("already applied"), "{}", error);
+
+        // Rebroadcasting the superseded ballot must not restore it
+        mine(&mut registry, 5, vec![vote_for]);
+        let proposal = registry.get_proposal("prop_1").unwrap();
+        assert_eq!(proposal.votes_for, 0.0);
+        assert_eq!(proposal.votes_against, 1.0);
+    }
+
+    #[test]
+    fn delegation_cycle_is_rejected() {
+        let (mut registry, keys) = registry_with_voters(&["alice", "bob", "carol"]);
+        let alice_to_bob = signed(Transaction::new_governance_delegation("alice".to_string(), "bob".to_string(), None), &keys[0].private_key);
+        let bob_to_carol = signed(Transaction::new_governance_delegation("bob".to_string(), "carol".to_string(), None), &keys[1].private_key);
+        mine(&mut registry, 2, vec![alice_to_bob, bob_to_carol]);
+
+        let carol_to_alice = signed(Transaction::new_governance_delegation("carol".to_string(), "alice".to_string(), None), &keys[2].private_key);
+        let error = registry.validate_transaction(&carol_to_alice, 3, 1_003).unwrap_err();
+        assert!(error.contains("delegation cycle"), "{}", error);
+
+        mine(&mut registry, 3, vec![carol_to_alice]);
+        for topic in GovernanceTopic::ALL.iter().copied() {
+            assert_eq!(registry.get_delegate("carol", topic), None);
+            assert_eq!(registry.get_delegate("bob", topic).map(String::as_str), Some("carol"));
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Insights computed from marketplace, credit and environmental data on chain.
+//! Every insight covers an explicit time window and carries the numbers behind
+//! its summary, so a reader can check it against the chain.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use crate::blockchain::Blockchain;
+use crate::fraud_detection::recorded_transactions;
+use crate::integration_service::{
+    MerchantEnvironmentalProfile, ProductSustainabilityData, CARBON_NEUTRAL_SHIPPING_BONUS, CARBON_NEUTRAL_SHIPPING_FACTOR,
+    CERTIFICATION_BONUS, MAX_CERTIFICATION_BONUS, RENEWABLE_ENERGY_BONUS, RENEWABLE_ENERGY_FACTOR, RENEWABLE_ENERGY_THRESHOLD,
+    WASTE_REDUCTION_WEIGHT,
+};
+use crate::transaction::{MarketplaceTransactionDetails, TransactionType};
+
+/// Readings at or above these count as a weather alert for their day.
+pub const HEAT_ALERT_TEMPERATURE: f64 = 35.0;
+pub const RAINFALL_ALERT_HUMIDITY: f64 = 95.0;
+/// Footprint change per day, as a share of the mean, below which a trend is flat.
+const FLAT_TREND: f64 = 0.01;
+const TOP_EMITTERS: usize = 3;
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InsightKind {
+    MerchantFootprintTrend,
+    CategoryFootprintTrend,
+    TopEmitters,
+    CreditIssuance,
+    WeatherActivity,
+    ScoreImprovement,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Insight {
+    pub kind: InsightKind,
+    /// The merchant, category or "marketplace" the insight is about
+    pub subject: String,
+    pub summary: String,
+    pub window_start: u64,
+    pub window_end: u64,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+/// What the chain alone does not hold: product categories and merchant practices.
+pub struct InsightSources<'a> {
+    pub products: &'a HashMap<String, ProductSustainabilityData>,
+    pub merchants: &'a HashMap<String, MerchantEnvironmentalProfile>,
+    /// Verified certifications per merchant DID
+    pub verified_certifications: HashMap<String, usize>,
+}
+
+struct Sale {
+    details: MarketplaceTransactionDetails,
+    timestamp: u64,
+}
+
+/// Insights over `[window_start, window_end)`, and the number of transactions they draw on.
+pub fn generate_insights(blockchain: &Blockchain, sources: &InsightSources, window_start: u64, window_end: u64) -> (Vec<Insight>, usize) {
+    let in_window = |timestamp: u64| timestamp >= window_start && timestamp < window_end;
+    let transactions: Vec<_> = recorded_transactions(blockchain)
+        .into_iter()
+        .filter(|tx| in_window(tx.timestamp))
+        .collect();
+    let sales: Vec<Sale> = transactions
+        .iter()
+        .filter_map(|tx| tx.get_marketplace_details().map(|details| Sale { details, timestamp: tx.timestamp }))
+        .collect();
+    let credits: f64 = transactions
+        .iter()
+        .filter_map(|tx| match &tx.transaction_type {
+            TransactionType::CarbonCredit { amount, .. } => Some(*amount),
+            _ => None,
+        })
+        .sum();
+    let readings: Vec<_> = blockchain.get_environmental_data()
+        .into_iter()
+        .chain(blockchain.get_pending_transactions().iter().filter_map(|tx| match &tx.transaction_type {
+            TransactionType::EnvironmentalData { data } => Some(data.clone()),
+            _ => None,
+        }))
+        .filter(|data| in_window(data.timestamp))
+        .collect();
+
+    let insight = |kind, subject: &str, summary: String, metrics: Vec<(&str, f64)>| Insight {
+        kind,
+        subject: subject.to_string(),
+        summary,
+        window_start,
+        window_end,
+        metrics: metrics.into_iter().map(|(name, value)| (name.to_string(), value)).collect(),
+    };
+    let mut insights = Vec::new();
+
+    // Footprint trends per merchant and per product category
+    let category_of = |sale: &Sale| {
+        sources.products
+            .get(&sale.details.product_did)
+            .map(|product| product.category.clone())
+            .filter(|category| !category.is_empty())
+            .unwrap_or_else(|| "Uncategorized".to_string())
+    };
+    for kind in [InsightKind::MerchantFootprintTrend, InsightKind::CategoryFootprintTrend] {
+        let mut groups: BTreeMap<String, Vec<&Sale>> = BTreeMap::new();
+        for sale in &sales {
+            let subject = match kind {
+                InsightKind::MerchantFootprintTrend => sale.details.seller_did.clone(),
+                _ => category_of(sale),
+            };
+            groups.entry(subject).or_default().push(sale);
+        }
+        for (subject, group) in groups {
+            let points: Vec<(f64, f64)> = group
+                .iter()
+                .map(|sale| ((sale.timestamp - window_start) as f64 / DAY as f64, sale.details.carbon_footprint.unwrap_or(0.0)))
+                .collect();
+            let total: f64 = points.iter().map(|(_, footprint)| footprint).sum();
+            let mean = total / points.len() as f64;
+            let slope = least_squares_slope(&points).unwrap_or(0.0);
+            let direction = if mean <= 0.0 || slope.abs() < mean * FLAT_TREND {
+                "flat"
+            } else if slope > 0.0 {
+                "rising"
+            } else {
+                "falling"
+            };
+            insights.push(insight(
+                kind,
+                &subject,
+                format!("{}: footprint per sale {} at {:.2} kg CO2 ({:+.3} kg/day over {} sales)", subject, direction, mean, slope, points.len()),
+                vec![("sales", points.len() as f64), ("total_kg_co2", total), ("mean_kg_co2_per_sale", mean), ("slope_kg_co2_per_day", slope)],
+            ));
+        }
+    }
+
+    // Merchants contributing most to emissions
+    let mut emissions: HashMap<&str, f64> = HashMap::new();
+    for sale in &sales {
+        *emissions.entry(sale.details.seller_did.as_str()).or_default() += sale.details.carbon_footprint.unwrap_or(0.0);
+    }
+    let total_emissions: f64 = emissions.values().sum();
+    let mut ranked: Vec<(&str, f64)> = emissions.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+    if total_emissions > 0.0 {
+        for (rank, (merchant, kg)) in ranked.iter().take(TOP_EMITTERS).enumerate() {
+            let share = kg / total_emissions * 100.0;
+            insights.push(insight(
+                InsightKind::TopEmitters,
+                merchant,
+                format!("#{} emitter {}: {:.2} kg CO2, {:.1}% of marketplace emissions", rank + 1, merchant, kg, share),
+                vec![("rank", (rank + 1) as f64), ("kg_co2", *kg), ("share_percent", share), ("marketplace_kg_co2", total_emissions)],
+            ));
+        }
+    }
+
+    // Carbon credit issuance against sales volume
+    let sales_volume: f64 = sales.iter().map(|sale| sale.details.amount).sum();
+    if !sales.is_empty() {
+        let per_thousand = credits / sales_volume.max(f64::EPSILON) * 1_000.0;
+        insights.push(insight(
+            InsightKind::CreditIssuance,
+            "marketplace",
+            format!("{:.2} credits issued on {:.2} in sales ({:.2} per 1,000 of sales)", credits, sales_volume, per_thousand),
+            vec![
+                ("credits_issued", credits),
+                ("sales_volume", sales_volume),
+                ("credits_per_thousand_sales", per_thousand),
+                ("sales", sales.len() as f64),
+            ],
+        ));
+    }
+
+    // Marketplace activity on days with weather alerts
+    if !readings.is_empty() {
+        let days = (window_end.saturating_sub(window_start)).div_ceil(DAY).max(1) as usize;
+        let mut alert_days = vec![false; days];
+        for data in &readings {
+            if data.temperature >= HEAT_ALERT_TEMPERATURE || data.humidity >= RAINFALL_ALERT_HUMIDITY {
+                alert_days[((data.timestamp - window_start) / DAY) as usize] = true;
+            }
+        }
+        let mut daily_sales = vec![0.0; days];
+        for sale in &sales {
+            daily_sales[((sale.timestamp - window_start) / DAY) as usize] += 1.0;
+        }
+        let average = |alert: bool| {
+            let counts: Vec<f64> = daily_sales.iter().zip(&alert_days).filter(|(_, day)| **day == alert).map(|(count, _)| *count).collect();
+            (counts.len(), if counts.is_empty() { 0.0 } else { counts.iter().sum::<f64>() / counts.len() as f64 })
+        };
+        let (alert_count, alert_average) = average(true);
+        let (normal_count, normal_average) = average(false);
+        let indicator: Vec<f64> = alert_days.iter().map(|alert| if *alert { 1.0 } else { 0.0 }).collect();
+
+        let mut metrics = vec![
+            ("alert_days", alert_count as f64),
+            ("normal_days", normal_count as f64),
+            ("mean_sales_on_alert_days", alert_average),
+            ("mean_sales_on_normal_days", normal_average),
+        ];
+        let summary = match pearson(&indicator, &daily_sales) {
+            Some(correlation) => {
+                metrics.push(("correlation", correlation));
+                format!(
+                    "Sales average {:.1}/day on {} alert days vs {:.1}/day otherwise (correlation {:+.2})",
+                    alert_average, alert_count, normal_average, correlation
+                )
+            }
+            None => format!("{} alert days in {} days; not enough variation to correlate with sales", alert_count, days),
+        };
+        insights.push(insight(InsightKind::WeatherActivity, "marketplace", summary, metrics));
+    }
+
+    // Practices that would raise a selling merchant's sustainability score
+    let mut selling: Vec<&str> = sales.iter().map(|sale| sale.details.seller_did.as_str()).collect();
+    selling.sort();
+    selling.dedup();
+    for merchant in selling {
+        let Some(profile) = sources.merchants.get(merchant) else { continue };
+        let merchant_sales: Vec<&Sale> = sales.iter().filter(|sale| sale.details.seller_did == merchant).collect();
+        let footprint: f64 = merchant_sales.iter().map(|sale| sale.details.carbon_footprint.unwrap_or(0.0)).sum();
+        let scores: Vec<f64> = merchant_sales.iter().filter_map(|sale| sale.details.sustainability_score).collect();
+        let mean_score = if scores.is_empty() { 0.0 } else { scores.iter().sum::<f64>() / scores.len() as f64 };
+
+        let mut steps = Vec::new();
+        let (mut score_gain, mut kg_saved, mut remaining) = (0.0, 0.0, footprint);
+        if !profile.carbon_neutral_shipping {
+            steps.push("switch to carbon neutral shipping");
+            score_gain += CARBON_NEUTRAL_SHIPPING_BONUS;
+            kg_saved += remaining * (1.0 - CARBON_NEUTRAL_SHIPPING_FACTOR);
+            remaining *= CARBON_NEUTRAL_SHIPPING_FACTOR;
+        }
+        if profile.renewable_energy_usage <= RENEWABLE_ENERGY_THRESHOLD {
+            steps.push("source over half its energy from renewables");
+            score_gain += RENEWABLE_ENERGY_BONUS;
+            kg_saved += remaining * (1.0 - RENEWABLE_ENERGY_FACTOR);
+        }
+        if profile.waste_reduction_score < 10.0 {
+            steps.push("improve waste reduction");
+            score_gain += (10.0 - profile.waste_reduction_score) * WASTE_REDUCTION_WEIGHT;
+        }
+        let certifications = sources.verified_certifications.get(merchant).copied().unwrap_or(0) as f64;
+        if certifications * CERTIFICATION_BONUS < MAX_CERTIFICATION_BONUS {
+            steps.push("earn verified eco-certifications");
+            score_gain += MAX_CERTIFICATION_BONUS - certifications * CERTIFICATION_BONUS;
+        }
+        // Scores are capped at 10
+        let score_gain = score_gain.min(10.0 - mean_score).max(0.0);
+        if steps.is_empty() || score_gain <= 0.0 {
+            continue;
+        }
+        insights.push(insight(
+            InsightKind::ScoreImprovement,
+            merchant,
+            format!("{} could gain up to {:.1} points (from {:.1}) and save {:.2} kg CO2: {}", merchant, score_gain, mean_score, kg_saved, steps.join(", ")),
+            vec![("current_score", mean_score), ("potential_score_gain", score_gain), ("potential_kg_co2_saved", kg_saved)],
+        ));
+    }
+
+    (insights, transactions.len())
+}
+
+/// Slope of the least-squares line through `points`, if the x values vary.
+fn least_squares_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let spread: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    (spread > 0.0).then(|| points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>() / spread)
+}
+
+/// Pearson correlation, if both series vary.
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let count = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / count;
+    let mean_y = ys.iter().sum::<f64>() / count;
+    let covariance: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let spread_x: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let spread_y: f64 = ys.iter().map(|y| (y - mean_y).powi(2)).sum();
+    (spread_x > 0.0 && spread_y > 0.0).then(|| covariance / (spread_x * spread_y).sqrt())
+}
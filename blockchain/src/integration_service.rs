@@ -1,10 +1,28 @@
-use crate::ai_module::AiModule;
+use crate::ai_module::{AiAnalytics, AiModule};
+use crate::insights::InsightSources;
 
-use crate::privacy_manager::{PrivacyManager, SectorType};
+use crate::privacy_manager::{identity_proof_message, AuditedRecord, DataAccessEvent, IdentityAttestation, PrivacyManager, PrivacyProof, RegulatorViewKey, SectorType, VerificationLevel, ViewKeyAccessEvent};
+use crate::compliance::ComplianceReport;
+use crate::data_privacy_act::{BreachAudience, DataSubjectRight, NpcReport, SubjectAccessExport, SubjectAuthorization};
+use crate::zk_proofs::{self, PedersenOpening, SchnorrProof};
+use crate::retention::{DeletionReceipt, RecordCategory, RetentionReport};
+use crate::disclosure::{self, DisclosureOpening, RedactedView};
+use crate::certification::{self, CertificationRegistry, EcoCertificationCredential};
+use crate::credentials::{self, CredentialVerificationReport, StatusList, StatusListRegistry, VerifiableCredential};
+use crate::did::{DidDocument, DidRegistry, DidResolutionResult, VerificationRelationship};
+use crate::fraud_detection::{FraudReport, RiskAssessment, RiskDecision};
+use crate::threat_detection::ThreatAlert;
+use crate::carbon_registry::{self, CarbonCreditRegistry, CarbonProject, OwnershipEvent, ReductionClaim, ReductionSource, RetirementCertificate};
+use crate::carbon_audit::{self, CreditAuditReport};
+use crate::governance::{self, GovernanceAction, GovernanceParams, GovernanceProposal, GovernanceRegistry, ParameterChangeRecord, ReputationBasis, VoteTally};
 
+use crate::block::Block;
 use crate::blockchain::Blockchain;
 use crate::transaction::{Transaction, TransactionType};
 use crate::environment::EnvironmentalData;
+use crate::wallet_integration::WalletIntegration;
+use crate::treasury::{self, Treasury, TreasuryReport};
+use crate::signing;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -25,8 +43,12 @@ pub struct ProductSustainabilityData {
     pub product_id: String,
     pub carbon_footprint: f64,
     pub sustainability_score: f64,
+    /// Self-declared labels; only credentials in the certification registry affect scoring
     pub eco_certification: Vec<String>,
     pub supply_chain_transparency: f64,
+    /// Product category used to group insights, e.g. "Apparel"
+    #[serde(default)]
+    pub category: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,26 +58,129 @@ pub struct MerchantEnvironmentalProfile {
     pub carbon_neutral_shipping: bool,
     pub renewable_energy_usage: f64,
     pub waste_reduction_score: f64,
+    /// Self-declared labels; only credentials in the certification registry affect scoring
     pub environmental_certifications: Vec<String>,
 }
 
+/// DID holding the credits buyers retire when they offset an order at checkout.
+/// The service creates it with its own key on startup.
+pub const CARBON_OFFSET_POOL: &str = "did:veritoken:ecogov:offset-pool";
+
+/// How merchant practices adjust a sale's footprint and sustainability score.
+pub const CARBON_NEUTRAL_SHIPPING_FACTOR: f64 = 0.7;
+pub const CARBON_NEUTRAL_SHIPPING_BONUS: f64 = 1.0;
+pub const RENEWABLE_ENERGY_THRESHOLD: f64 = 0.5;
+pub const RENEWABLE_ENERGY_FACTOR: f64 = 0.8;
+pub const RENEWABLE_ENERGY_BONUS: f64 = 0.5;
+pub const WASTE_REDUCTION_WEIGHT: f64 = 0.1;
+pub const CERTIFICATION_BONUS: f64 = 0.5;
+pub const MAX_CERTIFICATION_BONUS: f64 = 1.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonOffsetQuote {
+    pub order_id: String,
+    pub carbon_footprint_kg: f64,
+    pub credits_required: u64,
+    pub currency: String,
+    pub rate_per_kg: f64,
+    pub offset_cost: f64,
+    pub credits_available: u64,
+}
+
+/// A sale fraud screening kept off chain until it is released or rejected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldSale {
+    pub sideline_tx: SidelineTransaction,
+    pub with_offset: bool,
+    pub assessment: RiskAssessment,
+    pub held_at: u64,
+}
+
+/// Links an order to the retirements that offset it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutOffset {
+    pub order_id: String,
+    pub marketplace_tx_id: String,
+    pub buyer_did: String,
+    pub credits_retired: u64,
+    pub offset_cost: f64,
+    pub currency: String,
+    pub retirement_certificate_ids: Vec<String>,
+}
+
+struct PreparedSale {
+    marketplace_tx: Transaction,
+    credit_tx: Option<Transaction>,
+    environmental_tx: Transaction,
+    carbon_footprint: f64,
+    sustainability_score: f64,
+    credit_amount: Option<f64>,
+}
+
+impl PreparedSale {
+    fn into_transactions(self) -> Vec<Transaction> {
+        let mut transactions = vec![self.marketplace_tx];
+        transactions.extend(self.credit_tx);
+        transactions.push(self.environmental_tx);
+        transactions
+    }
+}
+
 pub struct LightChainIntegrationService {
     blockchain: Blockchain,
     product_sustainability_db: HashMap<String, ProductSustainabilityData>,
     merchant_profiles: HashMap<String, MerchantEnvironmentalProfile>,
     carbon_credit_rates: HashMap<String, f64>, // currency -> carbon credit rate
+    credit_award_threshold: f64, // sustainability score a sale must exceed to earn credits
+    wallet_integration: WalletIntegration,
     privacy_manager: PrivacyManager,
+    certification_registry: CertificationRegistry,
+    certification_registry_key: String, // signs certifier registrations and deactivations
+    did_registry: DidRegistry,
+    status_lists: StatusListRegistry,
+    carbon_registry: CarbonCreditRegistry,
+    offset_pool_key: String, // signs checkout retirements from the offset pool
+    carbon_registry_key: String, // signs the carbon registry authority's transactions
+    governance: GovernanceRegistry,
+    governance_authority_key: String, // signs the governance authority's transactions
+    voter_identities: HashMap<String, String>, // voter -> anonymous identity backing their sector
+    treasury: Treasury,
+    treasury_key: String, // signs grant disbursements
+    held_sales: HashMap<String, HeldSale>, // order id -> sale awaiting fraud review
     pub ai_module: AiModule,
 }
 
 impl LightChainIntegrationService {
     pub fn new() -> Self {
-let mut service = LightChainIntegrationService {
+        let governance_authority_keys = signing::generate_keypair();
+        let governance_params = GovernanceParams {
+            authority_key: Some(governance_authority_keys.public_key),
+            ..GovernanceParams::default()
+        };
+        let carbon_registry_keys = signing::generate_keypair();
+        let treasury_keys = signing::generate_keypair();
+        let certification_registry_keys = signing::generate_keypair();
+        let mut service = LightChainIntegrationService {
             blockchain: Blockchain::new(),
             product_sustainability_db: HashMap::new(),
             merchant_profiles: HashMap::new(),
             carbon_credit_rates: HashMap::new(),
-            privacy_manager: PrivacyManager::default(),
+            credit_award_threshold: 7.0,
+            wallet_integration: WalletIntegration::default(),
+            privacy_manager: PrivacyManager::with_authority_keys(signing::generate_keypair()),
+            certification_registry: CertificationRegistry::with_authority_key(certification_registry_keys.public_key),
+            certification_registry_key: certification_registry_keys.private_key,
+            did_registry: DidRegistry::default(),
+            status_lists: StatusListRegistry::default(),
+            carbon_registry: CarbonCreditRegistry::with_authority_key(carbon_registry_keys.public_key),
+            offset_pool_key: String::new(),
+            carbon_registry_key: carbon_registry_keys.private_key,
+            governance: GovernanceRegistry::with_params(governance_params),
+            governance_authority_key: governance_authority_keys.private_key,
+            voter_identities: HashMap::new(),
+            treasury: Treasury::with_authority_key(treasury_keys.public_key),
+            treasury_key: treasury_keys.private_key,
+            held_sales: HashMap::new(),
             ai_module: AiModule::default(),
         };
         
@@ -65,7 +190,13 @@ let mut service = LightChainIntegrationService {
         service.carbon_credit_rates.insert("EUR".to_string(), 0.13); // €0.13 per kg CO2
         
         service.ai_module.enable_threat_detection();
-        service.ai_module.generate_insights();
+        
+        // The offset pool is a DID so its retirements are signed like any other holder's
+        let offset_pool_keys = signing::generate_keypair();
+        let mut offset_pool_tx = Transaction::new_did_creation(DidDocument::new(CARBON_OFFSET_POOL, &offset_pool_keys.public_key));
+        offset_pool_tx.sign(&offset_pool_keys.private_key).unwrap();
+        service.submit_did_transaction(offset_pool_tx).unwrap();
+        service.offset_pool_key = offset_pool_keys.private_key;
 
         service
     }
@@ -74,15 +205,283 @@ let mut service = LightChainIntegrationService {
         self.product_sustainability_db.insert(product_id, data);
     }
     
-    pub fn register_merchant_profile(&mut self, merchant_did: String, profile: MerchantEnvironmentalProfile) {
+    /// Fails while the DID is rate limited or banned for flooding registrations.
+    pub fn register_merchant_profile(&mut self, merchant_did: String, profile: MerchantEnvironmentalProfile) -> Result<(), String> {
+        self.ai_module.screen_registration(&merchant_did, Self::current_timestamp())?;
         self.merchant_profiles.insert(merchant_did, profile);
+        Ok(())
     }
     
     pub fn process_sideline_transaction(&mut self, sideline_tx: SidelineTransaction) -> Result<String, String> {
+        self.process_sale(sideline_tx, true)
+    }
+    
+    fn process_sale(&mut self, sideline_tx: SidelineTransaction, screen: bool) -> Result<String, String> {
         println!("🔄 Processing Sideline_Pinas transaction: {}", sideline_tx.transaction_id);
         
+        let sale = self.prepare_sale(&sideline_tx)?;
+        if screen {
+            self.screen_sale(&sideline_tx, &sale.marketplace_tx, false)?;
+        }
+        let blockchain_tx_id = sale.marketplace_tx.id.clone();
+        let (carbon_footprint, sustainability_score, credit_amount) = (sale.carbon_footprint, sale.sustainability_score, sale.credit_amount);
+        self.commit_atomically(sale.into_transactions())?;
+        
+        if let Some(credit_amount) = credit_amount {
+            println!("🌟 Carbon credit awarded to {}: {:.2} credits", sideline_tx.seller_did, credit_amount);
+        }
+        println!("✅ Transaction processed successfully");
+        println!("   🌱 Carbon Footprint: {:.2} kg CO2", carbon_footprint);
+        println!("   ⭐ Sustainability Score: {:.1}/10", sustainability_score);
+        
+        Ok(blockchain_tx_id)
+    }
+    
+    /// Quotes what it costs the buyer to offset an order's carbon footprint,
+    /// priced with `carbon_credit_rates` in the order's currency.
+    pub fn quote_carbon_offset(&self, sideline_tx: &SidelineTransaction) -> Result<CarbonOffsetQuote, String> {
+        let rate_per_kg = *self.carbon_credit_rates.get(&sideline_tx.currency)
+            .ok_or_else(|| format!("No carbon credit rate for currency {}", sideline_tx.currency))?;
+        let (carbon_footprint, _) = self.calculate_environmental_impact(sideline_tx);
+        
+        // Credits are whole kilograms, so round up to fully cover the footprint
+        let credits_required = carbon_footprint.ceil().max(1.0) as u64;
+        
+        Ok(CarbonOffsetQuote {
+            order_id: sideline_tx.transaction_id.clone(),
+            carbon_footprint_kg: carbon_footprint,
+            credits_required,
+            currency: sideline_tx.currency.clone(),
+            rate_per_kg,
+            offset_cost: credits_required as f64 * rate_per_kg,
+            credits_available: self.carbon_registry.get_active_balance(CARBON_OFFSET_POOL),
+        })
+    }
+    
+    /// Records the purchase and retires enough pool credits to offset it, all
+    /// or nothing: if the pool cannot cover the footprint, neither is recorded.
+    pub fn process_sideline_transaction_with_offset(&mut self, sideline_tx: SidelineTransaction) -> Result<CheckoutOffset, String> {
+        self.process_sale_with_offset(sideline_tx, true)
+    }
+    
+    fn process_sale_with_offset(&mut self, sideline_tx: SidelineTransaction, screen: bool) -> Result<CheckoutOffset, String> {
+        println!("🔄 Processing Sideline_Pinas transaction with carbon offset: {}", sideline_tx.transaction_id);
+        
+        self.carbon_registry.check_order_offset(&sideline_tx.transaction_id)?;
+        let quote = self.quote_carbon_offset(&sideline_tx)?;
+        let serial_ranges = self.carbon_registry
+            .select_active_serials(CARBON_OFFSET_POOL, quote.credits_required)
+            .ok_or_else(|| format!(
+                "Offset pool holds {} credits, {} needed for order {}",
+                quote.credits_available, quote.credits_required, quote.order_id
+            ))?;
+        
+        let sale = self.prepare_sale(&sideline_tx)?;
+        if screen {
+            self.screen_sale(&sideline_tx, &sale.marketplace_tx, true)?;
+        }
+        let marketplace_tx_id = sale.marketplace_tx.id.clone();
+        
+        let mut retirement_certificate_ids = Vec::new();
+        let mut transactions = sale.into_transactions();
+        for (serial_start, serial_end) in serial_ranges {
+            let certificate_id = format!("ret_{:016x}", rand::random::<u64>());
+            let mut retirement_tx = Transaction::new(
+                CARBON_OFFSET_POOL.to_string(),
+                TransactionType::CarbonCreditRetirement {
+                    certificate_id: certificate_id.clone(),
+                    serial_start,
+                    serial_end,
+                    beneficiary: sideline_tx.buyer_did.clone(),
+                    reason: format!("Checkout offset for order {}", sideline_tx.transaction_id),
+                    order_id: Some(sideline_tx.transaction_id.clone()),
+                },
+            );
+            retirement_tx.sign(&self.offset_pool_key)?;
+            transactions.push(retirement_tx);
+            retirement_certificate_ids.push(certificate_id);
+        }
+        
+        self.commit_atomically(transactions)?;
+        
+        let checkout_offset = CheckoutOffset {
+            order_id: sideline_tx.transaction_id.clone(),
+            marketplace_tx_id,
+            buyer_did: sideline_tx.buyer_did.clone(),
+            credits_retired: quote.credits_required,
+            offset_cost: quote.offset_cost,
+            currency: quote.currency,
+            retirement_certificate_ids,
+        };
+        // Holds the buyer's DID, so it is kept encrypted and subject to retention
+        self.privacy_manager.store_personal_record(
+            &checkout_offset.order_id,
+            &checkout_offset.buyer_did,
+            SectorType::Individual,
+            RecordCategory::CheckoutOffset,
+            &checkout_offset,
+            Self::current_timestamp(),
+        )?;
+        
+        println!("✅ Transaction processed and offset successfully");
+        println!("   ♻️  Retired {} credits for {:.2} kg CO2 ({:.2} {})",
+            checkout_offset.credits_retired, quote.carbon_footprint_kg, checkout_offset.offset_cost, checkout_offset.currency);
+        
+        Ok(checkout_offset)
+    }
+    
+    /// Runs fraud screening on a prepared sale. Held sales wait in `held_sales`
+    /// for review; held and rejected sales are both reported as errors.
+    fn screen_sale(&mut self, sideline_tx: &SidelineTransaction, marketplace_tx: &Transaction, with_offset: bool) -> Result<(), String> {
+        let assessment = match self.ai_module.assess_transaction(&self.blockchain, &self.did_registry, marketplace_tx) {
+            Some(assessment) => assessment,
+            None => return Ok(()),
+        };
+        let reasons = assessment.reasons
+            .iter()
+            .map(|reason| reason.detail.clone())
+            .collect::<Vec<_>>()
+            .join("; ");
+        
+        match assessment.decision {
+            RiskDecision::Allow => Ok(()),
+            RiskDecision::Hold => {
+                let order_id = sideline_tx.transaction_id.clone();
+                self.held_sales.insert(order_id.clone(), HeldSale {
+                    sideline_tx: sideline_tx.clone(),
+                    with_offset,
+                    held_at: Self::current_timestamp(),
+                    assessment: assessment.clone(),
+                });
+                Err(format!("Order {} held for fraud review (risk {:.2}): {}", order_id, assessment.score, reasons))
+            }
+            RiskDecision::Reject => Err(format!(
+                "Order {} rejected by fraud screening (risk {:.2}): {}",
+                sideline_tx.transaction_id, assessment.score, reasons
+            )),
+        }
+    }
+    
+    /// Records a held sale after review, skipping fraud screening. Returns the
+    /// marketplace transaction id.
+    pub fn release_held_sale(&mut self, order_id: &str) -> Result<String, String> {
+        let held = self.held_sales
+            .remove(order_id)
+            .ok_or_else(|| format!("Order {} is not held", order_id))?;
+        let result = if held.with_offset {
+            self.process_sale_with_offset(held.sideline_tx.clone(), false)
+                .map(|checkout_offset| checkout_offset.marketplace_tx_id)
+        } else {
+            self.process_sale(held.sideline_tx.clone(), false)
+        };
+        if result.is_err() {
+            self.held_sales.insert(order_id.to_string(), held);
+        }
+        result
+    }
+    
+    /// Drops a held sale after review.
+    pub fn reject_held_sale(&mut self, order_id: &str) -> Result<HeldSale, String> {
+        self.held_sales
+            .remove(order_id)
+            .ok_or_else(|| format!("Order {} is not held", order_id))
+    }
+    
+    pub fn get_held_sales(&self) -> Vec<&HeldSale> {
+        self.held_sales.values().collect()
+    }
+    
+    /// Marketplace insights over `[window_start, window_end)`.
+    pub fn generate_insights(&mut self, window_start: u64, window_end: u64) -> &AiAnalytics {
+        let verified_certifications = self.merchant_profiles
+            .keys()
+            .map(|merchant| (merchant.clone(), self.certification_registry.verified_certifications(merchant, window_end).len()))
+            .collect();
+        let sources = InsightSources {
+            products: &self.product_sustainability_db,
+            merchants: &self.merchant_profiles,
+            verified_certifications,
+        };
+        self.ai_module.generate_insights(&self.blockchain, &sources, window_start, window_end)
+    }
+    
+    /// Scores every marketplace sale recorded so far.
+    pub fn run_fraud_detection(&self) -> FraudReport {
+        self.ai_module.run_fraud_detection(&self.blockchain, &self.did_registry, Self::current_timestamp())
+    }
+    
+    // Threat detection methods
+    /// Accepts a transaction gossiped by `peer` after threat screening, through
+    /// the same validation as a local submission of its type. Rewards, credits,
+    /// treasury movements and authority records are never taken from peers.
+    pub fn receive_transaction(&mut self, peer: &str, tx: Transaction) -> Result<String, String> {
+        let signature_valid = match &tx.transaction_type {
+            TransactionType::GovernanceVote { .. }
+            | TransactionType::GovernanceVoteCommitment { .. }
+            | TransactionType::GovernanceVoteReveal { .. }
+            | TransactionType::GovernanceProposalCreation { .. }
+            | TransactionType::GovernanceDelegation { .. }
+            | TransactionType::GovernanceUndelegation { .. } => self.governance.verify_voter_signature(&tx).is_ok(),
+            // A self-controlled DID is signed by a key of the document it creates
+            TransactionType::DidCreation { document } if document.controller == document.id => document
+                .keys_for(VerificationRelationship::Authentication)
+                .iter()
+                .any(|method| tx.verify_signature(&method.public_key_hex)),
+            TransactionType::DidCreation { .. }
+            | TransactionType::DidUpdate { .. }
+            | TransactionType::DidDeactivation { .. }
+            | TransactionType::CredentialStatusListPublication { .. }
+            | TransactionType::CarbonCreditTransfer { .. }
+            | TransactionType::CarbonCreditRetirement { .. }
+            | TransactionType::EnvironmentalData { .. }
+            | TransactionType::EnvironmentalImpact { .. }
+            | TransactionType::ValidatorStake { .. } => self.did_registry.verify_transaction(&tx).is_ok(),
+            _ => return Err(format!("{} transactions are not accepted from peers", tx.get_type_name())),
+        };
+        self.ai_module.screen_transaction(peer, &tx, signature_valid, Self::current_timestamp())?;
+        if self.blockchain.get_pending_transactions().iter().any(|pending| pending.id == tx.id) {
+            return Err(format!("Transaction {} is already pending", tx.id));
+        }
+        
+        match &tx.transaction_type {
+            TransactionType::GovernanceVote { .. }
+            | TransactionType::GovernanceVoteCommitment { .. }
+            | TransactionType::GovernanceVoteReveal { .. }
+            | TransactionType::GovernanceProposalCreation { .. }
+            | TransactionType::GovernanceDelegation { .. }
+            | TransactionType::GovernanceUndelegation { .. } => self.submit_governance_transaction(tx),
+            TransactionType::DidCreation { .. } | TransactionType::DidUpdate { .. } | TransactionType::DidDeactivation { .. } => {
+                self.submit_did_transaction(tx)
+            }
+            TransactionType::CredentialStatusListPublication { .. } => self.publish_status_list(tx),
+            TransactionType::CarbonCreditTransfer { .. } | TransactionType::CarbonCreditRetirement { .. } => self.submit_carbon_transaction(tx),
+            _ => {
+                let tx_id = tx.id.clone();
+                self.blockchain.add_transaction(tx);
+                Ok(tx_id)
+            }
+        }
+    }
+    
+    /// Screens a block announced by `peer` for a far-future timestamp or a deep reorg.
+    pub fn screen_block(&mut self, peer: &str, block: &Block) -> Result<(), String> {
+        self.ai_module.screen_block(peer, block, &self.blockchain, Self::current_timestamp())
+    }
+    
+    pub fn get_threat_alerts(&self) -> &[ThreatAlert] {
+        self.ai_module.get_threat_alerts()
+    }
+    
+    /// `None` once the offset record has passed its retention window; the
+    /// retirements themselves stay available from `get_order_retirements`.
+    pub fn get_order_offset(&self, order_id: &str) -> Option<CheckoutOffset> {
+        self.privacy_manager.load_personal_record(order_id).ok()
+    }
+    
+    /// Builds every transaction a sale produces without touching the chain.
+    fn prepare_sale(&self, sideline_tx: &SidelineTransaction) -> Result<PreparedSale, String> {
         // Create blockchain transaction
-        let mut blockchain_tx = Transaction::new_marketplace_transaction(
+        let mut marketplace_tx = Transaction::new_marketplace_transaction(
             sideline_tx.buyer_did.clone(),
             sideline_tx.seller_did.clone(),
             sideline_tx.product_did.clone(),
@@ -93,25 +492,37 @@ let mut service = LightChainIntegrationService {
         );
         
         // Calculate environmental impact
-        let (carbon_footprint, sustainability_score) = self.calculate_environmental_impact(&sideline_tx);
-        blockchain_tx.update_environmental_impact(carbon_footprint, sustainability_score);
-        
-        // Add to blockchain
-        self.blockchain.add_transaction(blockchain_tx.clone());
+        let (carbon_footprint, sustainability_score) = self.calculate_environmental_impact(sideline_tx);
+        marketplace_tx.update_environmental_impact(carbon_footprint, sustainability_score);
         
-        // Process carbon credits if applicable
-        if sustainability_score > 7.0 {
-            self.award_carbon_credits(&sideline_tx.seller_did, carbon_footprint, &sideline_tx.currency);
-        }
-        
-        // Record environmental impact
-        self.record_environmental_impact(&sideline_tx);
-        
-        println!("✅ Transaction processed successfully");
-        println!("   🌱 Carbon Footprint: {:.2} kg CO2", carbon_footprint);
-        println!("   ⭐ Sustainability Score: {:.1}/10", sustainability_score);
+        // Process carbon credits if applicable, rejecting reductions that were already credited
+        let (credit_tx, credit_amount) = if sustainability_score > self.credit_award_threshold {
+            let claim = ReductionClaim {
+                project_id: format!("sustainable_merchant_{}", sideline_tx.seller_did),
+                vintage: carbon_registry::vintage_for_timestamp(sideline_tx.timestamp),
+                source: ReductionSource::MarketplaceSale {
+                    order_id: sideline_tx.transaction_id.clone(),
+                    product_did: sideline_tx.product_did.clone(),
+                },
+                reduction_kg: carbon_footprint,
+                evidence_tx_id: marketplace_tx.id.clone(),
+            };
+            self.carbon_registry.check_claim(&claim)?;
+            
+            let (credit_tx, credit_amount) = self.create_carbon_credit_transaction(carbon_footprint, &sideline_tx.currency, claim)?;
+            (Some(credit_tx), Some(credit_amount))
+        } else {
+            (None, None)
+        };
         
-        Ok(blockchain_tx.id)
+        Ok(PreparedSale {
+            marketplace_tx,
+            credit_tx,
+            environmental_tx: self.create_environmental_impact_transaction(sideline_tx),
+            carbon_footprint,
+            sustainability_score,
+            credit_amount,
+        })
     }
     
     fn calculate_environmental_impact(&self, tx: &SidelineTransaction) -> (f64, f64) {
@@ -130,18 +541,23 @@ let mut service = LightChainIntegrationService {
         // Adjust based on merchant profile
         if let Some(merchant_profile) = self.merchant_profiles.get(&tx.seller_did) {
             if merchant_profile.carbon_neutral_shipping {
-                carbon_footprint *= 0.7; // 30% reduction for carbon neutral shipping
-                sustainability_score += 1.0;
+                carbon_footprint *= CARBON_NEUTRAL_SHIPPING_FACTOR; // 30% reduction for carbon neutral shipping
+                sustainability_score += CARBON_NEUTRAL_SHIPPING_BONUS;
             }
             
-            if merchant_profile.renewable_energy_usage > 0.5 {
-                carbon_footprint *= 0.8; // 20% reduction for renewable energy
-                sustainability_score += 0.5;
+            if merchant_profile.renewable_energy_usage > RENEWABLE_ENERGY_THRESHOLD {
+                carbon_footprint *= RENEWABLE_ENERGY_FACTOR; // 20% reduction for renewable energy
+                sustainability_score += RENEWABLE_ENERGY_BONUS;
             }
             
-            sustainability_score += merchant_profile.waste_reduction_score * 0.1;
+            sustainability_score += merchant_profile.waste_reduction_score * WASTE_REDUCTION_WEIGHT;
         }
         
+        // Verified certifications on the product or merchant, valid at sale time
+        let verified_certifications = self.certification_registry.verified_certifications(&tx.product_did, tx.timestamp).len()
+            + self.certification_registry.verified_certifications(&tx.seller_did, tx.timestamp).len();
+        sustainability_score += (verified_certifications as f64 * CERTIFICATION_BONUS).min(MAX_CERTIFICATION_BONUS);
+        
         // Shipping distance impact (simplified)
         carbon_footprint += 0.5; // Base shipping impact
         
@@ -151,24 +567,24 @@ let mut service = LightChainIntegrationService {
         (carbon_footprint, sustainability_score)
     }
     
-    fn award_carbon_credits(&mut self, merchant_did: &str, carbon_footprint: f64, currency: &str) {
+    fn create_carbon_credit_transaction(&self, carbon_footprint: f64, currency: &str, claim: ReductionClaim) -> Result<(Transaction, f64), String> {
         let rate = self.carbon_credit_rates.get(currency).unwrap_or(&0.15);
         let credit_amount = carbon_footprint * rate * 0.1; // 10% of carbon cost as credit
         
-        let carbon_credit_tx = Transaction::new(
-            "ecogov_system".to_string(),
+        let mut carbon_credit_tx = Transaction::new(
+            carbon_registry::REGISTRY_AUTHORITY.to_string(),
             TransactionType::CarbonCredit {
                 amount: credit_amount,
-                project_id: format!("sustainable_merchant_{}", merchant_did),
+                project_id: claim.project_id.clone(),
+                claim: Some(claim),
             },
         );
+        carbon_credit_tx.sign(&self.carbon_registry_key)?;
         
-        self.blockchain.add_transaction(carbon_credit_tx);
-        
-        println!("🌟 Carbon credit awarded to {}: {:.2} credits", merchant_did, credit_amount);
+        Ok((carbon_credit_tx, credit_amount))
     }
     
-    fn record_environmental_impact(&mut self, tx: &SidelineTransaction) {
+    fn create_environmental_impact_transaction(&self, tx: &SidelineTransaction) -> Transaction {
         let env_data = EnvironmentalData {
             location: "Philippines".to_string(), // Default location for Sideline_Pinas
             co2_level: 415.0, // Current atmospheric CO2 level
@@ -177,17 +593,192 @@ let mut service = LightChainIntegrationService {
             timestamp: tx.timestamp,
         };
         
-        let env_tx = Transaction::new(
+        Transaction::new(
             tx.transaction_id.clone(),
             TransactionType::EnvironmentalData { data: env_data },
-        );
-        
-        self.blockchain.add_transaction(env_tx);
+        )
     }
     
     pub fn mine_pending_transactions(&mut self) {
         println!("⛏️  Mining pending transactions...");
         self.blockchain.mine_pending_transactions("ecogov_miner".to_string());
+        
+        if let Some(block) = self.blockchain.get_latest_block().cloned() {
+            self.apply_mined_block(&block);
+        }
+        self.execute_due_governance_actions();
+    }
+    
+    /// Rebuilds governance and treasury state from the mined chain and reapplies
+    /// every recorded parameter change, so live parameters match the chain.
+    pub fn replay_governance(&mut self) {
+        self.governance = GovernanceRegistry::with_params(self.governance.get_params().clone());
+        self.treasury = match self.treasury.get_authority_key() {
+            Some(authority_key) => Treasury::with_authority_key(authority_key.clone()),
+            None => Treasury::new(),
+        };
+        let blocks = self.blockchain.get_blocks().to_vec();
+        for block in &blocks {
+            self.apply_mined_block(block);
+        }
+    }
+    
+    /// Governance state follows mined blocks only, so tallies match every other
+    /// node. Parameters change when their `GovernanceExecution` is mined.
+    fn apply_mined_block(&mut self, block: &Block) {
+        for proposal_id in self.governance.apply_block(block) {
+            println!("🗳️  Governance proposal {} finalized", proposal_id);
+        }
+        let released = self.treasury.apply_block(block, &self.governance, &self.did_registry);
+        self.governance.apply_treasury_releases(block, &released);
+        self.blockchain.record_released_disbursements(&released);
+        
+        let height = block.index as u64;
+        let changes: Vec<ParameterChangeRecord> = self.governance
+            .get_parameter_changes()
+            .iter()
+            .filter(|change| change.block_height == height)
+            .cloned()
+            .collect();
+        for change in changes {
+            match self.apply_governance_action(&change.applied) {
+                Ok(()) => match &change.previous {
+                    Some(previous) => println!("⚙️  Proposal {} executed: {} (was {})", change.proposal_id, change.applied, previous),
+                    None => println!("⚙️  Proposal {} executed: {}", change.proposal_id, change.applied),
+                },
+                Err(e) => println!("❌ Error executing proposal {}: {}", change.proposal_id, e),
+            }
+        }
+    }
+    
+    /// Queues a `GovernanceExecution` recording the before and after values for
+    /// each passed proposal whose timelock has elapsed.
+    fn execute_due_governance_actions(&mut self) {
+        let height = self.blockchain.get_chain_length().saturating_sub(1) as u64;
+        let due: Vec<(String, GovernanceAction)> = self.governance
+            .pending_executions(height)
+            .into_iter()
+            .filter_map(|proposal| proposal.action.clone().map(|action| (proposal.id.clone(), action)))
+            .collect();
+        
+        for (proposal_id, action) in due {
+            let already_queued = self.blockchain.get_pending_transactions().iter().any(|tx| {
+                matches!(&tx.transaction_type, TransactionType::GovernanceExecution { proposal_id: queued, .. } if *queued == proposal_id)
+            });
+            if already_queued {
+                continue;
+            }
+            
+            match self.current_governance_value(&action) {
+                Ok(previous) => {
+                    println!("⏳ Proposal {} queued for execution: {}", proposal_id, action);
+                    let mut execution_tx = Transaction::new(
+                        governance::GOVERNANCE_AUTHORITY.to_string(),
+                        TransactionType::GovernanceExecution {
+                            proposal_id,
+                            previous,
+                            applied: action,
+                        },
+                    );
+                    match execution_tx.sign(&self.governance_authority_key) {
+                        Ok(()) => self.blockchain.add_transaction(execution_tx),
+                        Err(e) => println!("❌ Error signing governance execution {}: {}", execution_tx.id, e),
+                    }
+                }
+                Err(e) => println!("❌ Error executing proposal {}: {}", proposal_id, e),
+            }
+        }
+    }
+    
+    /// Checks that an action can be applied and returns the action that would
+    /// restore the current value. Treasury actions replace no value.
+    fn current_governance_value(&self, action: &GovernanceAction) -> Result<Option<GovernanceAction>, String> {
+        action.validate()?;
+        
+        let previous = match action {
+            GovernanceAction::SetMiningDifficulty { .. } => {
+                GovernanceAction::SetMiningDifficulty { difficulty: self.blockchain.get_difficulty() }
+            }
+            GovernanceAction::SetMiningReward { .. } => {
+                GovernanceAction::SetMiningReward { reward: self.blockchain.get_mining_reward() }
+            }
+            GovernanceAction::SetCommissionRate { .. } => {
+                GovernanceAction::SetCommissionRate { rate: self.wallet_integration.get_commission_rate() }
+            }
+            GovernanceAction::SetWalletEnabled { .. } => {
+                GovernanceAction::SetWalletEnabled { enabled: self.wallet_integration.is_enabled() }
+            }
+            GovernanceAction::SetCarbonCreditRate { currency, .. } => {
+                // A currency without a rate is priced at the USD fallback
+                let current = self.carbon_credit_rates.get(currency).copied().unwrap_or(0.15);
+                GovernanceAction::SetCarbonCreditRate { currency: currency.clone(), rate_per_kg: current }
+            }
+            GovernanceAction::SetSectorRequirements { sector, .. } => {
+                let config = self.privacy_manager
+                    .get_sector_config(sector)
+                    .ok_or_else(|| format!("No configuration for sector {:?}", sector))?;
+                GovernanceAction::SetSectorRequirements {
+                    sector: sector.clone(),
+                    required_verification_level: config.required_verification_level.clone(),
+                    government_oversight: config.audit_requirements.government_oversight,
+                    transparency_level: config.audit_requirements.transparency_level,
+                }
+            }
+            GovernanceAction::SetCreditAwardThreshold { .. } => {
+                GovernanceAction::SetCreditAwardThreshold { threshold: self.credit_award_threshold }
+            }
+            GovernanceAction::SetTreasuryShare { .. } => {
+                GovernanceAction::SetTreasuryShare { share: self.blockchain.get_treasury_share() }
+            }
+            GovernanceAction::FundGrant { terms } => {
+                self.treasury.check_grant_funding(terms)?;
+                // Grants queued earlier but not yet mined are already spoken for
+                let queued: f64 = self.blockchain
+                    .get_pending_transactions()
+                    .iter()
+                    .filter_map(|tx| match &tx.transaction_type {
+                        TransactionType::GovernanceExecution { applied: GovernanceAction::FundGrant { terms }, .. } => Some(terms.total_amount()),
+                        _ => None,
+                    })
+                    .fold(0.0, |total, amount| total + amount);
+                if queued + terms.total_amount() > self.treasury.available_balance() {
+                    return Err(format!("Treasury cannot cover grant {} alongside queued grants", terms.grant_id));
+                }
+                return Ok(None);
+            }
+            GovernanceAction::ClawbackGrant { grant_id, .. } => {
+                self.treasury.check_clawback(grant_id)?;
+                return Ok(None);
+            }
+        };
+        
+        Ok(Some(previous))
+    }
+    
+    /// Applies a mined parameter change. Treasury actions are applied by the
+    /// treasury itself when their record is mined.
+    fn apply_governance_action(&mut self, action: &GovernanceAction) -> Result<(), String> {
+        match action {
+            GovernanceAction::SetMiningDifficulty { difficulty } => self.blockchain.set_difficulty(*difficulty),
+            GovernanceAction::SetMiningReward { reward } => self.blockchain.set_mining_reward(*reward),
+            GovernanceAction::SetCommissionRate { rate } => self.wallet_integration.set_commission_rate(*rate),
+            GovernanceAction::SetWalletEnabled { enabled } => self.wallet_integration.set_enabled(*enabled),
+            GovernanceAction::SetCarbonCreditRate { currency, rate_per_kg } => {
+                self.carbon_credit_rates.insert(currency.clone(), *rate_per_kg);
+            }
+            GovernanceAction::SetSectorRequirements { sector, required_verification_level, government_oversight, transparency_level } => {
+                self.privacy_manager.update_sector_requirements(
+                    sector,
+                    required_verification_level.clone(),
+                    *government_oversight,
+                    *transparency_level,
+                )?;
+            }
+            GovernanceAction::SetCreditAwardThreshold { threshold } => self.credit_award_threshold = *threshold,
+            GovernanceAction::SetTreasuryShare { share } => self.blockchain.set_treasury_share(*share),
+            GovernanceAction::FundGrant { .. } | GovernanceAction::ClawbackGrant { .. } => {}
+        }
+        Ok(())
     }
     
     pub fn get_blockchain_stats(&self) -> BlockchainStats {
@@ -275,17 +866,284 @@ let mut service = LightChainIntegrationService {
     
     // Privacy-related methods
     pub fn create_anonymous_identity(&mut self, sector: SectorType) -> Result<String, String> {
-        self.privacy_manager.create_anonymous_identity(sector)
+        let result = self.privacy_manager.create_anonymous_identity(sector);
+        self.anchor_identity_records();
+        result
+    }
+    
+    /// Registers an identity whose holder keeps the secret for `public_key`.
+    pub fn register_identity_key(&mut self, sector: SectorType, public_key: String) -> Result<String, String> {
+        let result = self.privacy_manager.register_identity_key(sector, public_key);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn register_identity_verifier(&mut self, verifier_did: String, name: String, public_key: String, accredited_levels: Vec<VerificationLevel>) -> Result<(), String> {
+        let result = self.privacy_manager.register_verifier(verifier_did, name, public_key, accredited_levels);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn submit_identity_attestation(&mut self, attestation: IdentityAttestation) -> Result<(), String> {
+        let result = self.privacy_manager.submit_attestation(attestation);
+        self.anchor_identity_records();
+        result
+    }
+    
+    /// Also withdraws the sector of every voter the identity backs.
+    pub fn suspend_identity(&mut self, anonymous_id: &str, reason: String) -> Result<(), String> {
+        let result = self.privacy_manager.suspend_identity(anonymous_id, reason.clone());
+        self.anchor_identity_records();
+        result?;
+        self.withdraw_voter_sectors(anonymous_id, &reason)
+    }
+    
+    /// Attests the sector again for every voter the identity backs.
+    pub fn reinstate_identity(&mut self, anonymous_id: &str) -> Result<(), String> {
+        let result = self.privacy_manager.reinstate_identity(anonymous_id);
+        self.anchor_identity_records();
+        result?;
+        for voter in self.voters_backed_by(anonymous_id) {
+            self.assign_voter_sector(&voter, anonymous_id)?;
+        }
+        Ok(())
+    }
+    
+    /// Also withdraws the sector of every voter the identity backs, for good.
+    pub fn revoke_identity(&mut self, anonymous_id: &str, reason: String) -> Result<(), String> {
+        let result = self.privacy_manager.revoke_identity(anonymous_id, reason.clone());
+        self.anchor_identity_records();
+        result?;
+        self.withdraw_voter_sectors(anonymous_id, &reason)?;
+        self.voter_identities.retain(|_, backing| backing != anonymous_id);
+        Ok(())
+    }
+    
+    fn voters_backed_by(&self, anonymous_id: &str) -> Vec<String> {
+        let mut voters: Vec<String> = self.voter_identities
+            .iter()
+            .filter(|(_, backing)| *backing == anonymous_id)
+            .map(|(voter, _)| voter.clone())
+            .collect();
+        voters.sort();
+        voters
+    }
+    
+    /// Queues a sector revocation for each voter the identity backs, so they drop
+    /// out of sector-scoped proposals created from the next block on.
+    fn withdraw_voter_sectors(&mut self, anonymous_id: &str, reason: &str) -> Result<(), String> {
+        for voter in self.voters_backed_by(anonymous_id) {
+            let mut revocation_tx = Transaction::new(
+                governance::GOVERNANCE_AUTHORITY.to_string(),
+                TransactionType::GovernanceSectorRevocation {
+                    voter: voter.clone(),
+                    reason: reason.to_string(),
+                },
+            );
+            revocation_tx.sign(&self.governance_authority_key)?;
+            self.submit_governance_transaction(revocation_tx)?;
+            println!("🚷 Sector of voter {} withdrawn: {}", voter, reason);
+        }
+        Ok(())
+    }
+    
+    /// Queues records made by the privacy manager so other nodes can replay them with `PrivacyManager::from_chain`.
+    fn anchor_identity_records(&mut self) {
+        for tx in self.privacy_manager.take_unanchored_records() {
+            self.blockchain.add_transaction(tx);
+        }
     }
     
     pub fn generate_privacy_proof(&mut self, transaction_hash: String, anonymous_id: String) -> Result<String, String> {
         self.privacy_manager.generate_zero_knowledge_proof(transaction_hash, anonymous_id)
     }
     
+    pub fn generate_confidential_proof(&mut self, transaction_hash: String, anonymous_id: String, amount: f64) -> Result<(String, PedersenOpening), String> {
+        self.privacy_manager.generate_confidential_proof(transaction_hash, anonymous_id, amount)
+    }
+    
     pub fn verify_privacy_proof(&self, proof_id: &str) -> bool {
         self.privacy_manager.verify_privacy_proof(proof_id)
     }
     
+    pub fn get_privacy_proof(&self, proof_id: &str) -> Option<&PrivacyProof> {
+        self.privacy_manager.get_privacy_proof(proof_id)
+    }
+    
+    pub fn set_regulator_view_key(&mut self, key_id: String, public_key: String) -> Result<(), String> {
+        let result = self.privacy_manager.set_regulator_view_key(key_id, public_key);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn get_regulator_view_key(&self) -> Option<&RegulatorViewKey> {
+        self.privacy_manager.get_regulator_view_key()
+    }
+    
+    /// Queues a record its author sealed with `privacy_manager::seal_confidential_record`.
+    pub fn submit_confidential_record(&mut self, tx: Transaction) -> Result<String, String> {
+        let result = self.privacy_manager.submit_confidential_record(tx);
+        self.anchor_identity_records();
+        result
+    }
+    
+    /// Decrypts a confidential record with the regulator view key; the access is recorded on chain.
+    pub fn audit_confidential_record(&mut self, record_tx_id: &str, auditor: &str, view_key_secret: &str, purpose: String) -> Result<AuditedRecord, String> {
+        let tx = self.find_transaction(record_tx_id)
+            .ok_or_else(|| format!("Record {} not found", record_tx_id))?;
+        let result = self.privacy_manager.audit_confidential_record(&tx, auditor, view_key_secret, purpose);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn get_view_key_accesses(&self) -> &[ViewKeyAccessEvent] {
+        self.privacy_manager.get_view_key_accesses()
+    }
+    
+    /// Builds the public view of a transaction under the sector's transparency level.
+    /// The opening that proves the view is kept encrypted until its retention ends.
+    pub fn publish_public_view(&mut self, tx_id: &str, sector: SectorType) -> Result<RedactedView, String> {
+        let tx = self.find_transaction(tx_id)
+            .ok_or_else(|| format!("Transaction {} not found", tx_id))?;
+        let transparency_level = self.privacy_manager
+            .get_sector_config(&sector)
+            .map(|config| config.audit_requirements.transparency_level)
+            .ok_or_else(|| format!("No configuration for sector {:?}", sector))?;
+        
+        let (view, opening) = disclosure::redact(&tx, sector.clone(), transparency_level);
+        self.privacy_manager.store_personal_record(
+            &format!("disclosure_{}", tx_id),
+            &tx.from,
+            sector,
+            RecordCategory::DisclosureOpening,
+            &opening,
+            Self::current_timestamp(),
+        )?;
+        Ok(view)
+    }
+    
+    /// Hands out the salts proving a published view, e.g. to an auditor or a court.
+    /// The access is recorded on chain.
+    pub fn get_disclosure_opening(&mut self, tx_id: &str, accessor: &str, purpose: String) -> Result<DisclosureOpening, String> {
+        let result = self.privacy_manager.access_personal_record(&format!("disclosure_{}", tx_id), accessor, purpose);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn get_data_accesses(&self) -> &[DataAccessEvent] {
+        self.privacy_manager.get_data_accesses()
+    }
+    
+    pub fn grant_consent(&mut self, anonymous_id: &str, purpose: &str) -> Result<(), String> {
+        let result = self.privacy_manager.grant_consent(anonymous_id, purpose);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn withdraw_consent(&mut self, anonymous_id: &str, purpose: &str) -> Result<(), String> {
+        let result = self.privacy_manager.withdraw_consent(anonymous_id, purpose);
+        self.anchor_identity_records();
+        result
+    }
+    
+    /// Records a consent change proved by an identity that holds its own secret.
+    pub fn submit_consent_change(&mut self, anonymous_id: &str, purpose: &str, granted: bool, subject_proof: SchnorrProof) -> Result<(), String> {
+        let result = self.privacy_manager.submit_consent_change(anonymous_id, purpose, granted, subject_proof);
+        self.anchor_identity_records();
+        result
+    }
+    
+    /// Deletes all off-chain data and any merchant profile held about a subject,
+    /// named by anonymous id or DID, and returns the deletion receipts.
+    pub fn erase_subject_data(&mut self, subject: &str) -> Vec<DeletionReceipt> {
+        self.merchant_profiles.remove(subject);
+        self.privacy_manager.erase_subject_data(subject, Self::current_timestamp())
+    }
+    
+    // Data Privacy Act (RA 10173) methods
+    /// Files a request the subject authorized over
+    /// `privacy_manager::data_subject_request_statement`.
+    pub fn file_data_subject_request(&mut self, request_id: &str, subject: &str, right: DataSubjectRight, authorization: SubjectAuthorization) -> Result<(), String> {
+        let result = self.privacy_manager.file_data_subject_request(request_id, subject, right, authorization, &self.did_registry);
+        self.anchor_identity_records();
+        result
+    }
+    
+    /// Exports everything held about the requester, including a merchant profile
+    /// stored under their DID.
+    pub fn fulfill_access_request(&mut self, request_id: &str) -> Result<SubjectAccessExport, String> {
+        let profiles = self.privacy_manager
+            .get_data_subject_request(request_id)
+            .and_then(|request| self.merchant_profiles.get(&request.subject))
+            .and_then(|profile| serde_json::to_value(profile).ok())
+            .into_iter()
+            .collect();
+        let result = self.privacy_manager.fulfill_access_request(request_id, profiles, Self::current_timestamp());
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn fulfill_erasure_request(&mut self, request_id: &str) -> Result<Vec<DeletionReceipt>, String> {
+        let result = self.privacy_manager.fulfill_erasure_request(request_id, Self::current_timestamp());
+        if result.is_ok() {
+            if let Some(request) = self.privacy_manager.get_data_subject_request(request_id) {
+                self.merchant_profiles.remove(&request.subject);
+            }
+        }
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn deny_data_subject_request(&mut self, request_id: &str, reason: String) -> Result<(), String> {
+        let result = self.privacy_manager.deny_data_subject_request(request_id, reason);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn report_breach(
+        &mut self,
+        description: String,
+        affected_sectors: Vec<SectorType>,
+        affected_subjects: usize,
+        sensitive_personal_information: bool,
+        discovered_at: u64,
+    ) -> Result<String, String> {
+        let result = self.privacy_manager.report_breach(description, affected_sectors, affected_subjects, sensitive_personal_information, discovered_at);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn record_breach_notification(&mut self, breach_id: &str, audience: BreachAudience) -> Result<(), String> {
+        let result = self.privacy_manager.record_breach_notification(breach_id, audience);
+        self.anchor_identity_records();
+        result
+    }
+    
+    pub fn get_npc_report(&self, personal_information_controller: &str, data_protection_officer: &str, period_start: u64, period_end: u64) -> NpcReport {
+        self.privacy_manager.npc_report(personal_information_controller, data_protection_officer, period_start, period_end, Self::current_timestamp())
+    }
+    
+    pub fn get_compliance_report(&self) -> ComplianceReport {
+        self.privacy_manager.compliance_report(Self::current_timestamp())
+    }
+    
+    fn find_transaction(&self, tx_id: &str) -> Option<Transaction> {
+        self.blockchain
+            .get_all_transactions()
+            .into_iter()
+            .chain(self.blockchain.get_pending_transactions().iter())
+            .find(|tx| tx.id == tx_id)
+            .cloned()
+    }
+    
+    pub fn enforce_data_retention(&mut self) -> Vec<DeletionReceipt> {
+        self.privacy_manager.enforce_retention(Self::current_timestamp())
+    }
+    
+    pub fn get_retention_report(&self) -> RetentionReport {
+        self.privacy_manager.retention_report(Self::current_timestamp())
+    }
+    
     pub fn get_privacy_summary(&self) -> crate::privacy_manager::PrivacySummary {
         self.privacy_manager.get_privacy_summary()
     }
@@ -293,6 +1151,605 @@ let mut service = LightChainIntegrationService {
     pub fn is_identity_compliant(&self, anonymous_id: &str, sector: &SectorType) -> bool {
         self.privacy_manager.is_compliant_for_sector(anonymous_id, sector)
     }
+    
+    // Governance methods
+    pub fn register_governance_voter(&mut self, voter: &str, public_key: &str) -> Result<String, String> {
+        let mut registration_tx = Transaction::new(
+            governance::GOVERNANCE_AUTHORITY.to_string(),
+            TransactionType::GovernanceVoterRegistration {
+                voter: voter.to_string(),
+                public_key: public_key.to_string(),
+            },
+        );
+        registration_tx.sign(&self.governance_authority_key)?;
+        
+        self.submit_governance_transaction(registration_tx)
+    }
+    
+    /// Registers an anonymous identity as a voter so its ballots cannot be linked
+    /// to the person behind it. The identity must be active, meet its sector's
+    /// requirements, and prove with `identity_proof` that it chose `public_key`.
+    pub fn register_anonymous_voter(&mut self, anonymous_id: &str, public_key: &str, identity_proof: &SchnorrProof) -> Result<String, String> {
+        let identity = self.privacy_manager
+            .get_anonymous_identity(anonymous_id)
+            .ok_or_else(|| format!("Anonymous identity {} not found", anonymous_id))?;
+        if !identity.is_active() {
+            return Err(format!("Identity {} is {:?}", anonymous_id, identity.status));
+        }
+        if !zk_proofs::verify_knowledge(&identity.public_key, &identity_proof_message(public_key, anonymous_id), identity_proof) {
+            return Err(format!("Identity proof for {} does not verify", anonymous_id));
+        }
+        if !self.privacy_manager.is_compliant_for_sector(anonymous_id, &identity.sector) {
+            return Err(format!("Identity {} does not meet the {:?} sector requirements", anonymous_id, identity.sector));
+        }
+        
+        let tx_id = self.register_governance_voter(anonymous_id, public_key)?;
+        self.assign_voter_sector(anonymous_id, anonymous_id)?;
+        Ok(tx_id)
+    }
+    
+    /// Attests a voter's sector from a compliant anonymous identity so they can
+    /// take part in sector-scoped proposals.
+    pub fn assign_voter_sector(&mut self, voter: &str, anonymous_id: &str) -> Result<String, String> {
+        let identity = self.privacy_manager
+            .get_anonymous_identity(anonymous_id)
+            .ok_or_else(|| format!("Anonymous identity {} not found", anonymous_id))?;
+        let sector = identity.sector.clone();
+        if !self.privacy_manager.is_compliant_for_sector(anonymous_id, &sector) {
+            return Err(format!("Identity {} does not meet the {:?} sector requirements", anonymous_id, sector));
+        }
+        
+        let mut assignment_tx = Transaction::new(
+            governance::GOVERNANCE_AUTHORITY.to_string(),
+            TransactionType::GovernanceSectorAssignment {
+                voter: voter.to_string(),
+                sector,
+            },
+        );
+        assignment_tx.sign(&self.governance_authority_key)?;
+        let tx_id = self.submit_governance_transaction(assignment_tx)?;
+        self.voter_identities.insert(voter.to_string(), anonymous_id.to_string());
+        Ok(tx_id)
+    }
+    
+    /// Records a reputation weight from the merchant's valid certifications and
+    /// the average sustainability score of their mined sales.
+    pub fn assign_merchant_reputation(&mut self, merchant_did: &str) -> Result<f64, String> {
+        let verified_certifications = self.certification_registry
+            .verified_certifications(merchant_did, Self::current_timestamp())
+            .len();
+        let scores: Vec<f64> = self.blockchain
+            .get_all_transactions()
+            .into_iter()
+            .filter_map(|tx| tx.get_marketplace_details())
+            .filter(|details| details.seller_did == merchant_did)
+            .filter_map(|details| details.sustainability_score)
+            .collect();
+        let sustainability_score = if scores.is_empty() {
+            0.0
+        } else {
+            scores.iter().sum::<f64>() / scores.len() as f64
+        };
+        
+        let weight = governance::merchant_reputation_weight(verified_certifications, sustainability_score);
+        self.submit_reputation_update(merchant_did, weight, ReputationBasis::MerchantRecord {
+            verified_certifications,
+            sustainability_score,
+        })?;
+        Ok(weight)
+    }
+    
+    /// Records a reputation weight from the sector of a compliant anonymous identity.
+    pub fn assign_sector_reputation(&mut self, voter: &str, anonymous_id: &str) -> Result<f64, String> {
+        let identity = self.privacy_manager
+            .get_anonymous_identity(anonymous_id)
+            .ok_or_else(|| format!("Anonymous identity {} not found", anonymous_id))?;
+        let sector = identity.sector.clone();
+        if !self.privacy_manager.is_compliant_for_sector(anonymous_id, &sector) {
+            return Err(format!("Identity {} does not meet the {:?} sector requirements", anonymous_id, sector));
+        }
+        
+        let weight = governance::sector_reputation_weight(&sector);
+        self.submit_reputation_update(voter, weight, ReputationBasis::Sector { sector })?;
+        Ok(weight)
+    }
+    
+    fn submit_reputation_update(&mut self, voter: &str, weight: f64, basis: ReputationBasis) -> Result<String, String> {
+        let mut update_tx = Transaction::new(
+            governance::GOVERNANCE_AUTHORITY.to_string(),
+            TransactionType::GovernanceReputationUpdate {
+                voter: voter.to_string(),
+                weight,
+                basis,
+            },
+        );
+        update_tx.sign(&self.governance_authority_key)?;
+        
+        self.submit_governance_transaction(update_tx)
+    }
+    
+    /// Locks tokens as validator stake once the next block is mined.
+    pub fn stake_tokens(&mut self, address: &str, amount: f64) -> Result<(), String> {
+        self.blockchain.add_validator(address.to_string(), amount)?;
+        println!("🔒 Queued stake of {:.2} for {}", amount, address);
+        Ok(())
+    }
+    
+    /// Accepts a signed proposal or vote into the pending pool if it would be valid
+    /// in the next block. It only counts once mined.
+    pub fn submit_governance_transaction(&mut self, tx: Transaction) -> Result<String, String> {
+        if !matches!(
+            tx.transaction_type,
+            TransactionType::GovernanceVote { .. }
+                | TransactionType::GovernanceProposalCreation { .. }
+                | TransactionType::GovernanceVoterRegistration { .. }
+                | TransactionType::GovernanceReputationUpdate { .. }
+                | TransactionType::GovernanceSectorAssignment { .. }
+                | TransactionType::GovernanceSectorRevocation { .. }
+                | TransactionType::GovernanceVoteCommitment { .. }
+                | TransactionType::GovernanceVoteReveal { .. }
+                | TransactionType::GovernanceDelegation { .. }
+                | TransactionType::GovernanceUndelegation { .. }
+        ) {
+            return Err(format!("Transaction {} is not a governance transaction", tx.id));
+        }
+        
+        if self.blockchain.get_pending_transactions().iter().any(|pending| pending.id == tx.id) {
+            return Err(format!("Governance transaction {} is already pending", tx.id));
+        }
+        let next_height = self.blockchain.get_chain_length() as u64;
+        self.governance.validate_transaction(&tx, next_height, Self::current_timestamp())?;
+        self.check_sector_compliance(&tx)?;
+        
+        let tx_id = tx.id.clone();
+        self.blockchain.add_transaction(tx);
+        Ok(tx_id)
+    }
+    
+    /// Re-checks the identity behind a ballot on a sector-scoped proposal, since
+    /// an identity can fall out of compliance after its sector was attested.
+    fn check_sector_compliance(&self, tx: &Transaction) -> Result<(), String> {
+        let proposal_id = match &tx.transaction_type {
+            TransactionType::GovernanceVote { proposal_id, .. }
+            | TransactionType::GovernanceVoteCommitment { proposal_id, .. }
+            | TransactionType::GovernanceVoteReveal { proposal_id, .. } => proposal_id,
+            _ => return Ok(()),
+        };
+        let scoped = self.governance
+            .get_proposal(proposal_id)
+            .is_some_and(|proposal| proposal.sector_scope.is_restricted());
+        if !scoped {
+            return Ok(());
+        }
+        
+        let sector = self.governance
+            .get_voter_sector(&tx.from)
+            .ok_or_else(|| format!("{} has no attested sector", tx.from))?;
+        let anonymous_id = self.voter_identities
+            .get(&tx.from)
+            .ok_or_else(|| format!("{} has no anonymous identity on record", tx.from))?;
+        if !self.privacy_manager.is_compliant_for_sector(anonymous_id, sector) {
+            return Err(format!("{} no longer meets the {:?} sector requirements", tx.from, sector));
+        }
+        Ok(())
+    }
+    
+    pub fn get_governance_proposal(&self, proposal_id: &str) -> Option<&GovernanceProposal> {
+        self.governance.get_proposal(proposal_id)
+    }
+    
+    pub fn get_vote_tally(&self, proposal_id: &str) -> Option<VoteTally> {
+        self.governance.tally(proposal_id)
+    }
+    
+    pub fn get_governance(&self) -> &GovernanceRegistry {
+        &self.governance
+    }
+    
+    /// Queues the next tranche of a grant, citing mined `EnvironmentalImpact`
+    /// evidence from the project that meets the tranche's milestone.
+    pub fn claim_grant_tranche(&mut self, grant_id: &str, evidence_tx_ids: Vec<String>) -> Result<String, String> {
+        let already_queued = self.blockchain.get_pending_transactions().iter().any(|tx| {
+            matches!(&tx.transaction_type, TransactionType::TreasuryDisbursement { grant_id: queued, .. } if queued == grant_id)
+        });
+        if already_queued {
+            return Err(format!("A tranche of grant {} is already awaiting mining", grant_id));
+        }
+        
+        let grant = self.treasury.get_grant(grant_id).ok_or_else(|| format!("Grant {} not found", grant_id))?;
+        let tranche_index = grant.released_tranches;
+        let tranche = grant.terms.tranches.get(tranche_index)
+            .ok_or_else(|| format!("Grant {} has no tranches left", grant_id))?;
+        let amount = tranche.amount;
+        let recipient = grant.terms.recipient.clone();
+        self.treasury.validate_disbursement(treasury::TREASURY_ADDRESS, grant_id, tranche_index, amount, &recipient, &evidence_tx_ids)?;
+        
+        let mut disbursement_tx = Transaction::new(
+            treasury::TREASURY_ADDRESS.to_string(),
+            TransactionType::TreasuryDisbursement {
+                grant_id: grant_id.to_string(),
+                tranche_index,
+                amount,
+                recipient: recipient.clone(),
+                evidence_tx_ids,
+            },
+        );
+        disbursement_tx.sign(&self.treasury_key)?;
+        let tx_id = disbursement_tx.id.clone();
+        self.blockchain.add_transaction(disbursement_tx);
+        
+        println!("💸 Tranche {} of grant {} ({:.2}) queued for {}", tranche_index + 1, grant_id, amount, recipient);
+        Ok(tx_id)
+    }
+    
+    pub fn get_treasury(&self) -> &Treasury {
+        &self.treasury
+    }
+    
+    pub fn get_treasury_report(&self) -> TreasuryReport {
+        self.treasury.report(0, self.blockchain.get_chain_length() as u64)
+    }
+    
+    pub fn get_credit_award_threshold(&self) -> f64 {
+        self.credit_award_threshold
+    }
+    
+    pub fn get_carbon_credit_rate(&self, currency: &str) -> Option<f64> {
+        self.carbon_credit_rates.get(currency).copied()
+    }
+    
+    pub fn get_wallet_integration(&self) -> &WalletIntegration {
+        &self.wallet_integration
+    }
+    
+    pub fn get_wallet_integration_mut(&mut self) -> &mut WalletIntegration {
+        &mut self.wallet_integration
+    }
+    
+    // Certification-related methods
+    /// Registers a certification body as the certification registry and queues the record.
+    pub fn register_certifier(&mut self, certifier_did: String, name: String, public_key: String, accredited_certifications: Vec<String>) -> Result<String, String> {
+        let registration_tx = Transaction::new(
+            certification::CERTIFICATION_AUTHORITY.to_string(),
+            TransactionType::CertifierRegistration {
+                certifier_did: certifier_did.clone(),
+                name,
+                public_key,
+                accredited_certifications,
+            },
+        );
+        let tx_id = self.submit_certification_record(registration_tx)?;
+        println!("🏛️  Certifier {} registered: {}", certifier_did, tx_id);
+        Ok(tx_id)
+    }
+    
+    /// Stops a certifier's credentials from verifying and queues the record.
+    pub fn deactivate_certifier(&mut self, certifier_did: &str) -> Result<String, String> {
+        let deactivation_tx = Transaction::new(
+            certification::CERTIFICATION_AUTHORITY.to_string(),
+            TransactionType::CertifierDeactivation {
+                certifier_did: certifier_did.to_string(),
+            },
+        );
+        let tx_id = self.submit_certification_record(deactivation_tx)?;
+        println!("🏛️  Certifier {} deactivated: {}", certifier_did, tx_id);
+        Ok(tx_id)
+    }
+    
+    fn submit_certification_record(&mut self, mut tx: Transaction) -> Result<String, String> {
+        tx.sign(&self.certification_registry_key)?;
+        self.certification_registry.apply_transaction(&tx)?;
+        let tx_id = tx.id.clone();
+        self.blockchain.add_transaction(tx);
+        Ok(tx_id)
+    }
+    
+    /// Verifies an eco-certification and records it as a `CredentialVerification`
+    /// transaction. Failed checks are reported as an error and not recorded.
+    pub fn submit_eco_certification(&mut self, credential: EcoCertificationCredential) -> Result<String, String> {
+        let now = Self::current_timestamp();
+        let status = self.certification_registry.check_credential(&credential, now);
+        let report = CredentialVerificationReport::for_certification(&credential, &status, now);
+        self.certification_registry.submit_credential(credential, now)?;
+        
+        let verification_tx = Transaction::new_credential_verification(
+            certification::CERTIFICATION_AUTHORITY.to_string(),
+            &report,
+        );
+        let verification_tx_id = verification_tx.id.clone();
+        self.blockchain.add_transaction(verification_tx);
+        
+        println!("🏅 Eco-certification verified: {}", verification_tx_id);
+        Ok(verification_tx_id)
+    }
+    
+    /// Revokes a certification and records a failing `CredentialVerification` for it.
+    pub fn revoke_eco_certification(&mut self, credential_id: &str, certifier_did: &str, reason: String) -> Result<String, String> {
+        let now = Self::current_timestamp();
+        let credential = self.certification_registry
+            .revoke_credential(credential_id, certifier_did, reason, now)?
+            .clone();
+        let report = CredentialVerificationReport::for_certification(&credential, &self.certification_registry.check_credential(&credential, now), now);
+        
+        let revocation_tx = Transaction::new_credential_verification(certifier_did.to_string(), &report);
+        let revocation_tx_id = revocation_tx.id.clone();
+        self.blockchain.add_transaction(revocation_tx);
+        
+        Ok(revocation_tx_id)
+    }
+    
+    // DID methods
+    /// Validates a signed DID creation, update or deactivation against the
+    /// registry and queues it.
+    pub fn submit_did_transaction(&mut self, tx: Transaction) -> Result<String, String> {
+        if !matches!(
+            tx.transaction_type,
+            TransactionType::DidCreation { .. } | TransactionType::DidUpdate { .. } | TransactionType::DidDeactivation { .. }
+        ) {
+            return Err(format!("Transaction {} is not a DID transaction", tx.id));
+        }
+        
+        self.did_registry.apply_transaction(&tx)?;
+        println!("🪪 {} recorded: {}", tx.get_type_name(), tx.id);
+        let tx_id = tx.id.clone();
+        self.blockchain.add_transaction(tx);
+        Ok(tx_id)
+    }
+    
+    pub fn resolve_did(&self, did: &str) -> DidResolutionResult {
+        self.did_registry.resolve(did)
+    }
+    
+    /// Checks a statement signed by a buyer, seller or product DID against its
+    /// resolved assertion keys. Returns the id of the key that signed.
+    pub fn verify_did_signature(&self, did: &str, message: &[u8], signature: &str) -> Result<String, String> {
+        self.did_registry.verify_signature(did, VerificationRelationship::AssertionMethod, message, signature)
+    }
+    
+    /// Checks that a transaction is signed by an authentication key of its sender's DID.
+    pub fn verify_transaction_signer(&self, tx: &Transaction) -> Result<String, String> {
+        self.did_registry.verify_transaction(tx)
+    }
+    
+    pub fn get_did_registry(&self) -> &DidRegistry {
+        &self.did_registry
+    }
+    
+    // Verifiable credential methods
+    /// Queues a status list publication signed by an authentication key of its issuer's DID.
+    pub fn publish_status_list(&mut self, tx: Transaction) -> Result<String, String> {
+        if !matches!(tx.transaction_type, TransactionType::CredentialStatusListPublication { .. }) {
+            return Err(format!("Transaction {} is not a status list publication", tx.id));
+        }
+        
+        self.did_registry.verify_transaction(&tx)?;
+        self.status_lists.apply_transaction(&tx)?;
+        println!("📋 Status list published: {}", tx.id);
+        let tx_id = tx.id.clone();
+        self.blockchain.add_transaction(tx);
+        Ok(tx_id)
+    }
+    
+    /// Revokes the credential at `index` and republishes the issuer's list.
+    pub fn revoke_verifiable_credential(&mut self, list_id: &str, index: usize, issuer_private_key: &str) -> Result<String, String> {
+        let mut list = self.status_lists
+            .get_list(list_id)
+            .cloned()
+            .ok_or_else(|| format!("Status list {} is not published", list_id))?;
+        list.revoke(index)?;
+        
+        let mut publication_tx = Transaction::new_status_list_publication(list);
+        publication_tx.sign(issuer_private_key)?;
+        self.publish_status_list(publication_tx)
+    }
+    
+    /// Verifies a credential's signature, expiry and revocation status and records
+    /// the result with the credential hash. Failed checks are recorded too, then
+    /// reported as an error.
+    pub fn verify_credential(&mut self, credential: &VerifiableCredential, verifier: &str) -> Result<String, String> {
+        let report = credentials::verify_credential(credential, &self.did_registry, &self.status_lists, Self::current_timestamp());
+        
+        let verification_tx = Transaction::new_credential_verification(verifier.to_string(), &report);
+        let verification_tx_id = verification_tx.id.clone();
+        self.blockchain.add_transaction(verification_tx);
+        
+        match report.failure {
+            Some(failure) => Err(format!("Credential {} failed verification: {}", credential.id, failure)),
+            None => {
+                println!("🎓 Credential verified: {}", verification_tx_id);
+                Ok(verification_tx_id)
+            }
+        }
+    }
+    
+    pub fn get_status_list(&self, list_id: &str) -> Option<&StatusList> {
+        self.status_lists.get_list(list_id)
+    }
+    
+    pub fn get_verified_certifications(&self, subject_did: &str) -> Vec<&EcoCertificationCredential> {
+        self.certification_registry.verified_certifications(subject_did, Self::current_timestamp())
+    }
+    
+    // Carbon credit registry methods
+    pub fn register_carbon_project(&mut self, project: CarbonProject) -> Result<String, String> {
+        let mut registration_tx = Transaction::new(
+            carbon_registry::REGISTRY_AUTHORITY.to_string(),
+            TransactionType::CarbonProjectRegistration {
+                project_id: project.project_id,
+                name: project.name,
+                developer_did: project.developer_did,
+                methodology: project.methodology,
+                location: project.location,
+            },
+        );
+        registration_tx.sign(&self.carbon_registry_key)?;
+        
+        self.submit_carbon_transaction(registration_tx)
+    }
+    
+    /// Issues `quantity` serialized credits (one per kg CO2e) against a reduction claim
+    /// whose evidence is already on chain. Returns the issuance transaction id and
+    /// the allocated serial range.
+    pub fn issue_carbon_credits(&mut self, claim: ReductionClaim, quantity: u64, recipient: &str) -> Result<(String, u64, u64), String> {
+        if quantity == 0 {
+            return Err("Cannot issue zero carbon credits".to_string());
+        }
+        self.verify_claim_evidence(&claim)?;
+        
+        let vintage = claim.vintage;
+        let serial_start = self.carbon_registry.next_serial();
+        let serial_end = serial_start
+            .checked_add(quantity)
+            .and_then(|end| end.checked_sub(1))
+            .ok_or_else(|| format!("Issuing {} credits from serial {} overflows the serial range", quantity, serial_start))?;
+        let mut issuance_tx = Transaction::new(
+            carbon_registry::REGISTRY_AUTHORITY.to_string(),
+            TransactionType::CarbonCreditIssuance {
+                batch_id: format!("batch_{:016x}", rand::random::<u64>()),
+                claim,
+                serial_start,
+                serial_end,
+                recipient: recipient.to_string(),
+            },
+        );
+        issuance_tx.sign(&self.carbon_registry_key)?;
+        
+        let issuance_tx_id = self.submit_carbon_transaction(issuance_tx)?;
+        println!("🌳 Issued carbon credits {}-{} ({} vintage) to {}", serial_start, serial_end, vintage, recipient);
+        
+        Ok((issuance_tx_id, serial_start, serial_end))
+    }
+    
+    /// Moves credits to `recipient`, signed with an authentication key of the holder's DID.
+    pub fn transfer_carbon_credits(&mut self, holder: &str, holder_private_key: &str, serial_start: u64, serial_end: u64, recipient: &str) -> Result<String, String> {
+        let mut transfer_tx = Transaction::new(
+            holder.to_string(),
+            TransactionType::CarbonCreditTransfer {
+                serial_start,
+                serial_end,
+                recipient: recipient.to_string(),
+            },
+        );
+        transfer_tx.sign(holder_private_key)?;
+        
+        self.submit_carbon_transaction(transfer_tx)
+    }
+    
+    /// Permanently retires credits; retired serials can never be transferred again.
+    /// Signed with an authentication key of the holder's DID.
+    pub fn retire_carbon_credits(
+        &mut self,
+        holder: &str,
+        holder_private_key: &str,
+        serial_start: u64,
+        serial_end: u64,
+        beneficiary: &str,
+        reason: String,
+    ) -> Result<RetirementCertificate, String> {
+        let certificate_id = format!("ret_{:016x}", rand::random::<u64>());
+        let mut retirement_tx = Transaction::new(
+            holder.to_string(),
+            TransactionType::CarbonCreditRetirement {
+                certificate_id: certificate_id.clone(),
+                serial_start,
+                serial_end,
+                beneficiary: beneficiary.to_string(),
+                reason,
+                order_id: None,
+            },
+        );
+        retirement_tx.sign(holder_private_key)?;
+        
+        self.submit_carbon_transaction(retirement_tx)?;
+        let certificate = self.carbon_registry.get_retirement_certificate(&certificate_id).cloned().unwrap();
+        println!("♻️  Retired carbon credits {}-{} on behalf of {}", serial_start, serial_end, beneficiary);
+        
+        Ok(certificate)
+    }
+    
+    /// Checks that a claim's evidence transaction exists and actually shows the claimed reduction.
+    fn verify_claim_evidence(&self, claim: &ReductionClaim) -> Result<(), String> {
+        let evidence = self.blockchain.find_transaction(&claim.evidence_tx_id)
+            .ok_or_else(|| format!("Evidence transaction {} not found", claim.evidence_tx_id))?;
+        
+        match (&claim.source, &evidence.transaction_type) {
+            (
+                ReductionSource::MarketplaceSale { order_id, product_did },
+                TransactionType::MarketplaceTransaction { transaction_id, product_did: sold_product, carbon_footprint, .. },
+            ) => {
+                if transaction_id != order_id || sold_product != product_did {
+                    return Err(format!("Evidence {} does not record order {}", evidence.id, order_id));
+                }
+                if carbon_footprint.unwrap_or(0.0) < claim.reduction_kg {
+                    return Err(format!("Order {} does not support a {:.2} kg CO2e claim", order_id, claim.reduction_kg));
+                }
+                Ok(())
+            }
+            (
+                ReductionSource::ProjectMonitoring { .. },
+                TransactionType::EnvironmentalImpact { impact_type, impact_value, measurement_unit, .. },
+            ) => {
+                if impact_type != "carbon_reduction" || measurement_unit != "kg_co2" {
+                    return Err(format!("Evidence {} is not a carbon reduction in kg_co2", evidence.id));
+                }
+                if -impact_value < claim.reduction_kg {
+                    return Err(format!(
+                        "Evidence {} shows {:.2} kg CO2e reduced, less than the {:.2} claimed",
+                        evidence.id, -impact_value, claim.reduction_kg
+                    ));
+                }
+                Ok(())
+            }
+            _ => Err(format!("Evidence {} does not match the claim source", evidence.id)),
+        }
+    }
+    
+    /// Lists credits across the chain that may count the same reduction more than once.
+    pub fn audit_carbon_credits(&self) -> CreditAuditReport {
+        carbon_audit::audit_carbon_credits(&self.blockchain)
+    }
+    
+    pub fn get_order_retirements(&self, order_id: &str) -> Vec<&RetirementCertificate> {
+        self.carbon_registry.get_order_retirements(order_id)
+    }
+    
+    pub fn get_retirement_certificate(&self, certificate_id: &str) -> Option<&RetirementCertificate> {
+        self.carbon_registry.get_retirement_certificate(certificate_id)
+    }
+    
+    pub fn get_serial_history(&self, serial: u64) -> Vec<&OwnershipEvent> {
+        self.carbon_registry.get_serial_history(serial)
+    }
+    
+    pub fn get_carbon_registry(&self) -> &CarbonCreditRegistry {
+        &self.carbon_registry
+    }
+    
+    /// Applies a carbon transaction to the registry before it enters the pending pool,
+    /// so invalid transfers or retirements never reach the chain.
+    fn submit_carbon_transaction(&mut self, tx: Transaction) -> Result<String, String> {
+        let tx_id = tx.id.clone();
+        self.commit_atomically(vec![tx])?;
+        Ok(tx_id)
+    }
+    
+    /// Applies a group of transactions to the registry and adds them to the
+    /// pending pool only if every one of them applies.
+    fn commit_atomically(&mut self, transactions: Vec<Transaction>) -> Result<(), String> {
+        self.carbon_registry.apply_batch(&transactions, &self.did_registry)?;
+        
+        for tx in transactions {
+            self.blockchain.add_transaction(tx);
+        }
+        Ok(())
+    }
+    
+    fn current_timestamp() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,32 @@
+//! LightChain Bantay Panahon core library.
+//!
+//! The demo binary in `main.rs` and the Sideline_Pinas bridge both build on
+//! these modules.
+
+pub mod blockchain;
+pub mod transaction;
+pub mod dedication;
+pub mod block;
+pub mod governance;
+pub mod environment;
+pub mod integration_service;
+pub mod wallet_integration;
+pub mod privacy_manager;
+pub mod ai_module;
+pub mod fraud_detection;
+pub mod model_registry;
+pub mod insights;
+pub mod threat_detection;
+pub mod signing;
+pub mod zk_proofs;
+pub mod retention;
+pub mod confidential;
+pub mod disclosure;
+pub mod compliance;
+pub mod data_privacy_act;
+pub mod did;
+pub mod credentials;
+pub mod certification;
+pub mod carbon_registry;
+pub mod carbon_audit;
+pub mod treasury;
@@ -1,19 +1,16 @@
-mod blockchain;
-mod transaction;
-mod dedication;
-mod block;
-mod governance;
-mod environment;
-mod integration_service;
-mod wallet_integration;
-mod privacy_manager;
-mod ai_module;
-
-use blockchain::Blockchain;
-use transaction::{Transaction, TransactionType};
-use governance::GovernanceProposal;
-use environment::EnvironmentalData;
-use integration_service::{
+use lightchain_bantay_panahon::{compliance, dedication, did, signing, zk_proofs};
+use lightchain_bantay_panahon::credentials::{StatusList, VerifiableCredential};
+use lightchain_bantay_panahon::did::DidDocument;
+use lightchain_bantay_panahon::certification::EcoCertificationCredential;
+use lightchain_bantay_panahon::block::Block;
+use lightchain_bantay_panahon::transaction::{Transaction, TransactionType};
+use lightchain_bantay_panahon::governance::{GovernanceTopic, ProposalType, SectorScope, VoteChoice};
+use lightchain_bantay_panahon::privacy_manager::{self, IdentityAttestation, SectorType, VerificationLevel};
+use lightchain_bantay_panahon::environment::EnvironmentalData;
+use lightchain_bantay_panahon::data_privacy_act::{DataSubjectRight, SubjectAuthorization};
+use lightchain_bantay_panahon::carbon_registry::{CarbonProject, ReductionClaim, ReductionSource};
+use lightchain_bantay_panahon::integration_service::{
+    CARBON_OFFSET_POOL,
     LightChainIntegrationService, 
     SidelineTransaction, 
     ProductSustainabilityData, 
@@ -30,6 +27,14 @@ fn main() {
     // Initialize the integration service
     let mut integration_service = LightChainIntegrationService::new();
     
+    // Load scoring models if any are present; otherwise the AI module uses its fallbacks
+    let model_dir = std::env::var("LIGHTCHAIN_MODEL_DIR").unwrap_or_else(|_| "models".to_string());
+    if std::path::Path::new(&model_dir).is_dir() {
+        if let Err(e) = integration_service.ai_module.load_models(std::path::Path::new(&model_dir)) {
+            println!("❌ Error loading models: {}", e);
+        }
+    }
+    
     println!("🔧 Setting up integration environment...");
     
     // Register sample products with sustainability data
@@ -39,6 +44,7 @@ fn main() {
         sustainability_score: 8.5,
         eco_certification: vec!["Organic Cotton".to_string(), "Fair Trade".to_string()],
         supply_chain_transparency: 9.0,
+        category: "Apparel".to_string(),
     };
     
     let product2 = ProductSustainabilityData {
@@ -47,6 +53,7 @@ fn main() {
         sustainability_score: 9.8,
         eco_certification: vec!["Solar Energy".to_string(), "Renewable".to_string()],
         supply_chain_transparency: 8.5,
+        category: "Energy".to_string(),
     };
     
     integration_service.register_product_sustainability(
@@ -82,16 +89,105 @@ fn main() {
         environmental_certifications: vec![],
     };
     
-    integration_service.register_merchant_profile(
-        "did:veritoken-merchant:mainnet:green-shop-ph".to_string(),
-        green_merchant,
+    for profile in [green_merchant, regular_merchant] {
+        let merchant = profile.merchant_did.clone();
+        if let Err(e) = integration_service.register_merchant_profile(merchant.clone(), profile) {
+            println!("❌ Error registering merchant {}: {}", merchant, e);
+        }
+    }
+    
+    // The green merchant anchors its own DID, then one for a product it controls
+    let merchant_keys = signing::generate_keypair();
+    let merchant_did = "did:veritoken-merchant:mainnet:green-shop-ph";
+    let mut merchant_document = DidDocument::new(merchant_did, &merchant_keys.public_key);
+    merchant_document.add_service(
+        "marketplace",
+        did::MARKETPLACE_SERVICE_TYPE,
+        &format!("https://marketplace.veritoken.org/merchant/{}", merchant_did),
+    );
+    let mut merchant_did_tx = Transaction::new_did_creation(merchant_document);
+    let mut product_did_tx = Transaction::new_did_creation(DidDocument::controlled_by(
+        "did:veritoken-product:mainnet:eco-shirt-001",
+        merchant_did,
+    ));
+    
+    let did_result = merchant_did_tx
+        .sign(&merchant_keys.private_key)
+        .and_then(|_| integration_service.submit_did_transaction(merchant_did_tx))
+        .and_then(|_| product_did_tx.sign(&merchant_keys.private_key))
+        .and_then(|_| integration_service.submit_did_transaction(product_did_tx));
+    if let Err(e) = did_result {
+        println!("❌ Error anchoring DIDs: {}", e);
+    }
+    
+    let resolution = integration_service.resolve_did(merchant_did);
+    if let Some(document) = &resolution.did_document {
+        println!("🪪 Resolved {}: {} key(s), service at {}",
+            document.id,
+            document.verification_method.len(),
+            document.service.first().map(|service| service.service_endpoint.as_str()).unwrap_or("none"));
+    }
+    
+    // A receipt signed by the merchant checks out against its resolved assertion key
+    let receipt = b"green-shop-ph shipped eco-shirt-001 carbon neutral";
+    match signing::sign_message(&merchant_keys.private_key, receipt)
+        .and_then(|signature| integration_service.verify_did_signature(merchant_did, receipt, &signature))
+    {
+        Ok(key_id) => println!("✅ Merchant receipt verified with {}", key_id),
+        Err(e) => println!("❌ Error verifying merchant receipt: {}", e),
+    }
+    
+    // The merchant vouches for the product's origin with a revocable verifiable credential
+    let status_list_id = format!("{}/status/1", merchant_did);
+    let mut origin_credential = VerifiableCredential::new(
+        merchant_did,
+        "ProductOriginCredential",
+        serde_json::json!({
+            "id": "did:veritoken-product:mainnet:eco-shirt-001",
+            "origin": "Baguio City, Benguet",
+            "material": "Organic Cotton",
+        }),
+        Some(365 * 24 * 60 * 60), // 1 year
     );
+    origin_credential.set_status(&status_list_id, 0);
+    let mut status_list_tx = Transaction::new_status_list_publication(StatusList::new(&status_list_id, merchant_did));
+    
+    match origin_credential
+        .sign(&merchant_keys.private_key, &format!("{}#key-1", merchant_did))
+        .and_then(|_| status_list_tx.sign(&merchant_keys.private_key))
+        .and_then(|_| integration_service.publish_status_list(status_list_tx))
+        .and_then(|_| integration_service.verify_credential(&origin_credential, "did:veritoken-verifier:mainnet:sideline-pinas"))
+    {
+        Ok(_) => println!("✅ {} for eco-shirt-001 verified (hash {}…)", origin_credential.primary_type(), &origin_credential.hash()[..16]),
+        Err(e) => println!("❌ Error verifying product credential: {}", e),
+    }
+    
+    // Register a certification body and have it attest the green merchant's ISO 14001
+    let certifier_keys = signing::generate_keypair();
+    if let Err(e) = integration_service.register_certifier(
+        "did:veritoken-certifier:mainnet:bureau-veritas-ph".to_string(),
+        "Bureau Veritas Philippines".to_string(),
+        certifier_keys.public_key.clone(),
+        vec!["ISO 14001".to_string(), "Organic Cotton".to_string()],
+    ) {
+        println!("❌ Error registering certifier: {}", e);
+    }
     
-    integration_service.register_merchant_profile(
-        "did:veritoken-merchant:mainnet:regular-shop-ph".to_string(),
-        regular_merchant,
+    let mut iso_certification = EcoCertificationCredential::new(
+        "ISO 14001".to_string(),
+        "did:veritoken-certifier:mainnet:bureau-veritas-ph".to_string(),
+        "did:veritoken-merchant:mainnet:green-shop-ph".to_string(),
+        3 * 365 * 24 * 60 * 60, // 3 years
     );
     
+    match iso_certification
+        .sign(&certifier_keys.private_key)
+        .and_then(|_| integration_service.submit_eco_certification(iso_certification))
+    {
+        Ok(verification_tx_id) => println!("✅ ISO 14001 certification verified: {}", verification_tx_id),
+        Err(e) => println!("❌ Error verifying certification: {}", e),
+    }
+    
     println!("✅ Integration environment set up successfully\n");
     
     // Simulate Sideline_Pinas marketplace transactions
@@ -143,7 +239,23 @@ fn main() {
     println!("\n📋 Processing transactions through LightChain...");
     
     match integration_service.process_sideline_transaction(tx1.clone()) {
-        Ok(blockchain_tx_id) => println!("✅ Transaction 1 processed: {}", blockchain_tx_id),
+        Ok(blockchain_tx_id) => {
+            println!("✅ Transaction 1 processed: {}", blockchain_tx_id);
+            match integration_service.publish_public_view(&blockchain_tx_id, SectorType::Individual) {
+                Ok(view) => {
+                    let shown: Vec<String> = view.fields
+                        .iter()
+                        .filter_map(|field| match (&field.value, &field.band) {
+                            (Some(value), _) => Some(format!("{}={}", field.name, value)),
+                            (None, Some(band)) => Some(format!("{}~{}", field.name, band)),
+                            _ => None,
+                        })
+                        .collect();
+                    println!("👁️  Public view (Individual): {} of {} fields shown: {}", shown.len(), view.fields.len(), shown.join(", "));
+                }
+                Err(e) => println!("❌ Error publishing public view: {}", e),
+            }
+        }
         Err(e) => println!("❌ Error processing transaction 1: {}", e),
     }
     
@@ -157,17 +269,109 @@ fn main() {
         Err(e) => println!("❌ Error processing transaction 3: {}", e),
     }
     
+    // A merchant buying from itself to farm carbon credits is stopped by fraud screening
+    let self_dealing_tx = SidelineTransaction {
+        transaction_id: "sideline_tx_004".to_string(),
+        buyer_did: "did:veritoken-merchant:mainnet:regular-shop-ph".to_string(),
+        ..tx3.clone()
+    };
+    if let Err(e) = integration_service.process_sideline_transaction(self_dealing_tx) {
+        println!("🚫 {}", e);
+    }
+    
+    // A peer replaying one transaction is rate limited; one announcing a block from the future is banned
+    let mut replayed_tx = Transaction::new_environmental_impact(
+        merchant_did.to_string(),
+        "air_quality".to_string(),
+        12.0,
+        "pm2_5".to_string(),
+        Some("Tarlac, Philippines".to_string()),
+    );
+    if let Err(e) = replayed_tx.sign(&merchant_keys.private_key) {
+        println!("❌ Error signing transaction: {}", e);
+    }
+    for _ in 0..5 {
+        let _ = integration_service.receive_transaction("peer-replayer", replayed_tx.clone());
+    }
+    let latest_block = integration_service.get_blockchain().get_latest_block().cloned();
+    if let Some(latest_block) = latest_block {
+        let future_block = Block::new(latest_block.index + 1, latest_block.hash, Vec::new(), latest_block.timestamp.max(tx3.timestamp) + 24 * 60 * 60);
+        let _ = integration_service.screen_block("peer-time-traveller", &future_block);
+    }
+    if let Err(e) = integration_service.receive_transaction("peer-time-traveller", replayed_tx) {
+        println!("🚫 {}", e);
+    }
+    
+    // Fund the checkout offset pool from a registered carbon project
+    println!("\n🌳 Funding the carbon offset pool...");
+    let solar_farm = CarbonProject {
+        project_id: "tarlac-solar-farm-001".to_string(),
+        name: "Tarlac Solar Farm".to_string(),
+        developer_did: "did:lightchain:entity:solar_farm_001".to_string(),
+        methodology: "Grid-connected renewable electricity".to_string(),
+        location: "Tarlac, Philippines".to_string(),
+        registered_at: 0,
+    };
+    
+    let monitoring_report = Transaction::new_environmental_impact(
+        "did:lightchain:entity:solar_farm_001".to_string(),
+        "carbon_reduction".to_string(),
+        -500.0,
+        "kg_co2".to_string(),
+        Some("Tarlac, Philippines".to_string()),
+    );
+    let solar_claim = ReductionClaim {
+        project_id: solar_farm.project_id.clone(),
+        vintage: 2025,
+        source: ReductionSource::ProjectMonitoring {
+            period_start: 1735689600, // January 1, 2025
+            period_end: 1751327999,   // June 30, 2025
+        },
+        reduction_kg: 500.0,
+        evidence_tx_id: monitoring_report.id.clone(),
+    };
+    integration_service.get_blockchain_mut().add_transaction(monitoring_report);
+    
+    let pool_funding = integration_service
+        .register_carbon_project(solar_farm)
+        .and_then(|_| integration_service.issue_carbon_credits(solar_claim, 500, CARBON_OFFSET_POOL));
+    if let Err(e) = pool_funding {
+        println!("❌ Error funding offset pool: {}", e);
+    }
+    
+    // A buyer chooses to offset their order at checkout
+    let tx4 = SidelineTransaction {
+        transaction_id: "sideline_tx_004".to_string(),
+        buyer_did: "did:veritoken-customer:mainnet:maria-santos".to_string(),
+        seller_did: "did:veritoken-merchant:mainnet:regular-shop-ph".to_string(),
+        product_did: "did:veritoken-product:mainnet:regular-item-002".to_string(),
+        amount: 120.00,
+        currency: "PHP".to_string(),
+        status: "completed".to_string(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+    
+    if let Ok(quote) = integration_service.quote_carbon_offset(&tx4) {
+        println!("💱 Offset quote for {}: {} credits for {:.2} {}", quote.order_id, quote.credits_required, quote.offset_cost, quote.currency);
+    }
+    
+    match integration_service.process_sideline_transaction_with_offset(tx4) {
+        Ok(offset) => println!("✅ Transaction 4 processed and offset: {:?}", offset.retirement_certificate_ids),
+        Err(e) => println!("❌ Error processing transaction 4: {}", e),
+    }
+    
     // Mine the pending transactions
     println!("\n⛏️  Mining transactions into blocks...");
     dedication::display_mining_dedication();
 integration_service.mine_pending_transactions();
 
 // --- Wallet Integration ---
-// Initialize wallet integration
-let mut wallet_integration = wallet_integration::WalletIntegration::default();
-
-// Process each sideline transaction for commission
+// Process each sideline transaction for commission; the wallet config is governed on chain
 for tx in [tx1, tx2, tx3].iter() {
+    let wallet_integration = integration_service.get_wallet_integration_mut();
     if let Ok(commission_tx) = wallet_integration.process_commission(tx.transaction_id.clone(), tx.amount) {
         let commission_blockchain_tx = wallet_integration.create_commission_blockchain_transaction(&commission_tx);
         println!("🌟 Commission Transaction for {} Processed: {:.2} tokens", commission_tx.original_transaction_id, commission_tx.commission_amount);
@@ -180,7 +384,23 @@ for tx in [tx1, tx2, tx3].iter() {
     // Display blockchain statistics
     println!("\n📊 LightChain x Sideline_Pinas Integration Stats:");
     println!("=============================================");
-        integration_service.ai_module.run_fraud_detection();
+    let fraud_report = integration_service.run_fraud_detection();
+    println!("🔍 Fraud screening: {} sales checked, {} flagged ({} held, {} rejected)",
+        fraud_report.transactions_checked, fraud_report.flagged.len(), fraud_report.held, fraud_report.rejected);
+    println!("🛡️  Threat alerts: {}", integration_service.get_threat_alerts().len());
+    let active_models = integration_service.ai_module.get_active_models();
+    let anomalies = integration_service.ai_module.detect_environmental_anomalies(integration_service.get_blockchain());
+    println!("🧠 Models: {} | Environmental anomalies: {}",
+        if active_models.is_empty() { "deterministic fallback".to_string() } else { active_models.join(", ") },
+        anomalies.len());
+    let insights_end = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() + 1;
+    let analytics = integration_service.generate_insights(insights_end - 30 * 24 * 60 * 60, insights_end);
+    for insight in &analytics.insights {
+        println!("   💡 {}", insight.summary);
+    }
         let stats = integration_service.get_blockchain_stats();
     println!("📦 Total Blocks: {}", stats.total_blocks);
     println!("📋 Total Transactions: {}", stats.total_transactions);
@@ -189,6 +409,23 @@ for tx in [tx1, tx2, tx3].iter() {
     println!("⭐ Average Sustainability Score: {:.1}/10", stats.average_sustainability_score);
     println!("🌱 Total Carbon Footprint: {:.2} kg CO2", stats.total_carbon_footprint);
     
+    let credit_audit = integration_service.audit_carbon_credits();
+    println!("🔎 Suspected Duplicate Credits: {}", credit_audit.suspected_duplicates.len());
+    let treasury_report = integration_service.get_treasury_report();
+    println!("🏦 Treasury: {:.2} deposited, {:.2} available for grants", treasury_report.total_deposits, treasury_report.available);
+    integration_service.enforce_data_retention();
+    let retention_report = integration_service.get_retention_report();
+    let records_held: usize = retention_report.sectors.iter().map(|sector| sector.records_held).sum();
+    println!("🗄️  Retention: {} off-chain records held, {} deletion receipts, log intact: {}",
+        records_held, retention_report.receipt_count, retention_report.receipt_log_intact);
+    let compliance_report = integration_service.get_compliance_report();
+    let compliant_sectors = compliance_report.sectors.iter().filter(|sector| sector.compliant).count();
+    println!("📜 Compliance: {}/{} sectors compliant, {} violations",
+        compliant_sectors, compliance_report.sectors.len(), compliance_report.total_violations);
+    for violation in compliance_report.sectors.iter().flat_map(|sector| &sector.violations) {
+        println!("   ⚠️  {} {:?} ({:?}): {}", violation.standard.label(), violation.rule, violation.sector, violation.detail);
+    }
+    
     // Display blockchain validation
     let blockchain = integration_service.get_blockchain();
     println!("\n🔐 Blockchain Validation:");
@@ -204,19 +441,202 @@ for tx in [tx1, tx2, tx3].iter() {
         println!("🔢 Nonce: {}", latest_block.nonce);
     }
     
-    // Create a governance proposal related to the marketplace
-    let proposal = GovernanceProposal::new(
+    // Register voters, then create and vote on a governance proposal through signed transactions
+    let proposer_keys = signing::generate_keypair();
+    let voter_keys = signing::generate_keypair();
+    let proposer_did = "did:veritoken-merchant:mainnet:green-shop-ph".to_string();
+    let voter_did = "did:veritoken-customer:mainnet:maria-santos".to_string();
+    let lgu_keys = signing::generate_keypair();
+    let lgu_did = "did:veritoken-lgu:mainnet:tarlac-city".to_string();
+    
+    for (did, keys) in [(&proposer_did, &proposer_keys), (&voter_did, &voter_keys), (&lgu_did, &lgu_keys)] {
+        if let Err(e) = integration_service.register_governance_voter(did, &keys.public_key) {
+            println!("❌ Error registering voter {}: {}", did, e);
+        }
+    }
+    
+    // Attest each voter's sector through an anonymous identity verified by an accredited body
+    let verifier_keys = signing::generate_keypair();
+    let verifier_did = "did:veritoken-verifier:mainnet:philsys".to_string();
+    if let Err(e) = integration_service.register_identity_verifier(
+        verifier_did.clone(),
+        "PhilSys National ID Verification".to_string(),
+        verifier_keys.public_key.clone(),
+        vec![VerificationLevel::Basic, VerificationLevel::Enhanced, VerificationLevel::Premium, VerificationLevel::Government],
+    ) {
+        println!("❌ Error registering identity verifier: {}", e);
+    }
+    // The LGU keeps its own identity secret so only it can author its sealed records
+    let lgu_identity_keys = zk_proofs::generate_identity_keypair();
+    let mut lgu_identity = None;
+    for (did, sector, level) in [
+        (&proposer_did, SectorType::Business, VerificationLevel::Enhanced),
+        (&voter_did, SectorType::Individual, VerificationLevel::Basic),
+        (&lgu_did, SectorType::Government, VerificationLevel::Government),
+    ] {
+        let identity = if did == &lgu_did {
+            integration_service.register_identity_key(sector.clone(), lgu_identity_keys.public_key.clone())
+        } else {
+            integration_service.create_anonymous_identity(sector.clone())
+        };
+        let attested = identity
+            .and_then(|anonymous_id| {
+                if did == &lgu_did {
+                    let statement = privacy_manager::consent_statement(compliance::PROCESSING_CONSENT, true, 0);
+                    let subject_proof = zk_proofs::prove_knowledge(
+                        &lgu_identity_keys.secret,
+                        &privacy_manager::identity_proof_message(&statement, &anonymous_id),
+                    )?;
+                    integration_service.submit_consent_change(&anonymous_id, compliance::PROCESSING_CONSENT, true, subject_proof)?;
+                } else {
+                    integration_service.grant_consent(&anonymous_id, compliance::PROCESSING_CONSENT)?;
+                }
+                let mut attestation = IdentityAttestation::new(anonymous_id.clone(), verifier_did.clone(), level);
+                attestation.sign(&verifier_keys.private_key)?;
+                integration_service.submit_identity_attestation(attestation)?;
+                integration_service.assign_voter_sector(did, &anonymous_id)?;
+                Ok(anonymous_id)
+            });
+        match attested {
+            Ok(anonymous_id) if did == &lgu_did => lgu_identity = Some(anonymous_id),
+            Ok(_) => {}
+            Err(e) => println!("❌ Error attesting {:?} sector for {}: {}", sector, did, e),
+        }
+    }
+    
+    // Government records are sealed, with a copy the privacy regulator can open during audits
+    let regulator_keys = zk_proofs::generate_identity_keypair();
+    if let Err(e) = integration_service.set_regulator_view_key("npc-audit-2026".to_string(), regulator_keys.public_key.clone()) {
+        println!("❌ Error registering regulator view key: {}", e);
+    }
+    let sealed = lgu_identity.as_deref().map(|anonymous_id| {
+        privacy_manager::seal_confidential_record(
+            anonymous_id,
+            SectorType::Government,
+            &lgu_identity_keys.secret,
+            &serde_json::json!({ "procurement": "Solar streetlights for barangay roads", "supplier": "did:veritoken-merchant:mainnet:green-shop-ph" }),
+            Some(250000.0),
+            integration_service.get_regulator_view_key(),
+        )
+        .and_then(|(record_tx, _)| integration_service.submit_confidential_record(record_tx))
+    });
+    integration_service.mine_pending_transactions();
+    match sealed {
+        Some(Ok(record_tx_id)) => {
+            match integration_service.audit_confidential_record(&record_tx_id, "did:veritoken-regulator:mainnet:npc", &regulator_keys.secret, "Annual LGU procurement audit".to_string()) {
+                Ok(audited) => println!("🔍 Audited sealed record: {} (commitment valid: {}, amount {:?})", audited.body["procurement"], audited.commitment_valid, audited.amount),
+                Err(e) => println!("❌ Error auditing sealed record: {}", e),
+            }
+        }
+        Some(Err(e)) => println!("❌ Error sealing LGU record: {}", e),
+        None => {}
+    }
+    
+    // A merchant exercises their right of access under the Data Privacy Act (RA 10173),
+    // signing the request with their DID key
+    let merchant_did = "did:veritoken-merchant:mainnet:green-shop-ph";
+    let request_id = "dsr_green-shop-ph_access";
+    let statement = privacy_manager::data_subject_request_statement(request_id, merchant_did, DataSubjectRight::Access);
+    match signing::sign_message(&merchant_keys.private_key, statement.as_bytes())
+        .and_then(|signature| integration_service.file_data_subject_request(
+            request_id,
+            merchant_did,
+            DataSubjectRight::Access,
+            SubjectAuthorization::DidSignature(signature),
+        ))
+        .and_then(|_| integration_service.fulfill_access_request(request_id))
+    {
+        Ok(export) => println!("📂 Access request for {}: {} profile(s) and {} record(s) exported",
+            merchant_did, export.profiles.len(), export.personal_records.len()),
+        Err(e) => println!("❌ Error handling access request: {}", e),
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let npc_report = integration_service.get_npc_report("LightChain Bantay Panahon", "did:veritoken-dpo:mainnet:lightchain", now - 31536000, now + 1);
+    println!("🇵🇭 NPC report: {} active consents, {} access and {} erasure requests, {} breaches ({} notified late)",
+        npc_report.consents.active,
+        npc_report.access_requests.received,
+        npc_report.erasure_requests.received,
+        npc_report.breaches.len(),
+        npc_report.late_notifications);
+    
+    // The LGU hands its marketplace-rules votes to a trusted representative
+    let mut delegation_tx = Transaction::new_governance_delegation(
+        lgu_did.clone(),
+        voter_did.clone(),
+        Some(GovernanceTopic::MarketplaceRules),
+    );
+    let delegated = delegation_tx
+        .sign(&lgu_keys.private_key)
+        .and_then(|_| integration_service.submit_governance_transaction(delegation_tx));
+    match delegated {
+        Ok(_) => println!("🤝 {} delegated marketplace-rules votes to {}", lgu_did, voter_did),
+        Err(e) => println!("❌ Error delegating votes: {}", e),
+    }
+    match integration_service.assign_merchant_reputation(&proposer_did) {
+        Ok(weight) => println!("⭐ Reputation weight for {}: {:.2}", proposer_did, weight),
+        Err(e) => println!("❌ Error assigning reputation: {}", e),
+    }
+    
+    let mut proposal_tx = Transaction::new_governance_proposal(
+        proposer_did.clone(),
         "prop_marketplace_001".to_string(),
-        "did:veritoken-merchant:mainnet:green-shop-ph".to_string(),
         "Implement mandatory sustainability scoring for all products".to_string(),
         "To promote transparency and encourage sustainable practices in the marketplace".to_string(),
+        ProposalType::General,
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + (7 * 24 * 60 * 60), // 7 days from now
+        None,
     );
-    
-    println!("\n🗳️  New Governance Proposal:");
-    println!("ID: {}", proposal.id);
-    println!("Title: {}", proposal.title);
-    println!("Proposer: {}", proposal.proposer);
-    println!("Status: {:?}", proposal.status);
+    let submitted = proposal_tx
+        .set_proposal_topic(GovernanceTopic::MarketplaceRules)
+        .and_then(|_| proposal_tx.set_sector_scope(SectorScope {
+            eligible_sectors: Vec::new(),
+            required_approvals: vec![SectorType::Business, SectorType::Government],
+        }))
+        .and_then(|_| proposal_tx.sign(&proposer_keys.private_key))
+        .and_then(|_| integration_service.submit_governance_transaction(proposal_tx));
+    if let Err(e) = submitted {
+        println!("❌ Error submitting proposal: {}", e);
+    }
+    integration_service.mine_pending_transactions();
+    
+    for (did, keys) in [(&proposer_did, &proposer_keys), (&voter_did, &voter_keys)] {
+        let mut vote_tx = Transaction::new_governance_vote(did.clone(), "prop_marketplace_001".to_string(), VoteChoice::For);
+        let cast = vote_tx
+            .sign(&keys.private_key)
+            .and_then(|_| integration_service.submit_governance_transaction(vote_tx));
+        if let Err(e) = cast {
+            println!("❌ Error casting vote for {}: {}", did, e);
+        }
+    }
+    integration_service.mine_pending_transactions();
+    
+    if let Some(proposal) = integration_service.get_governance_proposal("prop_marketplace_001") {
+        println!("\n🗳️  New Governance Proposal:");
+        println!("ID: {}", proposal.id);
+        println!("Title: {}", proposal.title);
+        println!("Proposer: {}", proposal.proposer);
+        println!("Status: {:?}", proposal.status);
+        println!("Topic: {:?}", proposal.topic);
+        println!("Voting Power: {:?}", proposal.voting_power);
+        println!("Votes: {:.2} for, {:.2} against, {:.2} abstain, {:.2} veto", proposal.votes_for, proposal.votes_against, proposal.votes_abstain, proposal.votes_veto);
+        println!("Turnout: {:.1}% of {} eligible voters", proposal.get_turnout_percentage(), proposal.eligible_voters);
+        for result in &proposal.sector_results {
+            println!(
+                "   {:?}{}: {:.2} for, {:.2} against of {:.2} eligible",
+                result.sector,
+                if result.required { " (approval required)" } else { "" },
+                result.votes_for,
+                result.votes_against,
+                result.eligible_power,
+            );
+        }
+    }
     
     println!("\n🎉 LightChain x Sideline_Pinas Integration Demo Complete!");
     println!("⚡ Your marketplace is now powered by LightChain environmental governance!");
@@ -224,9 +644,24 @@ for tx in [tx1, tx2, tx3].iter() {
     println!("   ✅ Marketplace transaction recording on blockchain");
     println!("   ✅ Automatic carbon footprint calculation");
     println!("   ✅ Sustainability scoring for merchants and products");
+    println!("   ✅ Resolvable W3C DID documents for merchants and products");
+    println!("   ✅ Verified eco-certifications from registered certifiers");
+    println!("   ✅ W3C verifiable credentials with on-chain revocation status lists");
     println!("   ✅ Carbon credit rewards for sustainable practices");
+    println!("   ✅ Serialized carbon credits with checkout offsetting");
     println!("   ✅ Environmental impact tracking");
-    println!("   ✅ Governance proposal system");
+    println!("   ✅ Community treasury funded by block rewards");
+    println!("   ✅ Sector retention windows with crypto-shredding and deletion receipts");
+    println!("   ✅ Sealed government records with a logged regulator view key");
+    println!("   ✅ Selective disclosure of transaction fields by sector transparency");
+    println!("   ✅ GDPR, FERPA, PCI DSS and SOX rule packs with per-sector violation reports");
+    println!("   ✅ Data Privacy Act (RA 10173) access requests and NPC reporting");
+    println!("   ✅ On-chain governance with signed, weighted and delegated votes");
+    println!("   ✅ Sector-scoped proposals with per-sector approval");
+    println!("   ✅ Fraud screening that holds or rejects risky sales");
+    println!("   ✅ Local CPU model inference with deterministic fallbacks");
+    println!("   ✅ Footprint, emission, credit and weather insights from chain data");
+    println!("   ✅ Threat detection with rate limiting and peer bans");
     println!("   ✅ Immutable audit trail for all transactions");
     
     // Add interactive CLI functionality
@@ -318,18 +753,45 @@ fn create_governance_proposal(integration_service: &mut LightChainIntegrationSer
     println!("\n🗳️  Create Governance Proposal");
     println!("===============================");
     
-    let proposal = GovernanceProposal::new(
-        format!("prop_{}", rand::random::<u32>()),
-        "did:lightchain:proposer:001".to_string(),
+    // A fresh proposer identity must be registered and mined before it can propose
+    let proposer_keys = signing::generate_keypair();
+    let proposer = format!("did:lightchain:proposer:{:08x}", rand::random::<u32>());
+    if let Err(e) = integration_service.register_governance_voter(&proposer, &proposer_keys.public_key) {
+        println!("❌ Error registering proposer: {}", e);
+        return;
+    }
+    integration_service.mine_pending_transactions();
+    
+    let proposal_id = format!("prop_{}", rand::random::<u32>());
+    let mut proposal_tx = Transaction::new_governance_proposal(
+        proposer,
+        proposal_id.clone(),
         "Increase carbon credit rewards for renewable energy usage".to_string(),
         "To incentivize more sustainable practices in the marketplace".to_string(),
+        ProposalType::ParameterChange,
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + (7 * 24 * 60 * 60), // 7 days from now
+        None,
     );
     
-    println!("📝 Proposal created:");
-    println!("   ID: {}", proposal.id);
-    println!("   Title: {}", proposal.title);
-    println!("   Status: {:?}", proposal.status);
-    println!("✅ Governance proposal created successfully!");
+    let submitted = proposal_tx
+        .sign(&proposer_keys.private_key)
+        .and_then(|_| integration_service.submit_governance_transaction(proposal_tx));
+    if let Err(e) = submitted {
+        println!("❌ Error submitting proposal: {}", e);
+        return;
+    }
+    integration_service.mine_pending_transactions();
+    
+    if let Some(proposal) = integration_service.get_governance_proposal(&proposal_id) {
+        println!("📝 Proposal created:");
+        println!("   ID: {}", proposal.id);
+        println!("   Title: {}", proposal.title);
+        println!("   Status: {:?}", proposal.status);
+        println!("✅ Governance proposal created successfully!");
+    }
 }
 
 fn view_blockchain_stats(integration_service: &LightChainIntegrationService) {
@@ -380,17 +842,28 @@ fn create_credential_verification(integration_service: &mut LightChainIntegratio
     println!("\n🎓 Create Credential Verification");
     println!("==================================");
     
-    let verification_tx = Transaction::new_credential_verification(
-        "did:lightchain:verifier:sustainability_council".to_string(),
-        format!("cred_{}", rand::random::<u32>()),
-        "did:lightchain:issuer:green_certification_body".to_string(),
-        "did:lightchain:merchant:eco_store_001".to_string(),
-        "Sustainable_Business_Certificate".to_string(),
-        true,
+    // A certification body anchors its DID, then issues a credential to a merchant
+    let issuer_keys = signing::generate_keypair();
+    let issuer_did = format!("did:veritoken-issuer:mainnet:green-body-{:08x}", rand::random::<u32>());
+    let mut issuer_did_tx = Transaction::new_did_creation(DidDocument::new(&issuer_did, &issuer_keys.public_key));
+    
+    let mut credential = VerifiableCredential::new(
+        &issuer_did,
+        "SustainableBusinessCredential",
+        serde_json::json!({ "id": "did:veritoken-merchant:mainnet:eco-store-001", "rating": "Gold" }),
+        Some(365 * 24 * 60 * 60), // 1 year
     );
     
-    integration_service.get_blockchain_mut().add_transaction(verification_tx);
-    println!("✅ Credential verification transaction created successfully!");
+    let result = issuer_did_tx
+        .sign(&issuer_keys.private_key)
+        .and_then(|_| integration_service.submit_did_transaction(issuer_did_tx))
+        .and_then(|_| credential.sign(&issuer_keys.private_key, &format!("{}#key-1", issuer_did)))
+        .and_then(|_| integration_service.verify_credential(&credential, "did:veritoken-verifier:mainnet:sustainability-council"));
+    
+    match result {
+        Ok(_) => println!("✅ Credential verification transaction created successfully!"),
+        Err(e) => println!("❌ Error verifying credential: {}", e),
+    }
 }
 
 fn view_environmental_data(integration_service: &LightChainIntegrationService) {
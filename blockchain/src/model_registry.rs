@@ -0,0 +1,301 @@
+//! Small scoring models run on the CPU with candle.
+//! A model is a stack of linear layers saved as safetensors under
+//! `layers.<i>.weight` and `layers.<i>.bias`, with ReLU between layers and a
+//! sigmoid on the single output. Files are named `<task>-<version>.safetensors`;
+//! the highest version of each task is used. Without a model, each task falls
+//! back to a fixed formula over the same features, so results are reproducible.
+
+use candle_core::{DType, Device, Tensor};
+use candle_nn::{Linear, Module};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::environment::EnvironmentalData;
+use crate::fraud_detection::RiskAssessment;
+use crate::transaction::Transaction;
+
+/// Version reported for results produced without a model.
+pub const FALLBACK_VERSION: &str = "fallback-1";
+
+pub const FRAUD_FEATURES: [&str; 6] = [
+    "log_amount",
+    "amount_to_product_median",
+    "buyer_recent_purchases",
+    "log_seller_sales",
+    "self_dealing",
+    "rule_score",
+];
+
+pub const ANOMALY_FEATURES: [&str; 6] = [
+    "co2_scaled",
+    "temperature_scaled",
+    "humidity_scaled",
+    "co2_z",
+    "temperature_z",
+    "humidity_z",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModelTask {
+    FraudScoring,
+    AnomalyDetection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureVector {
+    pub task: ModelTask,
+    pub values: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub task: ModelTask,
+    pub version: String,
+    pub path: PathBuf,
+    pub layers: usize,
+}
+
+/// A score from 0 to 1 and the model version that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prediction {
+    pub task: ModelTask,
+    pub score: f32,
+    pub model_version: String,
+    pub from_model: bool,
+    pub features: Vec<f32>,
+}
+
+struct LoadedModel {
+    info: ModelInfo,
+    layers: Vec<Linear>,
+}
+
+pub struct ModelRegistry {
+    device: Device,
+    models: HashMap<ModelTask, LoadedModel>,
+}
+
+impl ModelTask {
+    /// The file name prefix, e.g. "fraud_scoring".
+    pub fn file_prefix(&self) -> &'static str {
+        match self {
+            ModelTask::FraudScoring => "fraud_scoring",
+            ModelTask::AnomalyDetection => "anomaly_detection",
+        }
+    }
+
+    pub fn feature_names(&self) -> &'static [&'static str] {
+        match self {
+            ModelTask::FraudScoring => &FRAUD_FEATURES,
+            ModelTask::AnomalyDetection => &ANOMALY_FEATURES,
+        }
+    }
+}
+
+impl FeatureVector {
+    /// Features of a marketplace sale against the sales recorded before it, with
+    /// the rule-based score from fraud screening. `None` if `tx` is not a sale.
+    pub fn for_sale(tx: &Transaction, history: &[&Transaction], assessment: &RiskAssessment) -> Option<Self> {
+        let sale = tx.get_marketplace_details()?;
+        let earlier: Vec<_> = history
+            .iter()
+            .filter(|other| other.id != tx.id)
+            .filter_map(|other| other.get_marketplace_details().map(|details| (other.timestamp, details)))
+            .collect();
+
+        let mut product_amounts: Vec<f64> = earlier
+            .iter()
+            .filter(|(_, details)| details.product_did == sale.product_did)
+            .map(|(_, details)| details.amount)
+            .collect();
+        product_amounts.sort_by(|a, b| a.total_cmp(b));
+        let product_median = product_amounts.get(product_amounts.len() / 2).copied().unwrap_or(sale.amount);
+        let recent_purchases = earlier
+            .iter()
+            .filter(|(timestamp, details)| details.buyer_did == sale.buyer_did && tx.timestamp.abs_diff(*timestamp) <= 3_600)
+            .count();
+        let seller_sales = earlier.iter().filter(|(_, details)| details.seller_did == sale.seller_did).count();
+
+        Some(FeatureVector {
+            task: ModelTask::FraudScoring,
+            values: vec![
+                (sale.amount.max(0.0) as f32).ln_1p(),
+                if product_median > 0.0 { (sale.amount / product_median) as f32 } else { 1.0 },
+                recent_purchases as f32,
+                (seller_sales as f32).ln_1p(),
+                if sale.buyer_did == sale.seller_did { 1.0 } else { 0.0 },
+                assessment.score as f32,
+            ],
+        })
+    }
+
+    /// Features of an environmental reading, scaled and as z-scores against `history`.
+    pub fn for_environment(data: &EnvironmentalData, history: &[EnvironmentalData]) -> Self {
+        let z = |value: f64, field: fn(&EnvironmentalData) -> f64| {
+            let count = history.len() as f64;
+            if count < 2.0 {
+                return 0.0;
+            }
+            let mean = history.iter().map(field).sum::<f64>() / count;
+            let deviation = (history.iter().map(|entry| (field(entry) - mean).powi(2)).sum::<f64>() / count).sqrt();
+            if deviation > 0.0 { ((value - mean) / deviation) as f32 } else { 0.0 }
+        };
+
+        FeatureVector {
+            task: ModelTask::AnomalyDetection,
+            values: vec![
+                (data.co2_level / 1_000.0) as f32,
+                (data.temperature / 50.0) as f32,
+                (data.humidity / 100.0) as f32,
+                z(data.co2_level, |entry| entry.co2_level),
+                z(data.temperature, |entry| entry.temperature),
+                z(data.humidity, |entry| entry.humidity),
+            ],
+        }
+    }
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        ModelRegistry {
+            device: Device::Cpu,
+            models: HashMap::new(),
+        }
+    }
+
+    /// Loads the highest version of each task found in `dir`.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<Vec<ModelInfo>, String> {
+        let entries = std::fs::read_dir(dir).map_err(|e| format!("Cannot read model directory {}: {}", dir.display(), e))?;
+        let mut latest: HashMap<ModelTask, (String, PathBuf)> = HashMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some((task, version)) = parse_model_file(&path) else { continue };
+            let newer = latest
+                .get(&task)
+                .map(|(current, _)| compare_versions(&version, current).is_gt())
+                .unwrap_or(true);
+            if newer {
+                latest.insert(task, (version, path));
+            }
+        }
+
+        latest
+            .into_iter()
+            .map(|(task, (version, path))| self.load_model(task, &version, &path))
+            .collect()
+    }
+
+    /// Loads one model file, replacing any loaded model for the task.
+    pub fn load_model(&mut self, task: ModelTask, version: &str, path: &Path) -> Result<ModelInfo, String> {
+        let tensors = candle_core::safetensors::load(path, &self.device)
+            .map_err(|e| format!("Cannot load model {}: {}", path.display(), e))?;
+
+        let mut layers = Vec::new();
+        let mut input_size = task.feature_names().len();
+        while let Some(weight) = tensors.get(&format!("layers.{}.weight", layers.len())) {
+            let weight = weight.to_dtype(DType::F32).map_err(|e| e.to_string())?;
+            let (outputs, inputs) = weight.dims2().map_err(|e| format!("Layer {} of {}: {}", layers.len(), path.display(), e))?;
+            if inputs != input_size {
+                return Err(format!("Layer {} of {} takes {} inputs, expected {}", layers.len(), path.display(), inputs, input_size));
+            }
+            let bias = tensors
+                .get(&format!("layers.{}.bias", layers.len()))
+                .map(|bias| bias.to_dtype(DType::F32))
+                .transpose()
+                .map_err(|e| e.to_string())?;
+            layers.push(Linear::new(weight, bias));
+            input_size = outputs;
+        }
+        if layers.is_empty() || input_size != 1 {
+            return Err(format!("Model {} must have at least one layer and a single output", path.display()));
+        }
+
+        let info = ModelInfo {
+            task,
+            version: version.to_string(),
+            path: path.to_path_buf(),
+            layers: layers.len(),
+        };
+        self.models.insert(task, LoadedModel { info: info.clone(), layers });
+        Ok(info)
+    }
+
+    pub fn unload(&mut self, task: ModelTask) -> Option<ModelInfo> {
+        self.models.remove(&task).map(|model| model.info)
+    }
+
+    pub fn get_model(&self, task: ModelTask) -> Option<&ModelInfo> {
+        self.models.get(&task).map(|model| &model.info)
+    }
+
+    pub fn loaded_models(&self) -> Vec<&ModelInfo> {
+        self.models.values().map(|model| &model.info).collect()
+    }
+
+    /// Runs the task's model, or its fallback formula when no model is loaded or
+    /// inference fails.
+    pub fn predict(&self, features: &FeatureVector) -> Prediction {
+        let model_score = self.models
+            .get(&features.task)
+            .and_then(|model| self.run(model, &features.values).ok().map(|score| (score, model.info.version.clone())));
+
+        let (score, model_version, from_model) = match model_score {
+            Some((score, version)) => (score, version, true),
+            None => (fallback_score(features), FALLBACK_VERSION.to_string(), false),
+        };
+        Prediction {
+            task: features.task,
+            score,
+            model_version,
+            from_model,
+            features: features.values.clone(),
+        }
+    }
+
+    fn run(&self, model: &LoadedModel, values: &[f32]) -> candle_core::Result<f32> {
+        let mut activations = Tensor::from_slice(values, (1, values.len()), &self.device)?;
+        for (index, layer) in model.layers.iter().enumerate() {
+            activations = layer.forward(&activations)?;
+            if index + 1 < model.layers.len() {
+                activations = activations.relu()?;
+            }
+        }
+        let output = candle_nn::ops::sigmoid(&activations)?.flatten_all()?.to_vec1::<f32>()?;
+        output.first().copied().ok_or_else(|| candle_core::Error::Msg("Model produced no output".to_string()))
+    }
+}
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Splits `fraud_scoring-1.2.0.safetensors` into its task and version.
+fn parse_model_file(path: &Path) -> Option<(ModelTask, String)> {
+    if path.extension()? != "safetensors" {
+        return None;
+    }
+    let (prefix, version) = path.file_stem()?.to_str()?.rsplit_once('-')?;
+    let task = [ModelTask::FraudScoring, ModelTask::AnomalyDetection]
+        .into_iter()
+        .find(|task| task.file_prefix() == prefix)?;
+    Some((task, version.to_string()))
+}
+
+/// Compares dotted versions numerically, so "1.10" is newer than "1.9".
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |version: &str| version.split('.').map(|part| part.parse::<u64>().unwrap_or(0)).collect::<Vec<_>>();
+    parts(a).cmp(&parts(b))
+}
+
+/// The rule-based score for fraud, and the largest z-score mapped onto 0..1 for anomalies.
+fn fallback_score(features: &FeatureVector) -> f32 {
+    match features.task {
+        ModelTask::FraudScoring => features.values.last().copied().unwrap_or(0.0).clamp(0.0, 1.0),
+        ModelTask::AnomalyDetection => {
+            let largest_z = features.values.iter().skip(3).fold(0.0f32, |largest, z| largest.max(z.abs()));
+            (largest_z / 4.0).min(1.0)
+        }
+    }
+}
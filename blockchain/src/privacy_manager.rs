@@ -1,14 +1,62 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use rand::Rng;
+use crate::blockchain::Blockchain;
+use crate::compliance::{ComplianceReport, ComplianceRule, ComplianceStandard, ComplianceViolation, SectorComplianceReport};
+use crate::data_privacy_act::{
+    BreachAudience, BreachNotice, ConsentSummary, DataSubjectRequest, DataSubjectRight, NpcReport, PersonalRecordExport,
+    RequestOutcome, RequestSummary, SubjectAccessExport, SubjectAuthorization, BREACH_NOTIFICATION_WINDOW,
+};
+use crate::did::{DidRegistry, VerificationRelationship};
+use crate::confidential::{self, ConfidentialContents, EncryptedPayload};
+use crate::retention::{self, DeletionReceipt, PersonalDataVault, RecordCategory, RetainedRecord, RetentionAction, RetentionLog, RetentionReport, SectorRetentionReport};
+use crate::signing;
+use crate::transaction::{Transaction, TransactionType};
+use crate::zk_proofs::{self, OpeningProof, PedersenCommitment, PedersenOpening, SchnorrProof};
+
+/// Only this address may record identity lifecycle changes on chain.
+pub const IDENTITY_AUTHORITY: &str = "ecogov_identity_registry";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnonymousIdentity {
+    /// Derived from the public key, so an id always names the same key
     pub anonymous_id: String,
+    /// Ristretto public key; proofs show knowledge of its secret
+    pub public_key: String,
     pub sector: SectorType,
     pub verification_level: VerificationLevel,
+    pub attestations: Vec<IdentityAttestation>,
     pub created_at: u64,
-    pub is_active: bool,
+    pub status: IdentityStatus,
+    pub status_changed_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IdentityStatus {
+    Active,
+    Suspended { reason: String },
+    /// Terminal; a revoked identity cannot be reinstated
+    Revoked { reason: String },
+}
+
+/// A body accredited to vouch for identities up to the listed verification levels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityVerifier {
+    pub verifier_did: String,
+    pub name: String,
+    pub public_key: String,
+    pub accredited_levels: Vec<VerificationLevel>,
+    pub registered_at: u64,
+}
+
+/// A verifier's signed statement that an identity meets a verification level.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityAttestation {
+    pub anonymous_id: String,
+    pub verifier_did: String,
+    pub level: VerificationLevel,
+    pub issued_at: u64,
+    pub signature: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -21,7 +69,18 @@ pub enum SectorType {
     Individual,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl SectorType {
+    pub const ALL: [SectorType; 6] = [
+        SectorType::Government,
+        SectorType::Education,
+        SectorType::Industry,
+        SectorType::Enterprise,
+        SectorType::Business,
+        SectorType::Individual,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum VerificationLevel {
     Unverified,
     Basic,
@@ -30,19 +89,92 @@ pub enum VerificationLevel {
     Government,
 }
 
+/// Proves that the holder of an anonymous identity was behind a transaction,
+/// optionally with a hidden amount. Anyone can check it with `verify`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrivacyProof {
     pub proof_id: String,
     pub transaction_hash: String,
-    pub zero_knowledge_proof: String,
-    pub verification_status: bool,
+    pub anonymous_id: String,
+    pub public_key: String,
+    pub identity_proof: SchnorrProof,
+    pub amount_commitment: Option<AmountCommitment>,
     pub timestamp: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmountCommitment {
+    pub commitment: PedersenCommitment,
+    pub opening_proof: OpeningProof,
+}
+
+/// Public half of a regulator's view key; the auditor keeps the secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegulatorViewKey {
+    pub key_id: String,
+    pub public_key: String,
+    pub registered_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewKeyAccessEvent {
+    pub key_id: String,
+    pub auditor: String,
+    pub record_tx_id: String,
+    pub sector: SectorType,
+    pub purpose: String,
+    pub accessed_at: u64,
+}
+
+/// An identity's consent to one processing purpose; withdrawn consent stays on record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentRecord {
+    pub anonymous_id: String,
+    pub purpose: String,
+    pub granted_at: u64,
+    pub withdrawn_at: Option<u64>,
+}
+
+/// A read of an off-chain vault record by someone other than the system itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataAccessEvent {
+    pub record_id: String,
+    pub sector: SectorType,
+    pub accessor: String,
+    pub purpose: String,
+    pub accessed_at: u64,
+}
+
+/// A confidential record as decrypted by an auditor, with its commitments checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditedRecord {
+    pub record_tx_id: String,
+    pub anonymous_id: String,
+    pub sector: SectorType,
+    pub body: serde_json::Value,
+    pub amount: Option<f64>,
+    pub commitment_valid: bool,
+    pub amount_commitment_valid: Option<bool>,
+}
+
 pub struct PrivacyManager {
     anonymous_identities: HashMap<String, AnonymousIdentity>,
+    identity_secrets: HashMap<String, String>, // anonymous id -> secret held on the identity's behalf
+    verifiers: HashMap<String, IdentityVerifier>,
+    unanchored_records: Vec<Transaction>, // records not yet handed to the chain
     privacy_proofs: HashMap<String, PrivacyProof>,
+    personal_data: PersonalDataVault,
+    retention_log: RetentionLog,
+    regulator_view_keys: HashMap<String, RegulatorViewKey>,
+    active_view_key: Option<String>,
+    view_key_accesses: Vec<ViewKeyAccessEvent>,
+    consents: HashMap<String, Vec<ConsentRecord>>, // anonymous id -> consent history
+    data_accesses: Vec<DataAccessEvent>,
+    data_subject_requests: HashMap<String, DataSubjectRequest>,
+    breaches: HashMap<String, BreachNotice>,
     sector_configs: HashMap<SectorType, SectorConfig>,
+    authority_public_key: Option<String>, // must sign every identity registry record
+    authority_private_key: Option<String>, // signs the records this manager makes
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,14 +205,36 @@ impl PrivacyManager {
     pub fn new() -> Self {
         let mut manager = PrivacyManager {
             anonymous_identities: HashMap::new(),
+            identity_secrets: HashMap::new(),
+            verifiers: HashMap::new(),
+            unanchored_records: Vec::new(),
             privacy_proofs: HashMap::new(),
+            personal_data: PersonalDataVault::new(),
+            retention_log: RetentionLog::new(),
+            regulator_view_keys: HashMap::new(),
+            active_view_key: None,
+            view_key_accesses: Vec::new(),
+            consents: HashMap::new(),
+            data_accesses: Vec::new(),
+            data_subject_requests: HashMap::new(),
+            breaches: HashMap::new(),
             sector_configs: HashMap::new(),
+            authority_public_key: None,
+            authority_private_key: None,
         };
         
         manager.initialize_sector_configs();
         manager
     }
     
+    /// A manager acting as the identity registry, signing its records with `authority_keys`.
+    pub fn with_authority_keys(authority_keys: signing::KeyPair) -> Self {
+        let mut manager = Self::new();
+        manager.authority_public_key = Some(authority_keys.public_key);
+        manager.authority_private_key = Some(authority_keys.private_key);
+        manager
+    }
+    
     fn initialize_sector_configs(&mut self) {
         // Government sector configuration
         self.sector_configs.insert(SectorType::Government, SectorConfig {
@@ -95,6 +249,7 @@ impl PrivacyManager {
                     "ISO 27001".to_string(),
                     "GDPR".to_string(),
                     "SOX".to_string(),
+                    "RA 10173".to_string(),
                 ],
             },
         });
@@ -111,6 +266,7 @@ impl PrivacyManager {
                 compliance_standards: vec![
                     "FERPA".to_string(),
                     "COPPA".to_string(),
+                    "RA 10173".to_string(),
                 ],
             },
         });
@@ -128,6 +284,7 @@ impl PrivacyManager {
                     "ISO 14001".to_string(),
                     "OSHA".to_string(),
                     "EPA".to_string(),
+                    "RA 10173".to_string(),
                 ],
             },
         });
@@ -144,6 +301,7 @@ impl PrivacyManager {
                 compliance_standards: vec![
                     "SOX".to_string(),
                     "GDPR".to_string(),
+                    "RA 10173".to_string(),
                 ],
             },
         });
@@ -160,6 +318,7 @@ impl PrivacyManager {
                 compliance_standards: vec![
                     "PCI DSS".to_string(),
                     "GDPR".to_string(),
+                    "RA 10173".to_string(),
                 ],
             },
         });
@@ -175,48 +334,436 @@ impl PrivacyManager {
                 data_retention_period: 31536000, // 1 year
                 compliance_standards: vec![
                     "GDPR".to_string(),
+                    "RA 10173".to_string(),
                 ],
             },
         });
     }
     
+    /// Rebuilds identities and verifiers by replaying lifecycle records on the chain.
+    /// Secrets held on behalf of identities are not on chain and are not restored.
+    /// Registry records must be signed by `authority_public_key`; requests by DID
+    /// subjects are checked against `dids`.
+    pub fn from_chain(blockchain: &Blockchain, dids: &DidRegistry, authority_public_key: &str) -> Result<Self, String> {
+        let mut manager = Self::new();
+        manager.authority_public_key = Some(authority_public_key.to_string());
+        for transaction in blockchain.get_all_transactions() {
+            manager.apply_transaction(transaction, dids)?;
+        }
+        Ok(manager)
+    }
+    
+    /// Validates a lifecycle record against the current state and applies it.
+    /// Other transactions are ignored. Nothing changes on error.
+    pub fn apply_transaction(&mut self, tx: &Transaction, dids: &DidRegistry) -> Result<(), String> {
+        match &tx.transaction_type {
+            TransactionType::IdentityRegistration { anonymous_id, public_key, sector } => {
+                self.require_authority(tx)?;
+                if *anonymous_id != anonymous_id_for_key(public_key) {
+                    return Err(format!("Identity {} does not match its public key", anonymous_id));
+                }
+                if self.anonymous_identities.contains_key(anonymous_id) {
+                    return Err(format!("Identity {} is already registered", anonymous_id));
+                }
+                zk_proofs::verify_public_key(public_key)?;
+                self.anonymous_identities.insert(anonymous_id.clone(), AnonymousIdentity {
+                    anonymous_id: anonymous_id.clone(),
+                    public_key: public_key.clone(),
+                    sector: sector.clone(),
+                    verification_level: VerificationLevel::Unverified,
+                    attestations: Vec::new(),
+                    created_at: tx.timestamp,
+                    status: IdentityStatus::Active,
+                    status_changed_at: tx.timestamp,
+                });
+                Ok(())
+            }
+            TransactionType::IdentityVerifierRegistration { verifier_did, name, public_key, accredited_levels } => {
+                self.require_authority(tx)?;
+                if !signing::is_valid_public_key(public_key) {
+                    return Err(format!("Verifier {} has an invalid public key", verifier_did));
+                }
+                if accredited_levels.is_empty() {
+                    return Err(format!("Verifier {} has no accredited levels", verifier_did));
+                }
+                self.verifiers.insert(verifier_did.clone(), IdentityVerifier {
+                    verifier_did: verifier_did.clone(),
+                    name: name.clone(),
+                    public_key: public_key.clone(),
+                    accredited_levels: accredited_levels.clone(),
+                    registered_at: tx.timestamp,
+                });
+                Ok(())
+            }
+            TransactionType::IdentityVerification { attestation } => {
+                self.require_authority(tx)?;
+                self.check_attestation(attestation)?;
+                let identity = self.anonymous_identities.get_mut(&attestation.anonymous_id).unwrap();
+                identity.verification_level = attestation.level.clone();
+                identity.attestations.push(attestation.clone());
+                Ok(())
+            }
+            TransactionType::IdentityStatusChange { anonymous_id, status } => {
+                self.require_authority(tx)?;
+                let identity = self.anonymous_identities
+                    .get_mut(anonymous_id)
+                    .ok_or_else(|| format!("Identity {} not found", anonymous_id))?;
+                if matches!(identity.status, IdentityStatus::Revoked { .. }) {
+                    return Err(format!("Identity {} is revoked", anonymous_id));
+                }
+                if std::mem::discriminant(&identity.status) == std::mem::discriminant(status) {
+                    return Err(format!("Identity {} is already {:?}", anonymous_id, identity.status));
+                }
+                identity.status = status.clone();
+                identity.status_changed_at = tx.timestamp;
+                Ok(())
+            }
+            TransactionType::RegulatorViewKeyRegistration { key_id, public_key } => {
+                self.require_authority(tx)?;
+                zk_proofs::verify_public_key(public_key)?;
+                if self.regulator_view_keys.contains_key(key_id) {
+                    return Err(format!("View key {} is already registered", key_id));
+                }
+                self.regulator_view_keys.insert(key_id.clone(), RegulatorViewKey {
+                    key_id: key_id.clone(),
+                    public_key: public_key.clone(),
+                    registered_at: tx.timestamp,
+                });
+                self.active_view_key = Some(key_id.clone());
+                Ok(())
+            }
+            TransactionType::ViewKeyAccess { key_id, auditor, record_tx_id, sector, purpose } => {
+                self.require_authority(tx)?;
+                self.view_key_accesses.push(ViewKeyAccessEvent {
+                    key_id: key_id.clone(),
+                    auditor: auditor.clone(),
+                    record_tx_id: record_tx_id.clone(),
+                    sector: sector.clone(),
+                    purpose: purpose.clone(),
+                    accessed_at: tx.timestamp,
+                });
+                Ok(())
+            }
+            TransactionType::ConsentChange { anonymous_id, purpose, granted, subject_proof } => {
+                self.require_authority(tx)?;
+                let identity = self.anonymous_identities
+                    .get(anonymous_id)
+                    .ok_or_else(|| format!("Identity {} not found", anonymous_id))?;
+                let statement = consent_statement(purpose, *granted, self.consent_sequence(anonymous_id, purpose));
+                if !zk_proofs::verify_knowledge(&identity.public_key, &identity_proof_message(&statement, anonymous_id), subject_proof) {
+                    return Err(format!("Consent change is not authorized by {}", anonymous_id));
+                }
+                let has_consent = self.has_consent(anonymous_id, purpose);
+                if *granted && (has_consent || !identity.is_active()) {
+                    return Err(format!("Identity {} cannot grant consent for {}", anonymous_id, purpose));
+                }
+                if !*granted && !has_consent {
+                    return Err(format!("Identity {} has no consent for {} to withdraw", anonymous_id, purpose));
+                }
+                
+                let records = self.consents.entry(anonymous_id.clone()).or_default();
+                if *granted {
+                    records.push(ConsentRecord {
+                        anonymous_id: anonymous_id.clone(),
+                        purpose: purpose.clone(),
+                        granted_at: tx.timestamp,
+                        withdrawn_at: None,
+                    });
+                } else if let Some(consent) = records.iter_mut().find(|consent| consent.purpose == *purpose && consent.withdrawn_at.is_none()) {
+                    consent.withdrawn_at = Some(tx.timestamp);
+                }
+                Ok(())
+            }
+            TransactionType::PersonalDataAccess { record_id, sector, accessor, purpose } => {
+                self.require_authority(tx)?;
+                self.data_accesses.push(DataAccessEvent {
+                    record_id: record_id.clone(),
+                    sector: sector.clone(),
+                    accessor: accessor.clone(),
+                    purpose: purpose.clone(),
+                    accessed_at: tx.timestamp,
+                });
+                Ok(())
+            }
+            TransactionType::DataSubjectRequestFiled { request_id, subject, sector, right, authorization } => {
+                self.require_authority(tx)?;
+                if self.data_subject_requests.contains_key(request_id) {
+                    return Err(format!("Request {} is already filed", request_id));
+                }
+                let statement = data_subject_request_statement(request_id, subject, *right);
+                match authorization {
+                    SubjectAuthorization::IdentityProof(subject_proof) => {
+                        let identity = self.anonymous_identities
+                            .get(subject)
+                            .ok_or_else(|| format!("Identity {} not found", subject))?;
+                        if !zk_proofs::verify_knowledge(&identity.public_key, &identity_proof_message(&statement, subject), subject_proof) {
+                            return Err(format!("Request {} is not authorized by {}", request_id, subject));
+                        }
+                    }
+                    SubjectAuthorization::DidSignature(signature) => {
+                        dids.verify_signature(subject, VerificationRelationship::Authentication, statement.as_bytes(), signature)
+                            .map_err(|e| format!("Request {} is not authorized by {}: {}", request_id, subject, e))?;
+                    }
+                }
+                self.data_subject_requests.insert(request_id.clone(), DataSubjectRequest {
+                    request_id: request_id.clone(),
+                    subject: subject.clone(),
+                    sector: sector.clone(),
+                    right: *right,
+                    filed_at: tx.timestamp,
+                    resolved_at: None,
+                    outcome: None,
+                });
+                Ok(())
+            }
+            TransactionType::DataSubjectRequestResolved { request_id, outcome } => {
+                self.require_authority(tx)?;
+                let request = self.data_subject_requests
+                    .get_mut(request_id)
+                    .ok_or_else(|| format!("Request {} not found", request_id))?;
+                if !request.is_pending() {
+                    return Err(format!("Request {} is already resolved", request_id));
+                }
+                request.resolved_at = Some(tx.timestamp);
+                request.outcome = Some(outcome.clone());
+                Ok(())
+            }
+            TransactionType::BreachReported { breach_id, description, affected_sectors, affected_subjects, sensitive_personal_information, discovered_at } => {
+                self.require_authority(tx)?;
+                if self.breaches.contains_key(breach_id) {
+                    return Err(format!("Breach {} is already reported", breach_id));
+                }
+                if *discovered_at > tx.timestamp {
+                    return Err(format!("Breach {} cannot be discovered after it is reported", breach_id));
+                }
+                self.breaches.insert(breach_id.clone(), BreachNotice {
+                    breach_id: breach_id.clone(),
+                    description: description.clone(),
+                    affected_sectors: affected_sectors.clone(),
+                    affected_subjects: *affected_subjects,
+                    sensitive_personal_information: *sensitive_personal_information,
+                    discovered_at: *discovered_at,
+                    reported_at: tx.timestamp,
+                    commission_notified_at: None,
+                    subjects_notified_at: None,
+                });
+                Ok(())
+            }
+            TransactionType::BreachNotificationSent { breach_id, audience } => {
+                self.require_authority(tx)?;
+                let breach = self.breaches
+                    .get_mut(breach_id)
+                    .ok_or_else(|| format!("Breach {} not found", breach_id))?;
+                let notified_at = match audience {
+                    BreachAudience::Commission => &mut breach.commission_notified_at,
+                    BreachAudience::DataSubjects => &mut breach.subjects_notified_at,
+                };
+                if notified_at.is_some() {
+                    return Err(format!("{:?} already notified of breach {}", audience, breach_id));
+                }
+                *notified_at = Some(tx.timestamp);
+                Ok(())
+            }
+            TransactionType::ConfidentialRecord { anonymous_id, sector, commitment, amount_commitment, payload, view_key_id, author_proof } => {
+                let identity = self.anonymous_identities
+                    .get(anonymous_id)
+                    .ok_or_else(|| format!("Identity {} not found", anonymous_id))?;
+                if tx.from != *anonymous_id || identity.sector != *sector {
+                    return Err(format!("Confidential record does not match identity {}", anonymous_id));
+                }
+                if !identity.is_active() {
+                    return Err(format!("Identity {} is not active", anonymous_id));
+                }
+                let statement = confidential_record_statement(commitment, amount_commitment, payload, view_key_id);
+                if !zk_proofs::verify_knowledge(&identity.public_key, &identity_proof_message(&statement, anonymous_id), author_proof) {
+                    return Err(format!("Confidential record is not authored by {}", anonymous_id));
+                }
+                if !payload.has_recipient(confidential::OWNER_RECIPIENT) {
+                    return Err("Confidential record is not readable by its owner".to_string());
+                }
+                let oversight = self.sector_configs
+                    .get(sector)
+                    .is_some_and(|config| config.audit_requirements.government_oversight);
+                if oversight && (view_key_id.is_none() || *view_key_id != self.active_view_key || !payload.has_recipient(confidential::REGULATOR_RECIPIENT)) {
+                    return Err(format!("{:?} records must be readable with the current regulator view key", sector));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// An attestation upgrades an active identity only if it is signed by a
+    /// verifier accredited for that level.
+    pub fn check_attestation(&self, attestation: &IdentityAttestation) -> Result<(), String> {
+        let identity = self.anonymous_identities
+            .get(&attestation.anonymous_id)
+            .ok_or_else(|| format!("Identity {} not found", attestation.anonymous_id))?;
+        if !identity.is_active() {
+            return Err(format!("Identity {} is not active", attestation.anonymous_id));
+        }
+        let verifier = self.verifiers
+            .get(&attestation.verifier_did)
+            .ok_or_else(|| format!("Verifier {} is not registered", attestation.verifier_did))?;
+        if !verifier.accredited_levels.contains(&attestation.level) {
+            return Err(format!("Verifier {} is not accredited for {:?}", attestation.verifier_did, attestation.level));
+        }
+        let signature_valid = attestation
+            .signature
+            .as_ref()
+            .is_some_and(|signature| signing::verify_message(&verifier.public_key, &attestation.signing_payload(), signature));
+        if !signature_valid {
+            return Err(format!("Attestation from {} has an invalid signature", attestation.verifier_did));
+        }
+        if attestation.level.rank() <= identity.verification_level.rank() {
+            return Err(format!(
+                "Identity {} is already verified at {:?}",
+                attestation.anonymous_id, identity.verification_level
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Applies a lifecycle change made by this manager and keeps it for anchoring.
+    fn record(&mut self, transaction_type: TransactionType) -> Result<(), String> {
+        self.record_with_dids(transaction_type, &DidRegistry::new())
+    }
+    
+    /// Like `record`, for changes that are checked against DID documents.
+    fn record_with_dids(&mut self, transaction_type: TransactionType, dids: &DidRegistry) -> Result<(), String> {
+        let authority_private_key = self.authority_private_key
+            .as_ref()
+            .ok_or_else(|| "This manager cannot sign identity registry records".to_string())?;
+        let mut tx = Transaction::new(IDENTITY_AUTHORITY.to_string(), transaction_type);
+        tx.sign(authority_private_key)?;
+        self.apply_transaction(&tx, dids)?;
+        self.unanchored_records.push(tx);
+        Ok(())
+    }
+    
+    /// Hands over records made since the last call, to be added to the chain.
+    pub fn take_unanchored_records(&mut self) -> Vec<Transaction> {
+        std::mem::take(&mut self.unanchored_records)
+    }
+    
+    /// The sender must be the identity registry and sign with its configured key.
+    fn require_authority(&self, tx: &Transaction) -> Result<(), String> {
+        if tx.from != IDENTITY_AUTHORITY {
+            return Err(format!("{} is not authorized to act as the identity registry", tx.from));
+        }
+        let authority_public_key = self.authority_public_key
+            .as_ref()
+            .ok_or_else(|| "No identity registry key is configured".to_string())?;
+        if !tx.verify_signature(authority_public_key) {
+            return Err(format!("Transaction {} is not signed by the identity registry", tx.id));
+        }
+        Ok(())
+    }
+    
+    /// Creates an identity whose secret the manager keeps so it can prove on the holder's behalf.
     pub fn create_anonymous_identity(&mut self, sector: SectorType) -> Result<String, String> {
-        let anonymous_id = self.generate_anonymous_id();
-        let verification_level = self.get_required_verification_level(&sector);
-        
-        let identity = AnonymousIdentity {
+        let keypair = zk_proofs::generate_identity_keypair();
+        let anonymous_id = self.register_identity_key(sector, keypair.public_key)?;
+        self.identity_secrets.insert(anonymous_id.clone(), keypair.secret);
+        Ok(anonymous_id)
+    }
+    
+    /// Creates an identity for a holder who keeps their own secret and proves with
+    /// `zk_proofs::prove_knowledge` over `identity_proof_message`. The identity
+    /// starts unverified until an accredited verifier attests to it.
+    pub fn register_identity_key(&mut self, sector: SectorType, public_key: String) -> Result<String, String> {
+        let anonymous_id = anonymous_id_for_key(&public_key);
+        self.record(TransactionType::IdentityRegistration {
             anonymous_id: anonymous_id.clone(),
+            public_key,
             sector: sector.clone(),
-            verification_level: verification_level.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-            is_active: true,
-        };
-        
-        self.anonymous_identities.insert(anonymous_id.clone(), identity);
+        })?;
         
         println!("🔐 Anonymous identity created: {}", &anonymous_id[..8]);
         println!("   Sector: {:?}", sector);
-        println!("   Verification Level: {:?}", verification_level);
+        println!("   Verification Level: {:?}", VerificationLevel::Unverified);
         
         Ok(anonymous_id)
     }
     
+    pub fn register_verifier(&mut self, verifier_did: String, name: String, public_key: String, accredited_levels: Vec<VerificationLevel>) -> Result<(), String> {
+        self.record(TransactionType::IdentityVerifierRegistration {
+            verifier_did,
+            name,
+            public_key,
+            accredited_levels,
+        })
+    }
+    
+    pub fn get_verifier(&self, verifier_did: &str) -> Option<&IdentityVerifier> {
+        self.verifiers.get(verifier_did)
+    }
+    
+    /// Upgrades an identity's verification level from a signed attestation.
+    pub fn submit_attestation(&mut self, attestation: IdentityAttestation) -> Result<(), String> {
+        let (anonymous_id, level) = (attestation.anonymous_id.clone(), attestation.level.clone());
+        self.record(TransactionType::IdentityVerification { attestation })?;
+        println!("✅ Identity {} verified at {:?}", &anonymous_id[..8], level);
+        Ok(())
+    }
+    
+    pub fn suspend_identity(&mut self, anonymous_id: &str, reason: String) -> Result<(), String> {
+        self.record(TransactionType::IdentityStatusChange {
+            anonymous_id: anonymous_id.to_string(),
+            status: IdentityStatus::Suspended { reason },
+        })
+    }
+    
+    pub fn reinstate_identity(&mut self, anonymous_id: &str) -> Result<(), String> {
+        self.record(TransactionType::IdentityStatusChange {
+            anonymous_id: anonymous_id.to_string(),
+            status: IdentityStatus::Active,
+        })
+    }
+    
+    pub fn revoke_identity(&mut self, anonymous_id: &str, reason: String) -> Result<(), String> {
+        self.record(TransactionType::IdentityStatusChange {
+            anonymous_id: anonymous_id.to_string(),
+            status: IdentityStatus::Revoked { reason },
+        })?;
+        self.identity_secrets.remove(anonymous_id);
+        Ok(())
+    }
+    
     pub fn generate_zero_knowledge_proof(&mut self, transaction_hash: String, anonymous_id: String) -> Result<String, String> {
-        if !self.anonymous_identities.contains_key(&anonymous_id) {
-            return Err("Anonymous identity not found".to_string());
-        }
+        self.create_privacy_proof(transaction_hash, anonymous_id, None)
+    }
+    
+    /// Like `generate_zero_knowledge_proof`, but also commits to the transaction
+    /// amount. The opening is returned to the holder for selective disclosure.
+    pub fn generate_confidential_proof(&mut self, transaction_hash: String, anonymous_id: String, amount: f64) -> Result<(String, PedersenOpening), String> {
+        let (commitment, opening) = zk_proofs::commit_amount(amount)?;
+        let message = identity_proof_message(&transaction_hash, &anonymous_id);
+        let opening_proof = zk_proofs::prove_opening(&commitment, &opening, &message)?;
+        let proof_id = self.create_privacy_proof(transaction_hash, anonymous_id, Some(AmountCommitment {
+            commitment,
+            opening_proof,
+        }))?;
+        Ok((proof_id, opening))
+    }
+    
+    fn create_privacy_proof(&mut self, transaction_hash: String, anonymous_id: String, amount_commitment: Option<AmountCommitment>) -> Result<String, String> {
+        let identity = self.anonymous_identities
+            .get(&anonymous_id)
+            .ok_or_else(|| "Anonymous identity not found".to_string())?;
+        let secret = self.identity_secrets
+            .get(&anonymous_id)
+            .ok_or_else(|| format!("Identity {} holds its own secret and must prove for itself", anonymous_id))?;
         
+        let identity_proof = zk_proofs::prove_knowledge(secret, &identity_proof_message(&transaction_hash, &anonymous_id))?;
         let proof_id = format!("zkp_{}", rand::random::<u32>());
-        let zk_proof = self.create_zk_proof(&transaction_hash, &anonymous_id);
-        
         let privacy_proof = PrivacyProof {
             proof_id: proof_id.clone(),
             transaction_hash,
-            zero_knowledge_proof: zk_proof,
-            verification_status: true,
+            anonymous_id,
+            public_key: identity.public_key.clone(),
+            identity_proof,
+            amount_commitment,
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -230,23 +777,201 @@ impl PrivacyManager {
         Ok(proof_id)
     }
     
+    /// Stores a proof made by a self-custodied identity after checking it.
+    pub fn submit_privacy_proof(&mut self, proof: PrivacyProof) -> Result<String, String> {
+        let identity = self.anonymous_identities
+            .get(&proof.anonymous_id)
+            .ok_or_else(|| "Anonymous identity not found".to_string())?;
+        if identity.public_key != proof.public_key {
+            return Err(format!("Proof key does not belong to identity {}", proof.anonymous_id));
+        }
+        if !proof.verify() {
+            return Err(format!("Proof {} does not verify", proof.proof_id));
+        }
+        
+        let proof_id = proof.proof_id.clone();
+        self.privacy_proofs.insert(proof_id.clone(), proof);
+        Ok(proof_id)
+    }
+    
+    /// Re-verifies a stored proof and checks its key still belongs to the identity.
     pub fn verify_privacy_proof(&self, proof_id: &str) -> bool {
-        if let Some(proof) = self.privacy_proofs.get(proof_id) {
-            proof.verification_status
-        } else {
-            false
+        match self.privacy_proofs.get(proof_id) {
+            Some(proof) => {
+                self.anonymous_identities
+                    .get(&proof.anonymous_id)
+                    .is_some_and(|identity| {
+                        identity.public_key == proof.public_key && !matches!(identity.status, IdentityStatus::Revoked { .. })
+                    })
+                    && proof.verify()
+            }
+            None => false,
         }
     }
     
+    pub fn get_privacy_proof(&self, proof_id: &str) -> Option<&PrivacyProof> {
+        self.privacy_proofs.get(proof_id)
+    }
+    
+    pub fn get_anonymous_identity(&self, anonymous_id: &str) -> Option<&AnonymousIdentity> {
+        self.anonymous_identities.get(anonymous_id)
+    }
+    
+    /// Registers a new regulator view key; later records in oversight sectors are encrypted to it.
+    pub fn set_regulator_view_key(&mut self, key_id: String, public_key: String) -> Result<(), String> {
+        self.record(TransactionType::RegulatorViewKeyRegistration { key_id, public_key })
+    }
+    
+    pub fn get_regulator_view_key(&self) -> Option<&RegulatorViewKey> {
+        self.active_view_key.as_ref().and_then(|key_id| self.regulator_view_keys.get(key_id))
+    }
+    
+    /// Accepts a record sealed by its author with `seal_confidential_record` once
+    /// the author proof and the payload's recipients check out, and keeps it for anchoring.
+    pub fn submit_confidential_record(&mut self, tx: Transaction) -> Result<String, String> {
+        let sector = match &tx.transaction_type {
+            TransactionType::ConfidentialRecord { sector, .. } => sector,
+            _ => return Err(format!("Transaction {} is not a confidential record", tx.id)),
+        };
+        let config = self.sector_configs
+            .get(sector)
+            .ok_or_else(|| format!("No configuration for sector {:?}", sector))?;
+        if !config.requires_encryption() {
+            return Err(format!("{:?} records are public and are not sealed", sector));
+        }
+        
+        self.apply_transaction(&tx, &DidRegistry::new())?;
+        let tx_id = tx.id.clone();
+        self.unanchored_records.push(tx);
+        Ok(tx_id)
+    }
+    
+    /// Decrypts a record with the regulator view key and checks it against its
+    /// public commitments. Each use is recorded as a `ViewKeyAccess` event.
+    pub fn audit_confidential_record(&mut self, tx: &Transaction, auditor: &str, view_key_secret: &str, purpose: String) -> Result<AuditedRecord, String> {
+        let (anonymous_id, sector, commitment, amount_commitment, payload, view_key_id) = match &tx.transaction_type {
+            TransactionType::ConfidentialRecord { anonymous_id, sector, commitment, amount_commitment, payload, view_key_id, .. } => {
+                (anonymous_id, sector, commitment, amount_commitment, payload, view_key_id)
+            }
+            _ => return Err(format!("Transaction {} is not a confidential record", tx.id)),
+        };
+        let key_id = view_key_id
+            .as_ref()
+            .ok_or_else(|| format!("Record {} is not encrypted to a view key", tx.id))?;
+        let view_key = self.regulator_view_keys
+            .get(key_id)
+            .ok_or_else(|| format!("View key {} is not registered", key_id))?;
+        if zk_proofs::public_key_from_secret(view_key_secret)? != view_key.public_key {
+            return Err(format!("Secret does not match view key {}", key_id));
+        }
+        
+        let contents = confidential::open(payload, confidential::REGULATOR_RECIPIENT, view_key_secret)?;
+        self.record(TransactionType::ViewKeyAccess {
+            key_id: key_id.clone(),
+            auditor: auditor.to_string(),
+            record_tx_id: tx.id.clone(),
+            sector: sector.clone(),
+            purpose,
+        })?;
+        println!("🔍 View key {} used by {} on record {}", key_id, auditor, tx.id);
+        
+        let amount_commitment_valid = amount_commitment.as_ref().map(|amount_commitment| {
+            contents.amount_opening
+                .as_ref()
+                .is_some_and(|opening| zk_proofs::verify_opening(amount_commitment, opening))
+        });
+        Ok(AuditedRecord {
+            record_tx_id: tx.id.clone(),
+            anonymous_id: anonymous_id.clone(),
+            sector: sector.clone(),
+            amount: contents.amount_opening.as_ref().map(|opening| opening.units as f64 / zk_proofs::AMOUNT_SCALE),
+            commitment_valid: contents.commitment() == *commitment,
+            amount_commitment_valid,
+            body: contents.body,
+        })
+    }
+    
+    pub fn get_view_key_accesses(&self) -> &[ViewKeyAccessEvent] {
+        &self.view_key_accesses
+    }
+    
+    /// Records consent on behalf of an identity whose secret the manager holds.
+    pub fn grant_consent(&mut self, anonymous_id: &str, purpose: &str) -> Result<(), String> {
+        self.change_consent(anonymous_id, purpose, true)
+    }
+    
+    pub fn withdraw_consent(&mut self, anonymous_id: &str, purpose: &str) -> Result<(), String> {
+        self.change_consent(anonymous_id, purpose, false)
+    }
+    
+    fn change_consent(&mut self, anonymous_id: &str, purpose: &str, granted: bool) -> Result<(), String> {
+        let secret = self.identity_secrets
+            .get(anonymous_id)
+            .ok_or_else(|| format!("Identity {} holds its own secret and must consent for itself", anonymous_id))?;
+        let statement = consent_statement(purpose, granted, self.consent_sequence(anonymous_id, purpose));
+        let subject_proof = zk_proofs::prove_knowledge(secret, &identity_proof_message(&statement, anonymous_id))?;
+        self.submit_consent_change(anonymous_id, purpose, granted, subject_proof)
+    }
+    
+    /// Records a consent change proved by the identity itself over
+    /// `consent_statement(purpose, granted, consent_sequence(..))`.
+    pub fn submit_consent_change(&mut self, anonymous_id: &str, purpose: &str, granted: bool, subject_proof: SchnorrProof) -> Result<(), String> {
+        self.record(TransactionType::ConsentChange {
+            anonymous_id: anonymous_id.to_string(),
+            purpose: purpose.to_string(),
+            granted,
+            subject_proof,
+        })
+    }
+    
+    /// Number of grants and withdrawals so far for this identity and purpose.
+    pub fn consent_sequence(&self, anonymous_id: &str, purpose: &str) -> u64 {
+        self.consents
+            .get(anonymous_id)
+            .map(|records| {
+                records
+                    .iter()
+                    .filter(|consent| consent.purpose == purpose)
+                    .map(|consent| 1 + consent.withdrawn_at.is_some() as u64)
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+    
+    pub fn has_consent(&self, anonymous_id: &str, purpose: &str) -> bool {
+        self.get_consents(anonymous_id)
+            .iter()
+            .any(|consent| consent.purpose == purpose && consent.withdrawn_at.is_none())
+    }
+    
+    pub fn get_consents(&self, anonymous_id: &str) -> &[ConsentRecord] {
+        self.consents.get(anonymous_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+    
     pub fn get_sector_config(&self, sector: &SectorType) -> Option<&SectorConfig> {
         self.sector_configs.get(sector)
     }
     
+    pub fn update_sector_requirements(
+        &mut self,
+        sector: &SectorType,
+        required_verification_level: VerificationLevel,
+        government_oversight: bool,
+        transparency_level: f64,
+    ) -> Result<(), String> {
+        let config = self.sector_configs.get_mut(sector)
+            .ok_or_else(|| format!("No configuration for sector {:?}", sector))?;
+        config.required_verification_level = required_verification_level;
+        config.audit_requirements.government_oversight = government_oversight;
+        config.audit_requirements.transparency_level = transparency_level;
+        Ok(())
+    }
+    
     pub fn is_compliant_for_sector(&self, anonymous_id: &str, sector: &SectorType) -> bool {
         if let Some(identity) = self.anonymous_identities.get(anonymous_id) {
             if let Some(config) = self.sector_configs.get(sector) {
-                identity.is_active && 
-                self.verification_level_meets_requirement(&identity.verification_level, &config.required_verification_level)
+                identity.is_active() && 
+                identity.verification_level.rank() >= config.required_verification_level.rank()
             } else {
                 false
             }
@@ -255,52 +980,515 @@ impl PrivacyManager {
         }
     }
     
-    fn generate_anonymous_id(&self) -> String {
-        let mut rng = rand::thread_rng();
-        format!("anon_{:016x}", rng.gen::<u64>())
+    /// Stores off-chain personal data encrypted under a key of its own.
+    pub fn store_personal_record<T: Serialize>(&mut self, record_id: &str, subject: &str, sector: SectorType, category: RecordCategory, value: &T, now: u64) -> Result<(), String> {
+        self.personal_data.store(record_id, subject, sector, category, value, now)
     }
     
-    fn get_required_verification_level(&self, sector: &SectorType) -> VerificationLevel {
-        if let Some(config) = self.sector_configs.get(sector) {
-            config.required_verification_level.clone()
-        } else {
-            VerificationLevel::Basic
+    pub fn load_personal_record<T: serde::de::DeserializeOwned>(&self, record_id: &str) -> Result<T, String> {
+        self.personal_data.load(record_id)
+    }
+    
+    /// Reads a vault record for someone other than the system; the read is recorded on chain.
+    pub fn access_personal_record<T: serde::de::DeserializeOwned>(&mut self, record_id: &str, accessor: &str, purpose: String) -> Result<T, String> {
+        let value = self.personal_data.load(record_id)?;
+        let sector = self.personal_data
+            .get_record(record_id)
+            .map(|record| record.sector.clone())
+            .ok_or_else(|| format!("Record {} not found", record_id))?;
+        self.record(TransactionType::PersonalDataAccess {
+            record_id: record_id.to_string(),
+            sector,
+            accessor: accessor.to_string(),
+            purpose,
+        })?;
+        Ok(value)
+    }
+    
+    pub fn get_data_accesses(&self) -> &[DataAccessEvent] {
+        &self.data_accesses
+    }
+    
+    pub fn get_personal_data(&self) -> &PersonalDataVault {
+        &self.personal_data
+    }
+    
+    pub fn retention_period(&self, sector: &SectorType) -> u64 {
+        self.sector_configs
+            .get(sector)
+            .map(|config| config.audit_requirements.data_retention_period)
+            .unwrap_or(0)
+    }
+    
+    /// Every off-chain record still held. Identity secrets start their window
+    /// when the identity stops being active; proofs and vault records when stored.
+    pub fn retained_records(&self) -> Vec<RetainedRecord> {
+        let mut records = Vec::new();
+        for (anonymous_id, secret) in &self.identity_secrets {
+            if let Some(identity) = self.anonymous_identities.get(anonymous_id) {
+                records.push(RetainedRecord {
+                    record_id: anonymous_id.clone(),
+                    category: RecordCategory::IdentitySecret,
+                    sector: identity.sector.clone(),
+                    retained_since: (!identity.is_active()).then_some(identity.status_changed_at),
+                    digest: retention::record_digest(secret.as_bytes()),
+                });
+            }
         }
+        for proof in self.privacy_proofs.values() {
+            let sector = self.anonymous_identities
+                .get(&proof.anonymous_id)
+                .map(|identity| identity.sector.clone())
+                .unwrap_or(SectorType::Individual);
+            records.push(RetainedRecord {
+                record_id: proof.proof_id.clone(),
+                category: RecordCategory::PrivacyProof,
+                sector,
+                retained_since: Some(proof.timestamp),
+                digest: retention::record_digest(&serde_json::to_vec(proof).unwrap_or_default()),
+            });
+        }
+        for record in self.personal_data.readable_records() {
+            records.push(RetainedRecord {
+                record_id: record.record_id.clone(),
+                category: record.category,
+                sector: record.sector.clone(),
+                retained_since: Some(record.stored_at),
+                digest: retention::record_digest(record.ciphertext.as_bytes()),
+            });
+        }
+        records
     }
     
-    fn create_zk_proof(&self, transaction_hash: &str, anonymous_id: &str) -> String {
-        // Simplified zero-knowledge proof generation
-        // In production, use proper cryptographic libraries like arkworks or bellman
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+    /// Deletes every record past its sector's retention window: identity secrets
+    /// and vault records are crypto-shredded, proofs are purged. Returns the receipts.
+    pub fn enforce_retention(&mut self, now: u64) -> Vec<DeletionReceipt> {
+        let overdue: Vec<RetainedRecord> = self.retained_records()
+            .into_iter()
+            .filter(|record| record.is_overdue(self.retention_period(&record.sector), now))
+            .collect();
         
-        let mut hasher = DefaultHasher::new();
-        transaction_hash.hash(&mut hasher);
-        anonymous_id.hash(&mut hasher);
+        let receipts = self.delete_records(overdue, now);
+        if !receipts.is_empty() {
+            println!("🗑️  Retention enforced: {} records deleted", receipts.len());
+        }
+        receipts
+    }
+    
+    /// Deletes every off-chain record about a subject, named by anonymous id or
+    /// DID, ahead of its retention window. Returns the receipts.
+    pub fn erase_subject_data(&mut self, subject: &str, now: u64) -> Vec<DeletionReceipt> {
+        let records: Vec<RetainedRecord> = self.retained_records()
+            .into_iter()
+            .filter(|record| self.record_subject(record) == Some(subject))
+            .collect();
         
-        format!("zkp_{:016x}", hasher.finish())
+        let receipts = self.delete_records(records, now);
+        println!("🗑️  Erased {} records of {}", receipts.len(), subject);
+        receipts
     }
     
-    fn verification_level_meets_requirement(&self, current: &VerificationLevel, required: &VerificationLevel) -> bool {
-        use VerificationLevel::*;
+    /// Files an access or erasure request that its subject authorized over
+    /// `data_subject_request_statement(request_id, subject, right)`.
+    pub fn file_data_subject_request(
+        &mut self,
+        request_id: &str,
+        subject: &str,
+        right: DataSubjectRight,
+        authorization: SubjectAuthorization,
+        dids: &DidRegistry,
+    ) -> Result<(), String> {
+        let sector = self.anonymous_identities
+            .get(subject)
+            .map(|identity| identity.sector.clone())
+            .unwrap_or(SectorType::Individual);
+        self.record_with_dids(TransactionType::DataSubjectRequestFiled {
+            request_id: request_id.to_string(),
+            subject: subject.to_string(),
+            sector,
+            right,
+            authorization,
+        }, dids)?;
+        println!("📨 {:?} request {} filed for {}", right, request_id, subject);
+        Ok(())
+    }
+    
+    /// Gathers everything held about the subject of an access request, adds the
+    /// caller's `profiles`, and resolves the request with the export's digest.
+    pub fn fulfill_access_request(&mut self, request_id: &str, profiles: Vec<serde_json::Value>, now: u64) -> Result<SubjectAccessExport, String> {
+        let request = self.pending_request(request_id, DataSubjectRight::Access)?;
+        let subject = request.subject.clone();
+        let personal_records = self.personal_data
+            .readable_records()
+            .into_iter()
+            .filter(|record| record.subject == subject)
+            .map(|record| {
+                Ok(PersonalRecordExport {
+                    record_id: record.record_id.clone(),
+                    category: record.category,
+                    sector: record.sector.clone(),
+                    stored_at: record.stored_at,
+                    contents: self.personal_data.load(&record.record_id)?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let record_ids: Vec<&str> = personal_records.iter().map(|record| record.record_id.as_str()).collect();
         
-        let current_level = match current {
-            Unverified => 0,
-            Basic => 1,
-            Enhanced => 2,
-            Premium => 3,
-            Government => 4,
+        let export = SubjectAccessExport {
+            request_id: request_id.to_string(),
+            subject: subject.clone(),
+            identity: self.anonymous_identities.get(&subject).cloned(),
+            consents: self.get_consents(&subject).to_vec(),
+            privacy_proofs: self.privacy_proofs
+                .values()
+                .filter(|proof| proof.anonymous_id == subject)
+                .cloned()
+                .collect(),
+            accesses: self.data_accesses
+                .iter()
+                .filter(|access| record_ids.contains(&access.record_id.as_str()))
+                .cloned()
+                .collect(),
+            personal_records,
+            profiles,
+            generated_at: now,
         };
+        self.record(TransactionType::DataSubjectRequestResolved {
+            request_id: request_id.to_string(),
+            outcome: RequestOutcome::Fulfilled { evidence: export.digest() },
+        })?;
+        Ok(export)
+    }
+    
+    /// Erases the subject's off-chain data and resolves the request with the
+    /// head of the deletion receipt log.
+    pub fn fulfill_erasure_request(&mut self, request_id: &str, now: u64) -> Result<Vec<DeletionReceipt>, String> {
+        let subject = self.pending_request(request_id, DataSubjectRight::Erasure)?.subject.clone();
+        let receipts = self.erase_subject_data(&subject, now);
+        self.record(TransactionType::DataSubjectRequestResolved {
+            request_id: request_id.to_string(),
+            outcome: RequestOutcome::Fulfilled {
+                evidence: self.retention_log.head().unwrap_or("0").to_string(),
+            },
+        })?;
+        Ok(receipts)
+    }
+    
+    pub fn deny_data_subject_request(&mut self, request_id: &str, reason: String) -> Result<(), String> {
+        self.record(TransactionType::DataSubjectRequestResolved {
+            request_id: request_id.to_string(),
+            outcome: RequestOutcome::Denied { reason },
+        })
+    }
+    
+    fn pending_request(&self, request_id: &str, right: DataSubjectRight) -> Result<&DataSubjectRequest, String> {
+        let request = self.data_subject_requests
+            .get(request_id)
+            .ok_or_else(|| format!("Request {} not found", request_id))?;
+        if request.right != right {
+            return Err(format!("Request {} is not an {:?} request", request_id, right));
+        }
+        if !request.is_pending() {
+            return Err(format!("Request {} is already resolved", request_id));
+        }
+        Ok(request)
+    }
+    
+    pub fn get_data_subject_request(&self, request_id: &str) -> Option<&DataSubjectRequest> {
+        self.data_subject_requests.get(request_id)
+    }
+    
+    /// Records a personal data breach; `discovered_at` starts the notification clock.
+    pub fn report_breach(
+        &mut self,
+        description: String,
+        affected_sectors: Vec<SectorType>,
+        affected_subjects: usize,
+        sensitive_personal_information: bool,
+        discovered_at: u64,
+    ) -> Result<String, String> {
+        let breach_id = format!("breach_{}", rand::random::<u32>());
+        self.record(TransactionType::BreachReported {
+            breach_id: breach_id.clone(),
+            description,
+            affected_sectors,
+            affected_subjects,
+            sensitive_personal_information,
+            discovered_at,
+        })?;
+        println!("🚨 Personal data breach {} recorded", breach_id);
+        Ok(breach_id)
+    }
+    
+    pub fn record_breach_notification(&mut self, breach_id: &str, audience: BreachAudience) -> Result<(), String> {
+        self.record(TransactionType::BreachNotificationSent {
+            breach_id: breach_id.to_string(),
+            audience,
+        })
+    }
+    
+    pub fn get_breach(&self, breach_id: &str) -> Option<&BreachNotice> {
+        self.breaches.get(breach_id)
+    }
+    
+    /// Consents, data-subject requests and breaches for a reporting period, for
+    /// the National Privacy Commission. Requests and breaches count by filing and
+    /// discovery time; consents active at `period_end` count as active.
+    pub fn npc_report(&self, personal_information_controller: &str, data_protection_officer: &str, period_start: u64, period_end: u64, now: u64) -> NpcReport {
+        let in_period = |timestamp: u64| timestamp >= period_start && timestamp < period_end;
+        let consents = self.consents.values().flatten();
+        let consent_summary = ConsentSummary {
+            active: consents
+                .clone()
+                .filter(|consent| consent.granted_at < period_end && consent.withdrawn_at.is_none_or(|withdrawn| withdrawn >= period_end))
+                .count(),
+            granted_in_period: consents.clone().filter(|consent| in_period(consent.granted_at)).count(),
+            withdrawn_in_period: consents.filter(|consent| consent.withdrawn_at.is_some_and(in_period)).count(),
+        };
+        let requests = |right: DataSubjectRight| {
+            RequestSummary::from_requests(
+                self.data_subject_requests
+                    .values()
+                    .filter(|request| request.right == right && in_period(request.filed_at)),
+                now,
+            )
+        };
+        let mut breaches: Vec<BreachNotice> = self.breaches
+            .values()
+            .filter(|breach| in_period(breach.discovered_at))
+            .cloned()
+            .collect();
+        breaches.sort_by_key(|breach| breach.discovered_at);
+        
+        NpcReport {
+            personal_information_controller: personal_information_controller.to_string(),
+            data_protection_officer: data_protection_officer.to_string(),
+            period_start,
+            period_end,
+            consents: consent_summary,
+            access_requests: requests(DataSubjectRight::Access),
+            erasure_requests: requests(DataSubjectRight::Erasure),
+            mandatory_notifications: breaches.iter().filter(|breach| breach.is_mandatory()).count(),
+            late_notifications: breaches
+                .iter()
+                .filter(|breach| breach.is_mandatory() && breach.is_notification_late(BREACH_NOTIFICATION_WINDOW, now))
+                .count(),
+            breaches,
+            generated_at: now,
+        }
+    }
+    
+    /// Identity secrets and vault records are crypto-shredded, proofs are purged.
+    fn delete_records(&mut self, records: Vec<RetainedRecord>, now: u64) -> Vec<DeletionReceipt> {
+        let mut receipts = Vec::new();
+        for record in records {
+            let action = match record.category {
+                RecordCategory::IdentitySecret => {
+                    self.identity_secrets.remove(&record.record_id);
+                    RetentionAction::CryptoShredded
+                }
+                RecordCategory::PrivacyProof => {
+                    self.privacy_proofs.remove(&record.record_id);
+                    RetentionAction::Purged
+                }
+                RecordCategory::CheckoutOffset | RecordCategory::DisclosureOpening => {
+                    if self.personal_data.shred(&record.record_id).is_err() {
+                        continue;
+                    }
+                    RetentionAction::CryptoShredded
+                }
+            };
+            let retention_period = self.retention_period(&record.sector);
+            receipts.push(self.retention_log.append(&record, action, retention_period, now).clone());
+        }
+        receipts
+    }
+    
+    /// The identity an off-chain record is about, if it is about one.
+    fn record_subject<'a>(&'a self, record: &'a RetainedRecord) -> Option<&'a str> {
+        match record.category {
+            RecordCategory::IdentitySecret => Some(record.record_id.as_str()),
+            RecordCategory::PrivacyProof => self.privacy_proofs
+                .get(&record.record_id)
+                .map(|proof| proof.anonymous_id.as_str()),
+            RecordCategory::CheckoutOffset | RecordCategory::DisclosureOpening => self.personal_data
+                .get_record(&record.record_id)
+                .map(|stored| stored.subject.as_str()),
+        }
+    }
+    
+    pub fn retention_report(&self, now: u64) -> RetentionReport {
+        let records = self.retained_records();
+        let sectors = SectorType::ALL
+            .iter()
+            .map(|sector| {
+                let retention_period = self.retention_period(sector);
+                let held: Vec<&RetainedRecord> = records.iter().filter(|record| record.sector == *sector).collect();
+                let overdue_records = held.iter().filter(|record| record.is_overdue(retention_period, now)).count();
+                let deleted = |action: RetentionAction| {
+                    self.retention_log
+                        .receipts()
+                        .iter()
+                        .filter(|receipt| receipt.sector == *sector && receipt.action == action)
+                        .count()
+                };
+                SectorRetentionReport {
+                    sector: sector.clone(),
+                    retention_period,
+                    records_held: held.len(),
+                    overdue_records,
+                    purged: deleted(RetentionAction::Purged),
+                    crypto_shredded: deleted(RetentionAction::CryptoShredded),
+                    compliant: overdue_records == 0,
+                }
+            })
+            .collect();
         
-        let required_level = match required {
-            Unverified => 0,
-            Basic => 1,
-            Enhanced => 2,
-            Premium => 3,
-            Government => 4,
+        RetentionReport {
+            generated_at: now,
+            sectors,
+            receipt_count: self.retention_log.receipts().len(),
+            receipt_log_head: self.retention_log.head().map(str::to_string),
+            receipt_log_intact: self.retention_log.verify().is_ok(),
+        }
+    }
+    
+    pub fn get_retention_log(&self) -> &RetentionLog {
+        &self.retention_log
+    }
+    
+    /// Evaluates the rule pack of every supported standard in each sector's config.
+    pub fn compliance_report(&self, now: u64) -> ComplianceReport {
+        let records = self.retained_records();
+        let sectors: Vec<SectorComplianceReport> = SectorType::ALL
+            .iter()
+            .map(|sector| {
+                let labels = self.sector_configs
+                    .get(sector)
+                    .map(|config| config.audit_requirements.compliance_standards.clone())
+                    .unwrap_or_default();
+                let (standards, unsupported_standards): (Vec<_>, Vec<_>) = labels
+                    .into_iter()
+                    .map(|label| ComplianceStandard::from_label(&label).ok_or(label))
+                    .partition(Result::is_ok);
+                let standards: Vec<ComplianceStandard> = standards.into_iter().flatten().collect();
+                
+                let mut rules_checked = 0;
+                let mut violations = Vec::new();
+                for standard in &standards {
+                    for rule in standard.rule_pack() {
+                        rules_checked += 1;
+                        violations.extend(self.check_rule(*standard, &rule, sector, &records, now));
+                    }
+                }
+                SectorComplianceReport {
+                    sector: sector.clone(),
+                    standards,
+                    unsupported_standards: unsupported_standards.into_iter().filter_map(Result::err).collect(),
+                    rules_checked,
+                    compliant: violations.is_empty(),
+                    violations,
+                }
+            })
+            .collect();
+        
+        ComplianceReport {
+            generated_at: now,
+            total_violations: sectors.iter().map(|report| report.violations.len()).sum(),
+            sectors,
+        }
+    }
+    
+    fn check_rule(&self, standard: ComplianceStandard, rule: &ComplianceRule, sector: &SectorType, records: &[RetainedRecord], now: u64) -> Vec<ComplianceViolation> {
+        let violation = |subject: &str, detail: String| ComplianceViolation {
+            standard,
+            rule: rule.clone(),
+            sector: sector.clone(),
+            subject: subject.to_string(),
+            detail,
+        };
+        let identities = self.anonymous_identities.values().filter(|identity| identity.sector == *sector);
+        let records_of = |anonymous_id: &str| {
+            records
+                .iter()
+                .filter(|record| self.record_subject(record) == Some(anonymous_id))
+                .collect::<Vec<_>>()
         };
         
-        current_level >= required_level
+        match rule {
+            ComplianceRule::DataMinimization => {
+                let retention_period = self.retention_period(sector);
+                records
+                    .iter()
+                    .filter(|record| record.sector == *sector && record.is_overdue(retention_period, now))
+                    .map(|record| violation(&record.record_id, format!(
+                        "{:?} held past the {}-day retention window",
+                        record.category, retention_period / 86_400
+                    )))
+                    .collect()
+            }
+            ComplianceRule::Consent { purpose } => identities
+                .filter(|identity| identity.is_active() && !self.has_consent(&identity.anonymous_id, purpose))
+                .filter(|identity| !records_of(&identity.anonymous_id).is_empty())
+                .map(|identity| violation(&identity.anonymous_id, format!("Personal data held without consent for {}", purpose)))
+                .collect(),
+            ComplianceRule::RightToErasure => identities
+                .filter_map(|identity| self.erasure_reason(identity).map(|reason| (identity, reason)))
+                .flat_map(|(identity, reason)| {
+                    records_of(&identity.anonymous_id)
+                        .into_iter()
+                        .map(|record| violation(&record.record_id, format!(
+                            "{:?} of {} still held after {}",
+                            record.category, identity.anonymous_id, reason
+                        )))
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            ComplianceRule::AccessLogging => {
+                let view_key_accesses = self.view_key_accesses
+                    .iter()
+                    .filter(|access| access.sector == *sector)
+                    .map(|access| (&access.record_tx_id, &access.auditor, &access.purpose));
+                let data_accesses = self.data_accesses
+                    .iter()
+                    .filter(|access| access.sector == *sector)
+                    .map(|access| (&access.record_id, &access.accessor, &access.purpose));
+                view_key_accesses
+                    .chain(data_accesses)
+                    .filter(|(_, accessor, purpose)| accessor.trim().is_empty() || purpose.trim().is_empty())
+                    .map(|(record_id, accessor, _)| violation(record_id, format!("Access by '{}' does not name both accessor and purpose", accessor)))
+                    .collect()
+            }
+            ComplianceRule::BreachNotification { window } => self.breaches
+                .values()
+                .filter(|breach| breach.affected_sectors.contains(sector) && breach.is_mandatory())
+                .filter(|breach| breach.is_notification_late(*window, now))
+                .map(|breach| violation(&breach.breach_id, format!(
+                    "Regulator not notified within {} hours of discovery",
+                    window / 3_600
+                )))
+                .collect(),
+            ComplianceRule::RequestResponse { window } => self.data_subject_requests
+                .values()
+                .filter(|request| request.sector == *sector && request.is_overdue(*window, now))
+                .map(|request| violation(&request.request_id, format!(
+                    "{:?} request not resolved within {} days",
+                    request.right, window / 86_400
+                )))
+                .collect(),
+        }
+    }
+    
+    /// Why an identity's off-chain data should be gone, if it should.
+    fn erasure_reason(&self, identity: &AnonymousIdentity) -> Option<&'static str> {
+        let consents = self.get_consents(&identity.anonymous_id);
+        if matches!(identity.status, IdentityStatus::Revoked { .. }) {
+            Some("revocation")
+        } else if !consents.is_empty() && consents.iter().all(|consent| consent.withdrawn_at.is_some()) {
+            Some("consent withdrawal")
+        } else {
+            None
+        }
     }
     
     pub fn get_privacy_summary(&self) -> PrivacySummary {
@@ -308,7 +1496,7 @@ impl PrivacyManager {
             total_anonymous_identities: self.anonymous_identities.len(),
             total_privacy_proofs: self.privacy_proofs.len(),
             sector_distribution: self.get_sector_distribution(),
-            active_identities: self.anonymous_identities.values().filter(|i| i.is_active).count(),
+            active_identities: self.anonymous_identities.values().filter(|i| i.is_active()).count(),
         }
     }
     
@@ -323,6 +1511,157 @@ impl PrivacyManager {
     }
 }
 
+impl SectorConfig {
+    /// Private sectors and sectors under government oversight keep record contents off the public chain.
+    pub fn requires_encryption(&self) -> bool {
+        matches!(self.privacy_level, PrivacyLevel::Private | PrivacyLevel::HighlyPrivate)
+            || self.audit_requirements.government_oversight
+    }
+}
+
+impl VerificationLevel {
+    pub fn rank(&self) -> u8 {
+        match self {
+            VerificationLevel::Unverified => 0,
+            VerificationLevel::Basic => 1,
+            VerificationLevel::Enhanced => 2,
+            VerificationLevel::Premium => 3,
+            VerificationLevel::Government => 4,
+        }
+    }
+}
+
+impl AnonymousIdentity {
+    pub fn is_active(&self) -> bool {
+        self.status == IdentityStatus::Active
+    }
+}
+
+impl IdentityAttestation {
+    pub fn new(anonymous_id: String, verifier_did: String, level: VerificationLevel) -> Self {
+        IdentityAttestation {
+            anonymous_id,
+            verifier_did,
+            level,
+            issued_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            signature: None,
+        }
+    }
+    
+    /// The exact bytes the verifier signs; every field except the signature itself.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        format!("{}|{}|{:?}|{}", self.anonymous_id, self.verifier_did, self.level, self.issued_at).into_bytes()
+    }
+    
+    pub fn sign(&mut self, private_key: &str) -> Result<(), String> {
+        self.signature = Some(signing::sign_message(private_key, &self.signing_payload())?);
+        Ok(())
+    }
+}
+
+/// `anon_` followed by the first 16 hex digits of the key's SHA-256.
+pub fn anonymous_id_for_key(public_key: &str) -> String {
+    format!("anon_{}", &hex::encode(Sha256::digest(public_key.as_bytes()))[..16])
+}
+
+impl PrivacyProof {
+    /// Checks the proofs against the public key and transaction hash alone.
+    pub fn verify(&self) -> bool {
+        let message = identity_proof_message(&self.transaction_hash, &self.anonymous_id);
+        zk_proofs::verify_knowledge(&self.public_key, &message, &self.identity_proof)
+            && self.amount_commitment.as_ref().is_none_or(|amount| {
+                zk_proofs::verify_opening_proof(&amount.commitment, &message, &amount.opening_proof)
+            })
+    }
+}
+
+/// What an identity proves when it grants or withdraws consent. The sequence
+/// number stops an old grant from being replayed after a withdrawal.
+pub fn consent_statement(purpose: &str, granted: bool, sequence: u64) -> String {
+    format!("consent|{}|{}|{}", purpose, if granted { "grant" } else { "withdraw" }, sequence)
+}
+
+/// What a data subject proves or signs to file a request. The request id is
+/// unique, so the authorization cannot be reused for another request.
+pub fn data_subject_request_statement(request_id: &str, subject: &str, right: DataSubjectRight) -> String {
+    format!("data_subject_request|{}|{}|{:?}", request_id, subject, right)
+}
+
+/// What the author of a confidential record proves, so the proof covers the
+/// ciphertext and its recipients as well as the commitments.
+pub fn confidential_record_statement(
+    commitment: &str,
+    amount_commitment: &Option<PedersenCommitment>,
+    payload: &EncryptedPayload,
+    view_key_id: &Option<String>,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(commitment.as_bytes());
+    hasher.update(serde_json::to_vec(&(amount_commitment, payload, view_key_id)).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
+
+/// Encrypts a record to its author and, when `view_key` is given, the regulator,
+/// and proves authorship with the author's `secret`. Returns the transaction for
+/// `PrivacyManager::submit_confidential_record` and the opening of the amount
+/// commitment, which stays with the author.
+pub fn seal_confidential_record<T: Serialize>(
+    anonymous_id: &str,
+    sector: SectorType,
+    secret: &str,
+    body: &T,
+    amount: Option<f64>,
+    view_key: Option<&RegulatorViewKey>,
+) -> Result<(Transaction, Option<PedersenOpening>), String> {
+    let (amount_commitment, amount_opening) = match amount {
+        Some(amount) => {
+            let (commitment, opening) = zk_proofs::commit_amount(amount)?;
+            (Some(commitment), Some(opening))
+        }
+        None => (None, None),
+    };
+    let body = serde_json::to_value(body).map_err(|e| format!("Cannot serialize record: {}", e))?;
+    let contents = ConfidentialContents::new(body, amount_opening.clone());
+    let commitment = contents.commitment();
+    
+    let owner_key = zk_proofs::public_key_from_secret(secret)?;
+    let mut recipients = vec![(confidential::OWNER_RECIPIENT, owner_key.as_str())];
+    if let Some(view_key) = view_key {
+        recipients.push((confidential::REGULATOR_RECIPIENT, view_key.public_key.as_str()));
+    }
+    let payload = confidential::seal(&contents, &recipients)?;
+    let view_key_id = view_key.map(|view_key| view_key.key_id.clone());
+    let statement = confidential_record_statement(&commitment, &amount_commitment, &payload, &view_key_id);
+    let author_proof = zk_proofs::prove_knowledge(secret, &identity_proof_message(&statement, anonymous_id))?;
+    
+    let tx = Transaction::new(anonymous_id.to_string(), TransactionType::ConfidentialRecord {
+        anonymous_id: anonymous_id.to_string(),
+        sector,
+        commitment,
+        amount_commitment,
+        payload,
+        view_key_id,
+        author_proof,
+    });
+    Ok((tx, amount_opening))
+}
+
+/// Decrypts a record for its author, who holds `secret`.
+pub fn open_own_record(tx: &Transaction, secret: &str) -> Result<ConfidentialContents, String> {
+    match &tx.transaction_type {
+        TransactionType::ConfidentialRecord { payload, .. } => confidential::open(payload, confidential::OWNER_RECIPIENT, secret),
+        _ => Err(format!("Transaction {} is not a confidential record", tx.id)),
+    }
+}
+
+/// The message a privacy proof is bound to, so it cannot be replayed for another transaction.
+pub fn identity_proof_message(transaction_hash: &str, anonymous_id: &str) -> Vec<u8> {
+    format!("{}:{}", anonymous_id, transaction_hash).into_bytes()
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PrivacySummary {
     pub total_anonymous_identities: usize,
@@ -0,0 +1,256 @@
+//! Retention of off-chain personal data.
+//! Each vault record is encrypted under its own key, so deleting the key
+//! crypto-shreds the record along with any copy of its ciphertext. Every purge
+//! or shred leaves a receipt chained to the previous one, so a removed or edited
+//! receipt breaks the chain.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use crate::privacy_manager::SectorType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RecordCategory {
+    IdentitySecret,
+    PrivacyProof,
+    CheckoutOffset,
+    DisclosureOpening,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RetentionAction {
+    Purged,
+    CryptoShredded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedRecord {
+    pub record_id: String,
+    pub subject: String,
+    pub sector: SectorType,
+    pub category: RecordCategory,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub stored_at: u64,
+}
+
+/// Off-chain personal data encrypted at rest with one key per record.
+#[derive(Debug, Clone, Default)]
+pub struct PersonalDataVault {
+    records: HashMap<String, EncryptedRecord>,
+    keys: HashMap<String, [u8; 32]>, // record id -> key; a record without a key is shredded
+}
+
+/// An off-chain record as seen by the retention engine. `retained_since` is
+/// `None` while the record is still in use and its retention window has not started.
+#[derive(Debug, Clone)]
+pub struct RetainedRecord {
+    pub record_id: String,
+    pub category: RecordCategory,
+    pub sector: SectorType,
+    pub retained_since: Option<u64>,
+    pub digest: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletionReceipt {
+    pub sequence: u64,
+    pub record_id: String,
+    pub category: RecordCategory,
+    pub sector: SectorType,
+    pub action: RetentionAction,
+    /// SHA-256 of the record as held, so a surviving copy can be matched to this deletion
+    pub record_digest: String,
+    pub retained_since: u64,
+    pub retention_period: u64,
+    pub deleted_at: u64,
+    pub previous_hash: String,
+    pub receipt_hash: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RetentionLog {
+    receipts: Vec<DeletionReceipt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectorRetentionReport {
+    pub sector: SectorType,
+    pub retention_period: u64,
+    pub records_held: usize,
+    /// Records past their window that have not been deleted yet
+    pub overdue_records: usize,
+    pub purged: usize,
+    pub crypto_shredded: usize,
+    pub compliant: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub generated_at: u64,
+    pub sectors: Vec<SectorRetentionReport>,
+    pub receipt_count: usize,
+    pub receipt_log_head: Option<String>,
+    pub receipt_log_intact: bool,
+}
+
+impl RetainedRecord {
+    pub fn is_overdue(&self, retention_period: u64, now: u64) -> bool {
+        self.retained_since
+            .is_some_and(|since| now >= since.saturating_add(retention_period))
+    }
+}
+
+impl PersonalDataVault {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn store<T: Serialize>(&mut self, record_id: &str, subject: &str, sector: SectorType, category: RecordCategory, value: &T, now: u64) -> Result<(), String> {
+        if self.records.contains_key(record_id) {
+            return Err(format!("Record {} is already stored", record_id));
+        }
+        let plaintext = serde_json::to_vec(value).map_err(|e| format!("Cannot serialize record {}: {}", record_id, e))?;
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&key)
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| format!("Cannot encrypt record {}", record_id))?;
+
+        self.records.insert(record_id.to_string(), EncryptedRecord {
+            record_id: record_id.to_string(),
+            subject: subject.to_string(),
+            sector,
+            category,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            stored_at: now,
+        });
+        self.keys.insert(record_id.to_string(), key.into());
+        Ok(())
+    }
+
+    pub fn load<T: DeserializeOwned>(&self, record_id: &str) -> Result<T, String> {
+        let record = self.records
+            .get(record_id)
+            .ok_or_else(|| format!("Record {} not found", record_id))?;
+        let key = self.keys
+            .get(record_id)
+            .ok_or_else(|| format!("Record {} has been crypto-shredded", record_id))?;
+        let nonce = hex::decode(&record.nonce).map_err(|_| format!("Record {} has a corrupt nonce", record_id))?;
+        let ciphertext = hex::decode(&record.ciphertext).map_err(|_| format!("Record {} has corrupt ciphertext", record_id))?;
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(key))
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| format!("Record {} failed authentication", record_id))?;
+        serde_json::from_slice(&plaintext).map_err(|e| format!("Cannot decode record {}: {}", record_id, e))
+    }
+
+    /// Deletes the record's key; the ciphertext stays but can no longer be read.
+    pub fn shred(&mut self, record_id: &str) -> Result<(), String> {
+        self.keys
+            .remove(record_id)
+            .map(|_| ())
+            .ok_or_else(|| format!("Record {} has no key to shred", record_id))
+    }
+
+    pub fn purge(&mut self, record_id: &str) -> Result<EncryptedRecord, String> {
+        self.keys.remove(record_id);
+        self.records
+            .remove(record_id)
+            .ok_or_else(|| format!("Record {} not found", record_id))
+    }
+
+    pub fn get_record(&self, record_id: &str) -> Option<&EncryptedRecord> {
+        self.records.get(record_id)
+    }
+
+    pub fn is_readable(&self, record_id: &str) -> bool {
+        self.records.contains_key(record_id) && self.keys.contains_key(record_id)
+    }
+
+    /// Records that can still be decrypted.
+    pub fn readable_records(&self) -> Vec<&EncryptedRecord> {
+        self.records
+            .values()
+            .filter(|record| self.keys.contains_key(&record.record_id))
+            .collect()
+    }
+}
+
+impl DeletionReceipt {
+    pub fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!(
+            "{}|{}|{:?}|{:?}|{:?}|{}|{}|{}|{}|{}",
+            self.sequence,
+            self.record_id,
+            self.category,
+            self.sector,
+            self.action,
+            self.record_digest,
+            self.retained_since,
+            self.retention_period,
+            self.deleted_at,
+            self.previous_hash
+        ));
+        hex::encode(hasher.finalize())
+    }
+}
+
+impl RetentionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn append(&mut self, record: &RetainedRecord, action: RetentionAction, retention_period: u64, now: u64) -> &DeletionReceipt {
+        let mut receipt = DeletionReceipt {
+            sequence: self.receipts.len() as u64,
+            record_id: record.record_id.clone(),
+            category: record.category,
+            sector: record.sector.clone(),
+            action,
+            record_digest: record.digest.clone(),
+            retained_since: record.retained_since.unwrap_or(now),
+            retention_period,
+            deleted_at: now,
+            previous_hash: self.head().unwrap_or("0").to_string(),
+            receipt_hash: String::new(),
+        };
+        receipt.receipt_hash = receipt.compute_hash();
+        self.receipts.push(receipt);
+        self.receipts.last().unwrap()
+    }
+
+    /// Recomputes every receipt hash and link in the chain.
+    pub fn verify(&self) -> Result<(), String> {
+        let mut previous_hash = "0".to_string();
+        for (index, receipt) in self.receipts.iter().enumerate() {
+            if receipt.sequence != index as u64 {
+                return Err(format!("Receipt {} is out of sequence", index));
+            }
+            if receipt.previous_hash != previous_hash {
+                return Err(format!("Receipt {} does not link to its predecessor", index));
+            }
+            if receipt.receipt_hash != receipt.compute_hash() {
+                return Err(format!("Receipt {} has been altered", index));
+            }
+            previous_hash = receipt.receipt_hash.clone();
+        }
+        Ok(())
+    }
+
+    pub fn head(&self) -> Option<&str> {
+        self.receipts.last().map(|receipt| receipt.receipt_hash.as_str())
+    }
+
+    pub fn receipts(&self) -> &[DeletionReceipt] {
+        &self.receipts
+    }
+}
+
+pub fn record_digest(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
@@ -0,0 +1,63 @@
+//! Ed25519 helpers shared by every module that signs or checks data.
+//! Keys and signatures travel as hex strings, like hashes elsewhere on the chain.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::rngs::OsRng;
+
+#[derive(Debug, Clone)]
+pub struct KeyPair {
+    pub private_key: String,
+    pub public_key: String,
+}
+
+pub fn generate_keypair() -> KeyPair {
+    let signing_key = SigningKey::generate(&mut OsRng);
+    KeyPair {
+        private_key: hex::encode(signing_key.to_bytes()),
+        public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+    }
+}
+
+pub fn public_key_from_private(private_key: &str) -> Result<String, String> {
+    let signing_key = parse_signing_key(private_key)?;
+    Ok(hex::encode(signing_key.verifying_key().to_bytes()))
+}
+
+pub fn sign_message(private_key: &str, message: &[u8]) -> Result<String, String> {
+    let signing_key = parse_signing_key(private_key)?;
+    Ok(hex::encode(signing_key.sign(message).to_bytes()))
+}
+
+pub fn verify_message(public_key: &str, message: &[u8], signature: &str) -> bool {
+    let verifying_key = match parse_verifying_key(public_key) {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+    let signature_bytes: [u8; 64] = match hex::decode(signature).ok().and_then(|b| b.try_into().ok()) {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    verifying_key
+        .verify(message, &Signature::from_bytes(&signature_bytes))
+        .is_ok()
+}
+
+pub fn is_valid_public_key(public_key: &str) -> bool {
+    parse_verifying_key(public_key).is_ok()
+}
+
+fn parse_signing_key(private_key: &str) -> Result<SigningKey, String> {
+    let bytes: [u8; 32] = hex::decode(private_key)
+        .map_err(|_| "Private key is not valid hex".to_string())?
+        .try_into()
+        .map_err(|_| "Private key must be 32 bytes".to_string())?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+fn parse_verifying_key(public_key: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = hex::decode(public_key)
+        .map_err(|_| "Public key is not valid hex".to_string())?
+        .try_into()
+        .map_err(|_| "Public key must be 32 bytes".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| "Public key is not a valid Ed25519 point".to_string())
+}
@@ -0,0 +1,318 @@
+//! Detection of abuse aimed at the node itself.
+//! Inbound activity is reported per source: a peer id for gossiped
+//! transactions and blocks, or a DID for service calls. Mempool spam, repeated
+//! invalid signatures, blocks dated far in the future, deep reorgs and floods
+//! of merchant registrations raise typed alerts. A source that raises an alert
+//! is rate limited; consensus attacks, or too many strikes, get it banned.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use crate::block::Block;
+use crate::blockchain::Blockchain;
+use crate::transaction::Transaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ThreatKind {
+    /// Too many transactions, or the same transaction resent, within the window
+    MempoolSpam,
+    InvalidSignatures,
+    FutureBlockTimestamp,
+    /// A block that would replace more of the chain than allowed
+    DeepReorg,
+    /// One DID registering merchant profiles faster than allowed
+    RegistrationFlood,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThreatResponse {
+    RateLimited { until: u64 },
+    Banned { until: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatAlert {
+    pub kind: ThreatKind,
+    pub source: String,
+    pub detail: String,
+    pub raised_at: u64,
+    pub response: ThreatResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatDetectionConfig {
+    /// Sliding window, in seconds, for mempool and signature activity
+    pub window: u64,
+    pub max_transactions_per_window: usize,
+    /// Resends of a transaction already seen from the source within the window
+    pub max_duplicate_transactions: usize,
+    pub max_invalid_signatures: usize,
+    /// How far past the node's clock a block timestamp may be
+    pub max_future_drift: u64,
+    /// Blocks of the local chain a peer's block may replace
+    pub max_reorg_depth: usize,
+    pub registration_window: u64,
+    pub max_registrations_per_window: usize,
+    pub rate_limit_duration: u64,
+    pub ban_duration: u64,
+    /// Alerts from one source before it is banned rather than rate limited
+    pub strikes_before_ban: u32,
+}
+
+/// What one source has done recently.
+#[derive(Debug, Clone, Default)]
+struct SourceActivity {
+    transactions: VecDeque<(u64, String)>,
+    invalid_signatures: VecDeque<u64>,
+    registrations: VecDeque<u64>,
+    strikes: u32,
+    rate_limited_until: u64,
+    banned_until: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreatDetector {
+    config: ThreatDetectionConfig,
+    sources: HashMap<String, SourceActivity>,
+    alerts: Vec<ThreatAlert>,
+    last_eviction: u64,
+}
+
+impl Default for ThreatDetectionConfig {
+    fn default() -> Self {
+        ThreatDetectionConfig {
+            window: 60,
+            max_transactions_per_window: 50,
+            max_duplicate_transactions: 3,
+            max_invalid_signatures: 3,
+            max_future_drift: 2 * 60 * 60,
+            max_reorg_depth: 6,
+            registration_window: 60 * 60,
+            max_registrations_per_window: 3,
+            rate_limit_duration: 10 * 60,
+            ban_duration: 24 * 60 * 60,
+            strikes_before_ban: 3,
+        }
+    }
+}
+
+impl ThreatKind {
+    /// Attacks on consensus get the source banned on the first alert.
+    pub fn bans_immediately(&self) -> bool {
+        matches!(self, ThreatKind::FutureBlockTimestamp | ThreatKind::DeepReorg)
+    }
+}
+
+impl ThreatDetector {
+    pub fn new() -> Self {
+        ThreatDetector {
+            config: ThreatDetectionConfig::default(),
+            sources: HashMap::new(),
+            alerts: Vec::new(),
+            last_eviction: 0,
+        }
+    }
+
+    /// Fails while `source` is banned or rate limited.
+    pub fn check_source(&self, source: &str, now: u64) -> Result<(), String> {
+        let Some(activity) = self.sources.get(source) else { return Ok(()) };
+        if activity.banned_until > now {
+            return Err(format!("{} is banned until {}", source, activity.banned_until));
+        }
+        if activity.rate_limited_until > now {
+            return Err(format!("{} is rate limited until {}", source, activity.rate_limited_until));
+        }
+        Ok(())
+    }
+
+    /// Records a transaction submitted to the mempool by `source`.
+    pub fn observe_transaction(&mut self, source: &str, tx: &Transaction, now: u64) -> Option<ThreatAlert> {
+        self.evict_idle(now);
+        let window_start = now.saturating_sub(self.config.window);
+        let activity = self.sources.entry(source.to_string()).or_default();
+        while activity.transactions.front().is_some_and(|(seen_at, _)| *seen_at < window_start) {
+            activity.transactions.pop_front();
+        }
+        let duplicates = activity.transactions.iter().filter(|(_, id)| *id == tx.id).count();
+        activity.transactions.push_back((now, tx.id.clone()));
+
+        let detail = if duplicates > self.config.max_duplicate_transactions {
+            format!("{} resent transaction {} {} times within {}s", source, tx.id, duplicates, self.config.window)
+        } else if activity.transactions.len() > self.config.max_transactions_per_window {
+            format!("{} submitted {} transactions within {}s", source, activity.transactions.len(), self.config.window)
+        } else {
+            return None;
+        };
+        activity.transactions.clear();
+        Some(self.raise(ThreatKind::MempoolSpam, source, detail, now))
+    }
+
+    /// Records a transaction from `source` whose signature did not verify.
+    pub fn observe_invalid_signature(&mut self, source: &str, tx_id: &str, now: u64) -> Option<ThreatAlert> {
+        self.evict_idle(now);
+        let window_start = now.saturating_sub(self.config.window);
+        let activity = self.sources.entry(source.to_string()).or_default();
+        while activity.invalid_signatures.front().is_some_and(|seen_at| *seen_at < window_start) {
+            activity.invalid_signatures.pop_front();
+        }
+        activity.invalid_signatures.push_back(now);
+        if activity.invalid_signatures.len() <= self.config.max_invalid_signatures {
+            return None;
+        }
+
+        let detail = format!(
+            "{} sent {} transactions with invalid signatures within {}s, latest {}",
+            source,
+            activity.invalid_signatures.len(),
+            self.config.window,
+            tx_id,
+        );
+        activity.invalid_signatures.clear();
+        Some(self.raise(ThreatKind::InvalidSignatures, source, detail, now))
+    }
+
+    /// Checks a block announced by `source` against the node's clock and chain.
+    pub fn observe_block(&mut self, source: &str, block: &Block, blockchain: &Blockchain, now: u64) -> Option<ThreatAlert> {
+        if block.timestamp > now + self.config.max_future_drift {
+            let detail = format!(
+                "{} announced block {} dated {}s in the future",
+                source,
+                block.index,
+                block.timestamp - now,
+            );
+            return Some(self.raise(ThreatKind::FutureBlockTimestamp, source, detail, now));
+        }
+
+        let depth = reorg_depth(blockchain, block);
+        if depth > self.config.max_reorg_depth {
+            let detail = format!(
+                "{} announced block {} that would replace {} blocks (limit {})",
+                source,
+                block.index,
+                depth,
+                self.config.max_reorg_depth,
+            );
+            return Some(self.raise(ThreatKind::DeepReorg, source, detail, now));
+        }
+        None
+    }
+
+    /// Records a merchant profile registration by `did`.
+    pub fn observe_registration(&mut self, did: &str, now: u64) -> Option<ThreatAlert> {
+        self.evict_idle(now);
+        let window_start = now.saturating_sub(self.config.registration_window);
+        let activity = self.sources.entry(did.to_string()).or_default();
+        while activity.registrations.front().is_some_and(|seen_at| *seen_at < window_start) {
+            activity.registrations.pop_front();
+        }
+        activity.registrations.push_back(now);
+        if activity.registrations.len() <= self.config.max_registrations_per_window {
+            return None;
+        }
+
+        let detail = format!(
+            "{} registered {} merchant profiles within {}s",
+            did,
+            activity.registrations.len(),
+            self.config.registration_window,
+        );
+        activity.registrations.clear();
+        Some(self.raise(ThreatKind::RegistrationFlood, did, detail, now))
+    }
+
+    /// Lifts a ban or rate limit and forgets the source's strikes.
+    pub fn pardon(&mut self, source: &str) -> bool {
+        self.sources.remove(source).is_some()
+    }
+
+    pub fn is_banned(&self, source: &str, now: u64) -> bool {
+        self.sources.get(source).is_some_and(|activity| activity.banned_until > now)
+    }
+
+    pub fn is_rate_limited(&self, source: &str, now: u64) -> bool {
+        self.sources.get(source).is_some_and(|activity| activity.rate_limited_until > now)
+    }
+
+    pub fn banned_sources(&self, now: u64) -> Vec<&str> {
+        let mut banned: Vec<&str> = self.sources
+            .iter()
+            .filter(|(_, activity)| activity.banned_until > now)
+            .map(|(source, _)| source.as_str())
+            .collect();
+        banned.sort();
+        banned
+    }
+
+    /// Sources with recent activity or a penalty in force.
+    pub fn tracked_source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn get_alerts(&self) -> &[ThreatAlert] {
+        &self.alerts
+    }
+
+    pub fn get_config(&self) -> &ThreatDetectionConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: ThreatDetectionConfig) {
+        self.config = config;
+    }
+
+    /// Forgets sources with nothing left in their windows that are neither banned
+    /// nor rate limited. Sweeps at most once per window.
+    fn evict_idle(&mut self, now: u64) {
+        if now < self.last_eviction + self.config.window {
+            return;
+        }
+        self.last_eviction = now;
+        let window_start = now.saturating_sub(self.config.window);
+        let registration_window_start = now.saturating_sub(self.config.registration_window);
+        self.sources.retain(|_, activity| {
+            activity.banned_until > now
+                || activity.rate_limited_until > now
+                || activity.transactions.back().is_some_and(|(seen_at, _)| *seen_at >= window_start)
+                || activity.invalid_signatures.back().is_some_and(|seen_at| *seen_at >= window_start)
+                || activity.registrations.back().is_some_and(|seen_at| *seen_at >= registration_window_start)
+        });
+    }
+
+    /// Adds a strike to `source`, rate limits or bans it, and records the alert.
+    fn raise(&mut self, kind: ThreatKind, source: &str, detail: String, now: u64) -> ThreatAlert {
+        let activity = self.sources.entry(source.to_string()).or_default();
+        activity.strikes += 1;
+        let response = if kind.bans_immediately() || activity.strikes >= self.config.strikes_before_ban {
+            activity.banned_until = now + self.config.ban_duration;
+            ThreatResponse::Banned { until: activity.banned_until }
+        } else {
+            activity.rate_limited_until = now + self.config.rate_limit_duration;
+            ThreatResponse::RateLimited { until: activity.rate_limited_until }
+        };
+
+        let alert = ThreatAlert {
+            kind,
+            source: source.to_string(),
+            detail,
+            raised_at: now,
+            response,
+        };
+        self.alerts.push(alert.clone());
+        alert
+    }
+}
+
+impl Default for ThreatDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Blocks of the local chain that `block` would replace: none if it extends the
+/// chain or matches the block already at its height.
+pub fn reorg_depth(blockchain: &Blockchain, block: &Block) -> usize {
+    let chain = blockchain.get_blocks();
+    match chain.get(block.index) {
+        Some(existing) if existing.hash != block.hash => chain.len() - block.index,
+        _ => 0,
+    }
+}
@@ -1,5 +1,14 @@
 use serde::{Deserialize, Serialize};
 use crate::environment::EnvironmentalData;
+use crate::carbon_registry::ReductionClaim;
+use crate::governance::{GovernanceAction, GovernanceTopic, ReputationBasis, SectorScope, VotingMode};
+use crate::privacy_manager::{IdentityAttestation, IdentityStatus, SectorType, VerificationLevel};
+use crate::confidential::EncryptedPayload;
+use crate::credentials::{CredentialVerificationReport, StatusList};
+use crate::data_privacy_act::{BreachAudience, DataSubjectRight, RequestOutcome, SubjectAuthorization};
+use crate::did::DidDocument;
+use crate::zk_proofs::{PedersenCommitment, SchnorrProof};
+use crate::treasury::TreasurySource;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -15,11 +24,181 @@ pub enum TransactionType {
     CarbonCredit {
         amount: f64,
         project_id: String,
+        #[serde(default)]
+        claim: Option<ReductionClaim>,
     },
     GovernanceVote {
         proposal_id: String,
         vote: String,
     },
+    /// Sealed ballot for a commit-reveal proposal: `governance::vote_commitment` of the vote and a salt
+    GovernanceVoteCommitment {
+        proposal_id: String,
+        commitment: String,
+    },
+    GovernanceVoteReveal {
+        proposal_id: String,
+        vote: String,
+        salt: String,
+    },
+    GovernanceProposalCreation {
+        proposal_id: String,
+        title: String,
+        description: String,
+        #[serde(default)]
+        proposal_type: crate::governance::ProposalType,
+        #[serde(default)]
+        topic: GovernanceTopic,
+        #[serde(default)]
+        voting_mode: VotingMode,
+        #[serde(default)]
+        sector_scope: SectorScope,
+        voting_deadline: u64,
+        end_height: Option<u64>,
+        #[serde(default)]
+        action: Option<GovernanceAction>,
+    },
+    /// Records a passed proposal's action taking effect, with the value it replaced
+    GovernanceExecution {
+        proposal_id: String,
+        previous: Option<GovernanceAction>,
+        applied: GovernanceAction,
+    },
+    TreasuryDeposit {
+        amount: f64,
+        source: TreasurySource,
+    },
+    /// Pays one grant tranche, citing the impact evidence that met its milestone
+    TreasuryDisbursement {
+        grant_id: String,
+        tranche_index: usize,
+        amount: f64,
+        recipient: String,
+        evidence_tx_ids: Vec<String>,
+    },
+    GovernanceVoterRegistration {
+        voter: String,
+        public_key: String,
+    },
+    /// Hands the sender's vote to `delegate`; without a topic it covers every topic
+    GovernanceDelegation {
+        delegate: String,
+        topic: Option<GovernanceTopic>,
+    },
+    GovernanceUndelegation {
+        topic: Option<GovernanceTopic>,
+    },
+    IdentityRegistration {
+        anonymous_id: String,
+        public_key: String,
+        sector: SectorType,
+    },
+    IdentityVerifierRegistration {
+        verifier_did: String,
+        name: String,
+        public_key: String,
+        accredited_levels: Vec<VerificationLevel>,
+    },
+    IdentityVerification {
+        attestation: IdentityAttestation,
+    },
+    IdentityStatusChange {
+        anonymous_id: String,
+        status: IdentityStatus,
+    },
+    /// A record whose contents only its recipients can read; the public sees the commitments
+    ConfidentialRecord {
+        anonymous_id: String,
+        sector: SectorType,
+        commitment: String,
+        amount_commitment: Option<PedersenCommitment>,
+        payload: EncryptedPayload,
+        view_key_id: Option<String>,
+        /// Schnorr proof by the identity over the commitment
+        author_proof: SchnorrProof,
+    },
+    RegulatorViewKeyRegistration {
+        key_id: String,
+        public_key: String,
+    },
+    ViewKeyAccess {
+        key_id: String,
+        auditor: String,
+        record_tx_id: String,
+        sector: SectorType,
+        purpose: String,
+    },
+    /// Schnorr proof by the identity over `consent_statement`, so consent cannot be forged or replayed
+    ConsentChange {
+        anonymous_id: String,
+        purpose: String,
+        granted: bool,
+        subject_proof: SchnorrProof,
+    },
+    PersonalDataAccess {
+        record_id: String,
+        sector: SectorType,
+        accessor: String,
+        purpose: String,
+    },
+    DataSubjectRequestFiled {
+        request_id: String,
+        subject: String,
+        sector: SectorType,
+        right: DataSubjectRight,
+        authorization: SubjectAuthorization,
+    },
+    DataSubjectRequestResolved {
+        request_id: String,
+        outcome: RequestOutcome,
+    },
+    BreachReported {
+        breach_id: String,
+        description: String,
+        affected_sectors: Vec<SectorType>,
+        affected_subjects: usize,
+        sensitive_personal_information: bool,
+        discovered_at: u64,
+    },
+    BreachNotificationSent {
+        breach_id: String,
+        audience: BreachAudience,
+    },
+    /// Signed by the new DID itself, or by its controller when that is another DID
+    DidCreation {
+        document: DidDocument,
+    },
+    /// Replaces the document; signed by the current controller
+    DidUpdate {
+        document: DidDocument,
+        /// Version being replaced, so an old update cannot be replayed
+        previous_version_id: u64,
+    },
+    DidDeactivation {
+        did: String,
+    },
+    /// Signed by the issuer; replaces any earlier version of the list
+    CredentialStatusListPublication {
+        list: StatusList,
+    },
+    /// Attests the sector a voter belongs to, checked against their anonymous identity
+    GovernanceSectorAssignment {
+        voter: String,
+        sector: SectorType,
+    },
+    /// Withdraws a voter's sector once the identity behind it is suspended or revoked
+    GovernanceSectorRevocation {
+        voter: String,
+        reason: String,
+    },
+    GovernanceReputationUpdate {
+        voter: String,
+        weight: f64,
+        basis: ReputationBasis,
+    },
+    ValidatorStake {
+        amount: f64,
+    },
     EnvironmentalData {
         data: EnvironmentalData,
     },
@@ -37,11 +216,21 @@ pub enum TransactionType {
         carbon_footprint: Option<f64>,
         sustainability_score: Option<f64>,
     },
+    CertifierRegistration {
+        certifier_did: String,
+        name: String,
+        public_key: String,
+        accredited_certifications: Vec<String>,
+    },
+    CertifierDeactivation {
+        certifier_did: String,
+    },
     CredentialVerification {
         credential_id: String,
         issuer_did: String,
         subject_did: String,
         credential_type: String,
+        credential_hash: String,
         verification_result: bool,
     },
     EnvironmentalImpact {
@@ -58,6 +247,34 @@ pub enum TransactionType {
         original_transaction_id: String,
         original_amount: f64,
     },
+    CarbonProjectRegistration {
+        project_id: String,
+        name: String,
+        developer_did: String,
+        methodology: String,
+        location: String,
+    },
+    CarbonCreditIssuance {
+        batch_id: String,
+        claim: ReductionClaim,
+        serial_start: u64,
+        serial_end: u64,
+        recipient: String,
+    },
+    CarbonCreditTransfer {
+        serial_start: u64,
+        serial_end: u64,
+        recipient: String,
+    },
+    CarbonCreditRetirement {
+        certificate_id: String,
+        serial_start: u64,
+        serial_end: u64,
+        beneficiary: String,
+        reason: String,
+        /// Marketplace order the retirement offsets, if any
+        order_id: Option<String>,
+    },
 }
 
 impl Transaction {
@@ -99,22 +316,17 @@ impl Transaction {
         )
     }
     
-    pub fn new_credential_verification(
-        verifier: String,
-        credential_id: String,
-        issuer_did: String,
-        subject_did: String,
-        credential_type: String,
-        verification_result: bool,
-    ) -> Self {
+    /// Records the outcome of `credentials::verify_credential` or a certification check.
+    pub fn new_credential_verification(verifier: String, report: &CredentialVerificationReport) -> Self {
         Transaction::new(
             verifier,
             TransactionType::CredentialVerification {
-                credential_id,
-                issuer_did,
-                subject_did,
-                credential_type,
-                verification_result,
+                credential_id: report.credential_id.clone(),
+                issuer_did: report.issuer_did.clone(),
+                subject_did: report.subject_did.clone(),
+                credential_type: report.credential_type.clone(),
+                credential_hash: report.credential_hash.clone(),
+                verification_result: report.is_valid(),
             }
         )
     }
@@ -138,6 +350,94 @@ impl Transaction {
         )
     }
     
+    pub fn new_governance_proposal(
+        proposer: String,
+        proposal_id: String,
+        title: String,
+        description: String,
+        proposal_type: crate::governance::ProposalType,
+        voting_deadline: u64,
+        end_height: Option<u64>,
+    ) -> Self {
+        Transaction::new(
+            proposer,
+            TransactionType::GovernanceProposalCreation {
+                proposal_id,
+                title,
+                description,
+                proposal_type,
+                topic: GovernanceTopic::default(),
+                voting_mode: VotingMode::default(),
+                sector_scope: SectorScope::default(),
+                voting_deadline,
+                end_height,
+                action: None,
+            }
+        )
+    }
+    
+    pub fn new_governance_vote(voter: String, proposal_id: String, vote: crate::governance::VoteChoice) -> Self {
+        Transaction::new(
+            voter,
+            TransactionType::GovernanceVote {
+                proposal_id,
+                vote: vote.as_str().to_string(),
+            }
+        )
+    }
+    
+    pub fn new_governance_vote_commitment(voter: String, proposal_id: String, commitment: String) -> Self {
+        Transaction::new(
+            voter,
+            TransactionType::GovernanceVoteCommitment {
+                proposal_id,
+                commitment,
+            }
+        )
+    }
+    
+    pub fn new_governance_vote_reveal(voter: String, proposal_id: String, vote: crate::governance::VoteChoice, salt: String) -> Self {
+        Transaction::new(
+            voter,
+            TransactionType::GovernanceVoteReveal {
+                proposal_id,
+                vote: vote.as_str().to_string(),
+                salt,
+            }
+        )
+    }
+    
+    pub fn new_governance_delegation(delegator: String, delegate: String, topic: Option<GovernanceTopic>) -> Self {
+        Transaction::new(
+            delegator,
+            TransactionType::GovernanceDelegation {
+                delegate,
+                topic,
+            }
+        )
+    }
+    
+    pub fn new_governance_undelegation(delegator: String, topic: Option<GovernanceTopic>) -> Self {
+        Transaction::new(delegator, TransactionType::GovernanceUndelegation { topic })
+    }
+    
+    /// Sent by the document's controller, which is the DID itself unless delegated.
+    pub fn new_did_creation(document: DidDocument) -> Self {
+        Transaction::new(document.controller.clone(), TransactionType::DidCreation { document })
+    }
+    
+    pub fn new_did_update(controller: String, document: DidDocument, previous_version_id: u64) -> Self {
+        Transaction::new(controller, TransactionType::DidUpdate { document, previous_version_id })
+    }
+    
+    pub fn new_did_deactivation(controller: String, did: String) -> Self {
+        Transaction::new(controller, TransactionType::DidDeactivation { did })
+    }
+    
+    pub fn new_status_list_publication(list: StatusList) -> Self {
+        Transaction::new(list.issuer.clone(), TransactionType::CredentialStatusListPublication { list })
+    }
+    
     fn generate_id() -> String {
         use rand::Rng;
         let mut rng = rand::thread_rng();
@@ -148,12 +448,65 @@ impl Transaction {
         match self.transaction_type {
             TransactionType::CarbonCredit { .. } => "CarbonCredit",
             TransactionType::GovernanceVote { .. } => "GovernanceVote",
+            TransactionType::GovernanceVoteCommitment { .. } => "GovernanceVoteCommitment",
+            TransactionType::GovernanceVoteReveal { .. } => "GovernanceVoteReveal",
+            TransactionType::GovernanceProposalCreation { .. } => "GovernanceProposalCreation",
+            TransactionType::GovernanceVoterRegistration { .. } => "GovernanceVoterRegistration",
+            TransactionType::GovernanceExecution { .. } => "GovernanceExecution",
+            TransactionType::TreasuryDeposit { .. } => "TreasuryDeposit",
+            TransactionType::TreasuryDisbursement { .. } => "TreasuryDisbursement",
+            TransactionType::GovernanceDelegation { .. } => "GovernanceDelegation",
+            TransactionType::GovernanceUndelegation { .. } => "GovernanceUndelegation",
+            TransactionType::GovernanceSectorAssignment { .. } => "GovernanceSectorAssignment",
+            TransactionType::GovernanceSectorRevocation { .. } => "GovernanceSectorRevocation",
+            TransactionType::IdentityRegistration { .. } => "IdentityRegistration",
+            TransactionType::IdentityVerifierRegistration { .. } => "IdentityVerifierRegistration",
+            TransactionType::IdentityVerification { .. } => "IdentityVerification",
+            TransactionType::IdentityStatusChange { .. } => "IdentityStatusChange",
+            TransactionType::ConfidentialRecord { .. } => "ConfidentialRecord",
+            TransactionType::RegulatorViewKeyRegistration { .. } => "RegulatorViewKeyRegistration",
+            TransactionType::ViewKeyAccess { .. } => "ViewKeyAccess",
+            TransactionType::ConsentChange { .. } => "ConsentChange",
+            TransactionType::PersonalDataAccess { .. } => "PersonalDataAccess",
+            TransactionType::DataSubjectRequestFiled { .. } => "DataSubjectRequestFiled",
+            TransactionType::DataSubjectRequestResolved { .. } => "DataSubjectRequestResolved",
+            TransactionType::BreachReported { .. } => "BreachReported",
+            TransactionType::BreachNotificationSent { .. } => "BreachNotificationSent",
+            TransactionType::DidCreation { .. } => "DidCreation",
+            TransactionType::DidUpdate { .. } => "DidUpdate",
+            TransactionType::DidDeactivation { .. } => "DidDeactivation",
+            TransactionType::CredentialStatusListPublication { .. } => "CredentialStatusListPublication",
+            TransactionType::GovernanceReputationUpdate { .. } => "GovernanceReputationUpdate",
+            TransactionType::ValidatorStake { .. } => "ValidatorStake",
             TransactionType::EnvironmentalData { .. } => "EnvironmentalData",
             TransactionType::MiningReward { .. } => "MiningReward",
             TransactionType::MarketplaceTransaction { .. } => "MarketplaceTransaction",
+            TransactionType::CertifierRegistration { .. } => "CertifierRegistration",
+            TransactionType::CertifierDeactivation { .. } => "CertifierDeactivation",
             TransactionType::CredentialVerification { .. } => "CredentialVerification",
             TransactionType::EnvironmentalImpact { .. } => "EnvironmentalImpact",
             TransactionType::CommissionPayment { .. } => "CommissionPayment",
+            TransactionType::CarbonProjectRegistration { .. } => "CarbonProjectRegistration",
+            TransactionType::CarbonCreditIssuance { .. } => "CarbonCreditIssuance",
+            TransactionType::CarbonCreditTransfer { .. } => "CarbonCreditTransfer",
+            TransactionType::CarbonCreditRetirement { .. } => "CarbonCreditRetirement",
+        }
+    }
+    
+    /// The address this transaction credits tokens to, and how many. Treasury
+    /// disbursements only credit their recipient once the treasury accepts them.
+    pub fn balance_credit(&self) -> Option<(&str, f64)> {
+        match &self.transaction_type {
+            TransactionType::CarbonCredit { amount, .. } | TransactionType::MiningReward { amount } => Some((&self.from, *amount)),
+            _ => None,
+        }
+    }
+    
+    /// The recipient and amount of a treasury disbursement.
+    pub fn disbursement_credit(&self) -> Option<(&str, f64)> {
+        match &self.transaction_type {
+            TransactionType::TreasuryDisbursement { amount, recipient, .. } => Some((recipient, *amount)),
+            _ => None,
         }
     }
     
@@ -200,13 +553,72 @@ impl Transaction {
         }
     }
     
-    pub fn sign(&mut self, _private_key: &str) {
-        // This function could be used to sign a transaction if necessary.
+    /// Sets the topic of a proposal creation; call before signing.
+    pub fn set_proposal_topic(&mut self, topic: GovernanceTopic) -> Result<(), String> {
+        match self.transaction_type {
+            TransactionType::GovernanceProposalCreation { topic: ref mut current, .. } => {
+                *current = topic;
+                Ok(())
+            }
+            _ => Err(format!("Transaction {} is not a proposal creation", self.id)),
+        }
+    }
+    
+    /// Switches a proposal creation to a voting mode such as commit-reveal; call before signing.
+    pub fn set_voting_mode(&mut self, voting_mode: VotingMode) -> Result<(), String> {
+        match self.transaction_type {
+            TransactionType::GovernanceProposalCreation { voting_mode: ref mut current, .. } => {
+                *current = voting_mode;
+                Ok(())
+            }
+            _ => Err(format!("Transaction {} is not a proposal creation", self.id)),
+        }
+    }
+    
+    /// Restricts a proposal creation to certain sectors; call before signing.
+    pub fn set_sector_scope(&mut self, sector_scope: SectorScope) -> Result<(), String> {
+        match self.transaction_type {
+            TransactionType::GovernanceProposalCreation { sector_scope: ref mut current, .. } => {
+                *current = sector_scope;
+                Ok(())
+            }
+            _ => Err(format!("Transaction {} is not a proposal creation", self.id)),
+        }
+    }
+    
+    /// Attaches the action a proposal applies once passed; call before signing.
+    pub fn set_proposal_action(&mut self, action: GovernanceAction) -> Result<(), String> {
+        match self.transaction_type {
+            TransactionType::GovernanceProposalCreation { action: ref mut current, .. } => {
+                *current = Some(action);
+                Ok(())
+            }
+            _ => Err(format!("Transaction {} is not a proposal creation", self.id)),
+        }
+    }
+    
+    /// Bytes covered by the signature: everything except the signature itself.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        format!(
+            "{}|{}|{}|{}",
+            self.id,
+            self.from,
+            self.timestamp,
+            serde_json::to_string(&self.transaction_type).unwrap_or_default()
+        )
+        .into_bytes()
+    }
+    
+    pub fn sign(&mut self, private_key: &str) -> Result<(), String> {
+        self.signature = Some(crate::signing::sign_message(private_key, &self.signing_payload())?);
+        Ok(())
     }
 
-    pub fn verify_signature(&self, _public_key: &str) -> bool {
-        // This function could be used to verify a transaction signature if necessary.
-        true
+    pub fn verify_signature(&self, public_key: &str) -> bool {
+        match &self.signature {
+            Some(signature) => crate::signing::verify_message(public_key, &self.signing_payload(), signature),
+            None => false,
+        }
     }
 }
 
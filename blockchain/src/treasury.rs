@@ -0,0 +1,504 @@
+//! Community treasury funded by a share of every block reward. Grants are
+//! committed by passed treasury proposals and paid out in tranches, each
+//! released only against `EnvironmentalImpact` evidence that meets its milestone.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use crate::block::Block;
+use crate::blockchain::{self, Blockchain};
+use crate::did::DidRegistry;
+use crate::governance::{GovernanceAction, GovernanceParams, GovernanceRegistry};
+use crate::transaction::{Transaction, TransactionType};
+
+pub const TREASURY_ADDRESS: &str = "ecogov_treasury";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TreasurySource {
+    BlockReward { block_index: u64 },
+}
+
+/// Cumulative `EnvironmentalImpact` a project must report before a tranche is paid.
+/// A negative target (a reduction) is met at or below it, a positive one at or above.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Milestone {
+    pub impact_type: String,
+    pub measurement_unit: String,
+    pub target_value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrantTranche {
+    pub amount: f64,
+    pub milestone: Milestone,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrantTerms {
+    pub grant_id: String,
+    /// Entity id the project reports `EnvironmentalImpact` under
+    pub project_id: String,
+    pub recipient: String,
+    pub tranches: Vec<GrantTranche>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GrantStatus {
+    Active,
+    Completed,
+    ClawedBack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Grant {
+    pub terms: GrantTerms,
+    pub proposal_id: String,
+    pub status: GrantStatus,
+    pub funded_at_height: u64,
+    pub released_tranches: usize,
+    pub released_amount: f64,
+    pub clawed_back_amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TreasuryFlowKind {
+    Deposit { source: TreasurySource },
+    GrantCommitted,
+    TrancheReleased { tranche_index: usize, evidence_tx_ids: Vec<String> },
+    Clawback { reason: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryFlow {
+    pub kind: TreasuryFlowKind,
+    pub grant_id: Option<String>,
+    pub amount: f64,
+    pub transaction_id: String,
+    pub block_height: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryReport {
+    pub balance: f64,
+    pub reserved: f64,
+    pub available: f64,
+    pub total_deposits: f64,
+    pub total_committed: f64,
+    pub total_released: f64,
+    pub total_clawed_back: f64,
+    pub active_grants: usize,
+    pub flows: Vec<TreasuryFlow>,
+}
+
+/// Impact evidence indexed from mined blocks.
+#[derive(Debug, Clone)]
+struct ImpactEvidence {
+    entity_id: String,
+    impact_type: String,
+    measurement_unit: String,
+    impact_value: f64,
+    block_height: u64,
+    /// Sender whose DID signature verified, if any
+    signed_by: Option<String>,
+}
+
+impl GrantTerms {
+    pub fn total_amount(&self) -> f64 {
+        self.tranches.iter().fold(0.0, |total, tranche| total + tranche.amount)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.grant_id.trim().is_empty() || self.project_id.trim().is_empty() || self.recipient.trim().is_empty() {
+            return Err("Grant needs an id, a project and a recipient".to_string());
+        }
+        if self.tranches.is_empty() {
+            return Err(format!("Grant {} has no tranches", self.grant_id));
+        }
+        for (index, tranche) in self.tranches.iter().enumerate() {
+            if !tranche.amount.is_finite() || tranche.amount <= 0.0 {
+                return Err(format!("Tranche {} of grant {} has an invalid amount", index, self.grant_id));
+            }
+            if !tranche.milestone.target_value.is_finite() || tranche.milestone.target_value == 0.0 {
+                return Err(format!("Tranche {} of grant {} has no milestone target", index, self.grant_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Milestone {
+    pub fn is_met(&self, cumulative_value: f64) -> bool {
+        if self.target_value < 0.0 {
+            cumulative_value <= self.target_value
+        } else {
+            cumulative_value >= self.target_value
+        }
+    }
+}
+
+impl Grant {
+    pub fn remaining_amount(&self) -> f64 {
+        self.terms.tranches[self.released_tranches..]
+            .iter()
+            .fold(0.0, |total, tranche| total + tranche.amount)
+    }
+}
+
+pub struct Treasury {
+    balance: f64,
+    reserved: f64,
+    grants: HashMap<String, Grant>,
+    flows: Vec<TreasuryFlow>,
+    evidence: HashMap<String, ImpactEvidence>, // transaction id -> impact
+    used_evidence: HashSet<String>,
+    mining_reward: f64, // block reward in effect, as set by mined executions
+    treasury_share: f64,
+    funded_blocks: HashSet<u64>, // blocks whose reward share was deposited
+    authority_key: Option<String>, // must sign every disbursement
+}
+
+impl Treasury {
+    pub fn new() -> Self {
+        Treasury {
+            balance: 0.0,
+            reserved: 0.0,
+            grants: HashMap::new(),
+            flows: Vec::new(),
+            evidence: HashMap::new(),
+            used_evidence: HashSet::new(),
+            mining_reward: blockchain::DEFAULT_MINING_REWARD,
+            treasury_share: blockchain::DEFAULT_TREASURY_SHARE,
+            funded_blocks: HashSet::new(),
+            authority_key: None,
+        }
+    }
+
+    pub fn with_authority_key(authority_key: String) -> Self {
+        Treasury {
+            authority_key: Some(authority_key),
+            ..Self::new()
+        }
+    }
+
+    /// Grants are only committed by executions the governance registry accepted,
+    /// so both are replayed side by side.
+    pub fn from_chain(blockchain: &Blockchain, params: GovernanceParams, dids: &DidRegistry, authority_key: &str) -> Self {
+        let mut governance = GovernanceRegistry::with_params(params);
+        let mut treasury = Self::with_authority_key(authority_key.to_string());
+        for block in blockchain.get_blocks() {
+            governance.apply_block(block);
+            let released = treasury.apply_block(block, &governance, dids);
+            governance.apply_treasury_releases(block, &released);
+        }
+        treasury
+    }
+
+    /// Applies a mined block after `governance` has applied it. Evidence counts
+    /// only when signed by its sender's DID. Returns the ids of transactions
+    /// that moved treasury funds.
+    pub fn apply_block(&mut self, block: &Block, governance: &GovernanceRegistry, dids: &DidRegistry) -> Vec<String> {
+        let height = block.index as u64;
+        let mut applied = Vec::new();
+        for tx in &block.transactions {
+            if let TransactionType::EnvironmentalImpact { entity_id, impact_type, impact_value, measurement_unit, .. } = &tx.transaction_type {
+                self.evidence.insert(tx.id.clone(), ImpactEvidence {
+                    entity_id: entity_id.clone(),
+                    impact_type: impact_type.clone(),
+                    measurement_unit: measurement_unit.clone(),
+                    impact_value: *impact_value,
+                    block_height: height,
+                    signed_by: dids.verify_transaction(tx).ok().map(|_| tx.from.clone()),
+                });
+            }
+        }
+        for tx in &block.transactions {
+            // Invalid transactions stay on chain but never move funds
+            if let Ok(true) = self.apply_transaction(tx, height, block.timestamp, governance) {
+                applied.push(tx.id.clone());
+            }
+        }
+
+        // Reward changes mined here fund the treasury from the next block on
+        for change in governance.get_parameter_changes().iter().filter(|change| change.block_height == height) {
+            match change.applied {
+                GovernanceAction::SetMiningReward { reward } => self.mining_reward = reward,
+                GovernanceAction::SetTreasuryShare { share } => self.treasury_share = share,
+                _ => {}
+            }
+        }
+        applied
+    }
+
+    fn apply_transaction(&mut self, tx: &Transaction, height: u64, timestamp: u64, governance: &GovernanceRegistry) -> Result<bool, String> {
+        let flow = match &tx.transaction_type {
+            TransactionType::TreasuryDeposit { amount, source } => {
+                self.validate_deposit(tx, *amount, source, height)?;
+                self.funded_blocks.insert(height);
+                self.balance += amount;
+                TreasuryFlow {
+                    kind: TreasuryFlowKind::Deposit { source: source.clone() },
+                    grant_id: None,
+                    amount: *amount,
+                    transaction_id: tx.id.clone(),
+                    block_height: height,
+                    timestamp,
+                }
+            }
+            TransactionType::GovernanceExecution { proposal_id, applied, .. } => {
+                // Only executions the governance registry accepted in this block count
+                if !governance.get_parameter_changes().iter().any(|change| change.transaction_id == tx.id) {
+                    return Err(format!("Execution {} was not accepted by governance", tx.id));
+                }
+                match applied {
+                    GovernanceAction::FundGrant { terms } => self.commit_grant(tx, proposal_id, terms, height, timestamp)?,
+                    GovernanceAction::ClawbackGrant { grant_id, reason } => self.claw_back(tx, grant_id, reason, height, timestamp)?,
+                    _ => return Ok(false),
+                }
+            }
+            TransactionType::TreasuryDisbursement { grant_id, tranche_index, amount, recipient, evidence_tx_ids } => {
+                self.require_authority(tx)?;
+                self.validate_disbursement(&tx.from, grant_id, *tranche_index, *amount, recipient, evidence_tx_ids)?;
+                let grant = self.grants.get_mut(grant_id).unwrap();
+                grant.released_tranches += 1;
+                grant.released_amount += amount;
+                if grant.released_tranches == grant.terms.tranches.len() {
+                    grant.status = GrantStatus::Completed;
+                }
+                self.balance -= amount;
+                self.reserved -= amount;
+                self.used_evidence.extend(evidence_tx_ids.iter().cloned());
+                TreasuryFlow {
+                    kind: TreasuryFlowKind::TrancheReleased {
+                        tranche_index: *tranche_index,
+                        evidence_tx_ids: evidence_tx_ids.clone(),
+                    },
+                    grant_id: Some(grant_id.clone()),
+                    amount: *amount,
+                    transaction_id: tx.id.clone(),
+                    block_height: height,
+                    timestamp,
+                }
+            }
+            _ => return Ok(false),
+        };
+
+        self.flows.push(flow);
+        Ok(true)
+    }
+
+    /// A block funds the treasury once, with exactly the reward share governance
+    /// set for it, so deposits need no signature.
+    fn validate_deposit(&self, tx: &Transaction, amount: f64, source: &TreasurySource, height: u64) -> Result<(), String> {
+        if tx.from != TREASURY_ADDRESS {
+            return Err(format!("{} cannot deposit into the treasury", tx.from));
+        }
+        let TreasurySource::BlockReward { block_index } = source;
+        if *block_index != height {
+            return Err(format!("Deposit {} claims the reward of block {} but was mined in block {}", tx.id, block_index, height));
+        }
+        if self.funded_blocks.contains(&height) {
+            return Err(format!("Block {} already funded the treasury", height));
+        }
+        let expected = self.mining_reward * self.treasury_share;
+        if amount != expected {
+            return Err(format!("Deposit {} of {:.2} does not match the {:.2} reward share of block {}", tx.id, amount, expected, height));
+        }
+        Ok(())
+    }
+
+    fn commit_grant(&mut self, tx: &Transaction, proposal_id: &str, terms: &GrantTerms, height: u64, timestamp: u64) -> Result<TreasuryFlow, String> {
+        self.check_grant_funding(terms)?;
+        let total = terms.total_amount();
+        self.reserved += total;
+        self.grants.insert(terms.grant_id.clone(), Grant {
+            terms: terms.clone(),
+            proposal_id: proposal_id.to_string(),
+            status: GrantStatus::Active,
+            funded_at_height: height,
+            released_tranches: 0,
+            released_amount: 0.0,
+            clawed_back_amount: 0.0,
+        });
+
+        Ok(TreasuryFlow {
+            kind: TreasuryFlowKind::GrantCommitted,
+            grant_id: Some(terms.grant_id.clone()),
+            amount: total,
+            transaction_id: tx.id.clone(),
+            block_height: height,
+            timestamp,
+        })
+    }
+
+    fn claw_back(&mut self, tx: &Transaction, grant_id: &str, reason: &str, height: u64, timestamp: u64) -> Result<TreasuryFlow, String> {
+        self.check_clawback(grant_id)?;
+        let grant = self.grants.get_mut(grant_id).unwrap();
+        let amount = grant.remaining_amount();
+        grant.status = GrantStatus::ClawedBack;
+        grant.clawed_back_amount = amount;
+        self.reserved -= amount;
+
+        Ok(TreasuryFlow {
+            kind: TreasuryFlowKind::Clawback { reason: reason.to_string() },
+            grant_id: Some(grant_id.to_string()),
+            amount,
+            transaction_id: tx.id.clone(),
+            block_height: height,
+            timestamp,
+        })
+    }
+
+    /// The sender must be the treasury and sign with its configured key.
+    fn require_authority(&self, tx: &Transaction) -> Result<(), String> {
+        if tx.from != TREASURY_ADDRESS {
+            return Err(format!("{} cannot disburse treasury funds", tx.from));
+        }
+        let authority_key = self.authority_key
+            .as_ref()
+            .ok_or_else(|| "No treasury key is configured".to_string())?;
+        if !tx.verify_signature(authority_key) {
+            return Err(format!("Transaction {} is not signed by the treasury", tx.id));
+        }
+        Ok(())
+    }
+
+    /// A new grant needs an unused id and enough unreserved funds for every tranche.
+    pub fn check_grant_funding(&self, terms: &GrantTerms) -> Result<(), String> {
+        terms.validate()?;
+        if self.grants.contains_key(&terms.grant_id) {
+            return Err(format!("Grant {} already exists", terms.grant_id));
+        }
+        let total = terms.total_amount();
+        if total > self.available_balance() {
+            return Err(format!(
+                "Treasury has {:.2} available, grant {} needs {:.2}",
+                self.available_balance(), terms.grant_id, total
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check_clawback(&self, grant_id: &str) -> Result<(), String> {
+        match self.grants.get(grant_id) {
+            Some(grant) if grant.status == GrantStatus::Active => Ok(()),
+            Some(grant) => Err(format!("Grant {} is {:?}", grant_id, grant.status)),
+            None => Err(format!("Grant {} not found", grant_id)),
+        }
+    }
+
+    /// Checks that a disbursement pays the next tranche to the grant's recipient
+    /// and that its evidence is signed by the grantee, mined after funding, unused,
+    /// and meets the milestone.
+    pub fn validate_disbursement(
+        &self,
+        from: &str,
+        grant_id: &str,
+        tranche_index: usize,
+        amount: f64,
+        recipient: &str,
+        evidence_tx_ids: &[String],
+    ) -> Result<(), String> {
+        if from != TREASURY_ADDRESS {
+            return Err(format!("{} cannot disburse treasury funds", from));
+        }
+        let grant = self.grants.get(grant_id).ok_or_else(|| format!("Grant {} not found", grant_id))?;
+        if grant.status != GrantStatus::Active {
+            return Err(format!("Grant {} is {:?}", grant_id, grant.status));
+        }
+        if tranche_index != grant.released_tranches {
+            return Err(format!("Grant {} is due tranche {}, not {}", grant_id, grant.released_tranches, tranche_index));
+        }
+        let tranche = &grant.terms.tranches[tranche_index];
+        if amount != tranche.amount || recipient != grant.terms.recipient {
+            return Err(format!("Disbursement does not match tranche {} of grant {}", tranche_index, grant_id));
+        }
+
+        let milestone = &tranche.milestone;
+        let mut cumulative_value = 0.0;
+        let mut seen = HashSet::new();
+        for evidence_tx_id in evidence_tx_ids {
+            if !seen.insert(evidence_tx_id) || self.used_evidence.contains(evidence_tx_id) {
+                return Err(format!("Evidence {} was already used", evidence_tx_id));
+            }
+            let evidence = self.evidence
+                .get(evidence_tx_id)
+                .ok_or_else(|| format!("Evidence {} is not a mined EnvironmentalImpact", evidence_tx_id))?;
+            if evidence.entity_id != grant.terms.project_id
+                || evidence.impact_type != milestone.impact_type
+                || evidence.measurement_unit != milestone.measurement_unit
+            {
+                return Err(format!("Evidence {} does not report {} for {}", evidence_tx_id, milestone.impact_type, grant.terms.project_id));
+            }
+            if evidence.signed_by.as_deref() != Some(grant.terms.recipient.as_str()) {
+                return Err(format!("Evidence {} is not signed by grantee {}", evidence_tx_id, grant.terms.recipient));
+            }
+            if evidence.block_height < grant.funded_at_height {
+                return Err(format!("Evidence {} predates grant {}", evidence_tx_id, grant_id));
+            }
+            cumulative_value += evidence.impact_value;
+        }
+
+        if !milestone.is_met(cumulative_value) {
+            return Err(format!(
+                "Evidence totals {:.2} {}, milestone for tranche {} is {:.2}",
+                cumulative_value, milestone.measurement_unit, tranche_index, milestone.target_value
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn get_balance(&self) -> f64 {
+        self.balance
+    }
+
+    /// Funds not committed to any grant.
+    pub fn available_balance(&self) -> f64 {
+        self.balance - self.reserved
+    }
+
+    pub fn get_authority_key(&self) -> Option<&String> {
+        self.authority_key.as_ref()
+    }
+
+    pub fn get_grant(&self, grant_id: &str) -> Option<&Grant> {
+        self.grants.get(grant_id)
+    }
+
+    pub fn get_grants(&self) -> Vec<&Grant> {
+        self.grants.values().collect()
+    }
+
+    pub fn get_flows(&self) -> &Vec<TreasuryFlow> {
+        &self.flows
+    }
+
+    /// Totals for every flow mined between `from_height` and `to_height`, inclusive.
+    pub fn report(&self, from_height: u64, to_height: u64) -> TreasuryReport {
+        let flows: Vec<TreasuryFlow> = self.flows
+            .iter()
+            .filter(|flow| flow.block_height >= from_height && flow.block_height <= to_height)
+            .cloned()
+            .collect();
+        let total = |matches: fn(&TreasuryFlowKind) -> bool| {
+            flows.iter().filter(|flow| matches(&flow.kind)).fold(0.0, |total, flow| total + flow.amount)
+        };
+
+        TreasuryReport {
+            balance: self.balance,
+            reserved: self.reserved,
+            available: self.available_balance(),
+            total_deposits: total(|kind| matches!(kind, TreasuryFlowKind::Deposit { .. })),
+            total_committed: total(|kind| matches!(kind, TreasuryFlowKind::GrantCommitted)),
+            total_released: total(|kind| matches!(kind, TreasuryFlowKind::TrancheReleased { .. })),
+            total_clawed_back: total(|kind| matches!(kind, TreasuryFlowKind::Clawback { .. })),
+            active_grants: self.grants.values().filter(|grant| grant.status == GrantStatus::Active).count(),
+            flows,
+        }
+    }
+}
+
+impl Default for Treasury {
+    fn default() -> Self {
+        Self::new()
+    }
+}
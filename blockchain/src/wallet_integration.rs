@@ -113,6 +113,14 @@ impl WalletIntegration {
         self.config.enabled = !self.config.enabled;
     }
 
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.config.enabled = enabled;
+    }
+
+    pub fn set_commission_rate(&mut self, commission_rate: f64) {
+        self.config.commission_rate = commission_rate;
+    }
+
     pub fn get_earnings_summary(&self) -> WalletEarningsSummary {
         WalletEarningsSummary {
             wallet_address: self.config.owner_address.clone(),
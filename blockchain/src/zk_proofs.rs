@@ -0,0 +1,275 @@
+//! Zero-knowledge primitives over the Ristretto group: Schnorr proofs that the
+//! prover knows an identity secret, and Pedersen commitments that hide amounts.
+//! Points and scalars travel as hex strings. Proofs are made non-interactive with
+//! a SHA-512 challenge and verify from the proof and public inputs alone.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+/// Amounts are committed in millionths so fractional credits stay exact.
+pub const AMOUNT_SCALE: f64 = 1_000_000.0;
+
+const SCHNORR_DOMAIN: &[u8] = b"lightchain/schnorr/v1";
+const OPENING_DOMAIN: &[u8] = b"lightchain/pedersen-opening/v1";
+const PEDERSEN_H_DOMAIN: &[u8] = b"lightchain/pedersen-h/v1";
+
+#[derive(Debug, Clone)]
+pub struct IdentityKeyPair {
+    pub secret: String,
+    pub public_key: String,
+}
+
+/// Proof of knowledge of the secret behind a public key, bound to a message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchnorrProof {
+    pub commitment: String,
+    pub response: String,
+}
+
+/// `value·G + blinding·H`; reveals nothing about the value without the opening.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PedersenCommitment {
+    pub point: String,
+}
+
+/// The secret values behind a commitment, kept by the committer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PedersenOpening {
+    pub units: u64,
+    pub blinding: String,
+}
+
+/// Proof that the prover can open a commitment, without revealing the opening.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpeningProof {
+    pub commitment: String,
+    pub value_response: String,
+    pub blinding_response: String,
+}
+
+pub fn generate_identity_keypair() -> IdentityKeyPair {
+    let secret = Scalar::random(&mut OsRng);
+    IdentityKeyPair {
+        secret: hex::encode(secret.to_bytes()),
+        public_key: encode_point(&(secret * RISTRETTO_BASEPOINT_POINT)),
+    }
+}
+
+pub fn public_key_from_secret(secret: &str) -> Result<String, String> {
+    let secret = parse_scalar(secret)?;
+    Ok(encode_point(&(secret * RISTRETTO_BASEPOINT_POINT)))
+}
+
+pub fn verify_public_key(public_key: &str) -> Result<(), String> {
+    parse_point(public_key).map(|_| ())
+}
+
+pub fn prove_knowledge(secret: &str, message: &[u8]) -> Result<SchnorrProof, String> {
+    let secret = parse_scalar(secret)?;
+    let public_key = secret * RISTRETTO_BASEPOINT_POINT;
+    let nonce = Scalar::random(&mut OsRng);
+    let commitment = nonce * RISTRETTO_BASEPOINT_POINT;
+    let challenge = challenge_scalar(SCHNORR_DOMAIN, &[&public_key, &commitment], message);
+
+    Ok(SchnorrProof {
+        commitment: encode_point(&commitment),
+        response: hex::encode((nonce + challenge * secret).to_bytes()),
+    })
+}
+
+/// Checks `response·G == commitment + challenge·public_key`.
+pub fn verify_knowledge(public_key: &str, message: &[u8], proof: &SchnorrProof) -> bool {
+    let (public_key, commitment, response) = match (
+        parse_point(public_key),
+        parse_point(&proof.commitment),
+        parse_scalar(&proof.response),
+    ) {
+        (Ok(public_key), Ok(commitment), Ok(response)) => (public_key, commitment, response),
+        _ => return false,
+    };
+    let challenge = challenge_scalar(SCHNORR_DOMAIN, &[&public_key, &commitment], message);
+    response * RISTRETTO_BASEPOINT_POINT == commitment + challenge * public_key
+}
+
+pub fn amount_to_units(amount: f64) -> Result<u64, String> {
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(format!("Cannot commit to amount {}", amount));
+    }
+    let units = (amount * AMOUNT_SCALE).round();
+    if units > u64::MAX as f64 {
+        return Err(format!("Amount {} is too large to commit to", amount));
+    }
+    Ok(units as u64)
+}
+
+pub fn commit_amount(amount: f64) -> Result<(PedersenCommitment, PedersenOpening), String> {
+    let units = amount_to_units(amount)?;
+    let blinding = Scalar::random(&mut OsRng);
+    let commitment = PedersenCommitment {
+        point: encode_point(&pedersen_point(units, &blinding)),
+    };
+    let opening = PedersenOpening {
+        units,
+        blinding: hex::encode(blinding.to_bytes()),
+    };
+    Ok((commitment, opening))
+}
+
+pub fn verify_opening(commitment: &PedersenCommitment, opening: &PedersenOpening) -> bool {
+    match (parse_point(&commitment.point), parse_scalar(&opening.blinding)) {
+        (Ok(point), Ok(blinding)) => point == pedersen_point(opening.units, &blinding),
+        _ => false,
+    }
+}
+
+/// Commitments add homomorphically: the sum opens to the summed amounts and blindings.
+pub fn add_commitments(a: &PedersenCommitment, b: &PedersenCommitment) -> Result<PedersenCommitment, String> {
+    Ok(PedersenCommitment {
+        point: encode_point(&(parse_point(&a.point)? + parse_point(&b.point)?)),
+    })
+}
+
+pub fn prove_opening(commitment: &PedersenCommitment, opening: &PedersenOpening, message: &[u8]) -> Result<OpeningProof, String> {
+    if !verify_opening(commitment, opening) {
+        return Err("Opening does not match the commitment".to_string());
+    }
+    let point = parse_point(&commitment.point)?;
+    let value = Scalar::from(opening.units);
+    let blinding = parse_scalar(&opening.blinding)?;
+
+    let value_nonce = Scalar::random(&mut OsRng);
+    let blinding_nonce = Scalar::random(&mut OsRng);
+    let nonce_commitment = value_nonce * RISTRETTO_BASEPOINT_POINT + blinding_nonce * pedersen_h();
+    let challenge = challenge_scalar(OPENING_DOMAIN, &[&point, &nonce_commitment], message);
+
+    Ok(OpeningProof {
+        commitment: encode_point(&nonce_commitment),
+        value_response: hex::encode((value_nonce + challenge * value).to_bytes()),
+        blinding_response: hex::encode((blinding_nonce + challenge * blinding).to_bytes()),
+    })
+}
+
+/// Checks `value_response·G + blinding_response·H == commitment + challenge·C`.
+pub fn verify_opening_proof(commitment: &PedersenCommitment, message: &[u8], proof: &OpeningProof) -> bool {
+    let (point, nonce_commitment, value_response, blinding_response) = match (
+        parse_point(&commitment.point),
+        parse_point(&proof.commitment),
+        parse_scalar(&proof.value_response),
+        parse_scalar(&proof.blinding_response),
+    ) {
+        (Ok(point), Ok(nonce_commitment), Ok(value_response), Ok(blinding_response)) => {
+            (point, nonce_commitment, value_response, blinding_response)
+        }
+        _ => return false,
+    };
+    let challenge = challenge_scalar(OPENING_DOMAIN, &[&point, &nonce_commitment], message);
+    value_response * RISTRETTO_BASEPOINT_POINT + blinding_response * pedersen_h() == nonce_commitment + challenge * point
+}
+
+fn pedersen_point(units: u64, blinding: &Scalar) -> RistrettoPoint {
+    Scalar::from(units) * RISTRETTO_BASEPOINT_POINT + blinding * pedersen_h()
+}
+
+/// Second generator with no known discrete log relative to G.
+fn pedersen_h() -> RistrettoPoint {
+    RistrettoPoint::from_uniform_bytes(&Sha512::digest(PEDERSEN_H_DOMAIN).into())
+}
+
+fn challenge_scalar(domain: &[u8], points: &[&RistrettoPoint], message: &[u8]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(domain);
+    for point in points {
+        hasher.update(point.compress().as_bytes());
+    }
+    hasher.update((message.len() as u64).to_le_bytes());
+    hasher.update(message);
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+pub(crate) fn encode_point(point: &RistrettoPoint) -> String {
+    hex::encode(point.compress().as_bytes())
+}
+
+pub(crate) fn parse_point(encoded: &str) -> Result<RistrettoPoint, String> {
+    let bytes: [u8; 32] = hex::decode(encoded)
+        .map_err(|_| "Point is not valid hex".to_string())?
+        .try_into()
+        .map_err(|_| "Point must be 32 bytes".to_string())?;
+    CompressedRistretto(bytes)
+        .decompress()
+        .ok_or_else(|| "Point is not a valid Ristretto encoding".to_string())
+}
+
+pub(crate) fn parse_scalar(encoded: &str) -> Result<Scalar, String> {
+    let bytes: [u8; 32] = hex::decode(encoded)
+        .map_err(|_| "Scalar is not valid hex".to_string())?
+        .try_into()
+        .map_err(|_| "Scalar must be 32 bytes".to_string())?;
+    Option::from(Scalar::from_canonical_bytes(bytes))
+        .ok_or_else(|| "Scalar is not canonical".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knowledge_proof_round_trips() {
+        let keys = generate_identity_keypair();
+        let proof = prove_knowledge(&keys.secret, b"message").unwrap();
+        assert!(verify_knowledge(&keys.public_key, b"message", &proof));
+        assert_eq!(public_key_from_secret(&keys.secret).unwrap(), keys.public_key);
+    }
+
+    #[test]
+    fn knowledge_proof_rejects_wrong_message_key_or_response() {
+        let keys = generate_identity_keypair();
+        let proof = prove_knowledge(&keys.secret, b"message").unwrap();
+        assert!(!verify_knowledge(&keys.public_key, b"other message", &proof));
+        assert!(!verify_knowledge(&generate_identity_keypair().public_key, b"message", &proof));
+
+        let mut tampered = proof.clone();
+        tampered.response = hex::encode((parse_scalar(&proof.response).unwrap() + Scalar::ONE).to_bytes());
+        assert!(!verify_knowledge(&keys.public_key, b"message", &tampered));
+    }
+
+    #[test]
+    fn opening_proof_round_trips() {
+        let (commitment, opening) = commit_amount(12.5).unwrap();
+        assert!(verify_opening(&commitment, &opening));
+        let proof = prove_opening(&commitment, &opening, b"message").unwrap();
+        assert!(verify_opening_proof(&commitment, b"message", &proof));
+        assert!(!verify_opening_proof(&commitment, b"other message", &proof));
+
+        let wrong_amount = PedersenOpening { units: opening.units + 1, ..opening };
+        assert!(!verify_opening(&commitment, &wrong_amount));
+        assert!(prove_opening(&commitment, &wrong_amount, b"message").is_err());
+    }
+
+    #[test]
+    fn commitments_add_homomorphically() {
+        let (a, a_opening) = commit_amount(1.25).unwrap();
+        let (b, b_opening) = commit_amount(2.5).unwrap();
+        let sum = add_commitments(&a, &b).unwrap();
+        let blinding = parse_scalar(&a_opening.blinding).unwrap() + parse_scalar(&b_opening.blinding).unwrap();
+        let sum_opening = PedersenOpening {
+            units: amount_to_units(3.75).unwrap(),
+            blinding: hex::encode(blinding.to_bytes()),
+        };
+        assert_eq!(sum_opening.units, a_opening.units + b_opening.units);
+        assert!(verify_opening(&sum, &sum_opening));
+    }
+
+    #[test]
+    fn amount_to_units_rejects_invalid_amounts() {
+        assert_eq!(amount_to_units(1.5).unwrap(), 1_500_000);
+        assert!(amount_to_units(f64::NAN).is_err());
+        assert!(amount_to_units(f64::INFINITY).is_err());
+        assert!(amount_to_units(-0.01).is_err());
+        assert!(amount_to_units(u64::MAX as f64).is_err());
+    }
+}